@@ -1,10 +1,12 @@
 //! Cache Performance Benchmarks
 //!
-//! Run with: `cargo bench --bench cache_benchmark`
+//! Run with: `cargo bench --bench cache_benchmark --features bench`
 //!
 //! These benchmarks measure:
 //! - DashMap concurrent read/write performance
 //! - LRU cache eviction overhead
+//! - Cold cache eviction policies (LRU, TinyLFU, S3-FIFO) on skewed and
+//!   scan workloads, including their hit ratios
 //! - Thread-local cache access patterns
 //! - Cache hit/miss ratios impact
 
@@ -116,11 +118,11 @@ fn bench_cache_hits(c: &mut Criterion) {
     group.bench_function("l1_new_two_tier", |b| {
         let mut cache = HotCache::new();
         for i in 0..128u64 {
-            cache.insert(i, format!("<html>{}</html>", i).into());
+            cache.insert(i, &format!("/page/{}", i), format!("<html>{}</html>", i).into());
         }
 
         b.iter(|| {
-            let result = cache.peek(50);
+            let result = cache.peek(50, "/page/50");
             black_box(result)
         })
     });
@@ -130,11 +132,11 @@ fn bench_cache_hits(c: &mut Criterion) {
         let mut cache = HotCache::new();
         // Only insert 8 entries - all in ultra-hot array
         for i in 0..8u64 {
-            cache.insert(i, format!("<html>{}</html>", i).into());
+            cache.insert(i, &format!("/page/{}", i), format!("<html>{}</html>", i).into());
         }
 
         b.iter(|| {
-            let result = cache.peek(4); // Middle of array
+            let result = cache.peek(4, "/page/4"); // Middle of array
             black_box(result)
         })
     });
@@ -144,11 +146,11 @@ fn bench_cache_hits(c: &mut Criterion) {
         let mut cache = HotCache::new();
         // Insert 100 entries - first 92 will be in HashMap
         for i in 0..100u64 {
-            cache.insert(i, format!("<html>{}</html>", i).into());
+            cache.insert(i, &format!("/page/{}", i), format!("<html>{}</html>", i).into());
         }
 
         b.iter(|| {
-            let result = cache.peek(10); // Should be in HashMap
+            let result = cache.peek(10, "/page/10"); // Should be in HashMap
             black_box(result)
         })
     });
@@ -222,6 +224,113 @@ fn bench_lru_eviction(c: &mut Criterion) {
     group.finish();
 }
 
+/// Request trace with Zipf-distributed popularity
+///
+/// Every `scan_every`-th request (if any) is a one-off URL, like a crawler
+/// walking long-tail pages. Requests are URL hashes with their URLs.
+fn request_trace(
+    len: usize,
+    universe: usize,
+    exponent: f64,
+    scan_every: Option<usize>,
+) -> Vec<(u64, String)> {
+    use std::hash::{DefaultHasher, Hash, Hasher};
+
+    // Cumulative Zipf distribution over ranks 1..=universe
+    let mut cdf: Vec<f64> = (1..=universe)
+        .map(|rank| 1.0 / (rank as f64).powf(exponent))
+        .collect();
+    let total: f64 = cdf.iter().sum();
+    let mut acc = 0.0;
+    for p in cdf.iter_mut() {
+        acc += *p / total;
+        *p = acc;
+    }
+
+    let mut rng = 0x2545_F491_4F6C_DD1Du64;
+    (0..len)
+        .map(|i| {
+            let rank = match scan_every {
+                Some(n) if i % n == 0 => universe + i,
+                _ => {
+                    // xorshift64
+                    rng ^= rng << 13;
+                    rng ^= rng >> 7;
+                    rng ^= rng << 17;
+                    let u = (rng >> 11) as f64 / (1u64 << 53) as f64;
+                    cdf.partition_point(|&p| p < u)
+                }
+            };
+            // SipHash, like the cache's keyed URL hash
+            let url = format!("/p/{}", rank);
+            let mut hasher = DefaultHasher::new();
+            url.hash(&mut hasher);
+            (hasher.finish(), url)
+        })
+        .collect()
+}
+
+/// Replay a trace against a fresh cold cache, returning the hit count
+fn replay(
+    eviction: &rusty_ssr::cache::Eviction,
+    capacity: usize,
+    trace: &[(u64, String)],
+) -> usize {
+    use rusty_ssr::cache::ColdCache;
+
+    let mut cache = ColdCache::new(capacity);
+    cache.set_eviction(eviction);
+    let html: Arc<str> = "<html><body>page</body></html>".into();
+
+    let mut hits = 0;
+    for (url_hash, url) in trace {
+        if cache.get(*url_hash, url).is_some() {
+            hits += 1;
+        } else {
+            cache.insert(*url_hash, url, Arc::clone(&html));
+        }
+    }
+    hits
+}
+
+/// Benchmark cold cache eviction policies (hit ratio is printed per run)
+fn bench_eviction_policies(c: &mut Criterion) {
+    use rusty_ssr::cache::Eviction;
+
+    let mut group = c.benchmark_group("eviction_policies");
+
+    let capacity = 1_000;
+    let workloads = [
+        ("zipf", request_trace(100_000, 100_000, 0.9, None)),
+        ("zipf_scan", request_trace(100_000, 100_000, 0.9, Some(3))),
+    ];
+    let policies = [
+        ("lru", Eviction::Lru),
+        ("tinylfu", Eviction::TinyLfu),
+        ("s3fifo", Eviction::S3Fifo),
+    ];
+
+    for (workload, trace) in &workloads {
+        group.throughput(Throughput::Elements(trace.len() as u64));
+
+        for (name, eviction) in &policies {
+            let hits = replay(eviction, capacity, trace);
+            println!(
+                "{}/{}: hit ratio {:.1}%",
+                workload,
+                name,
+                hits as f64 * 100.0 / trace.len() as f64
+            );
+
+            group.bench_with_input(BenchmarkId::new(*workload, name), trace, |b, trace| {
+                b.iter(|| black_box(replay(eviction, capacity, trace)))
+            });
+        }
+    }
+
+    group.finish();
+}
+
 /// Benchmark Arc<str> vs String cloning
 fn bench_arc_vs_string(c: &mut Criterion) {
     use std::sync::Arc;
@@ -389,7 +498,7 @@ fn criterion_config() -> Criterion {
 criterion_group! {
     name = benches;
     config = criterion_config();
-    targets = bench_dashmap_concurrent, bench_dashmap_sharding, bench_dashmap_m4_comparison, bench_dashmap_shards, bench_cache_hits, bench_lru_eviction, bench_arc_vs_string
+    targets = bench_dashmap_concurrent, bench_dashmap_sharding, bench_dashmap_m4_comparison, bench_dashmap_shards, bench_cache_hits, bench_lru_eviction, bench_eviction_policies, bench_arc_vs_string
 }
 
 criterion_main!(benches);
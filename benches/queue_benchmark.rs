@@ -0,0 +1,122 @@
+//! Render Queue Benchmarks
+//!
+//! Run with: `cargo bench --bench queue_benchmark --features bench`
+//!
+//! These benchmarks measure:
+//! - Work distribution to pool workers: a `Mutex`-wrapped receiver against
+//!   the lock-free render queue (1-8 workers, many small jobs)
+//!
+//! The render queue is internal to the V8 pool; the `bench` feature exposes
+//! it to this benchmark only.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use std::time::Duration;
+
+/// Benchmark handing many small jobs to workers
+///
+/// Compares the former `Arc<Mutex<mpsc::Receiver>>` distribution, where each
+/// dequeue serializes on the lock, with the lock-free `RenderQueue`.
+fn bench_work_distribution(c: &mut Criterion) {
+    use rusty_ssr::v8_pool::{PushError, RenderQueue};
+    use rusty_ssr::{Priority, Scheduling};
+    use std::sync::{mpsc, Arc, Mutex};
+
+    const JOBS: u64 = 10_000;
+
+    // Stand-in for a tiny render
+    fn work(job: u64) -> u64 {
+        (0..32).fold(job, |acc, i| acc.wrapping_mul(31).wrapping_add(i))
+    }
+
+    let mut group = c.benchmark_group("work_distribution");
+    group.throughput(Throughput::Elements(JOBS));
+
+    for workers in [1, 2, 4, 8] {
+        group.bench_with_input(
+            BenchmarkId::new("mutex_receiver", workers),
+            &workers,
+            |b, &workers| {
+                b.iter(|| {
+                    let (tx, rx) = mpsc::sync_channel::<u64>(512);
+                    let rx = Arc::new(Mutex::new(rx));
+                    let handles: Vec<_> = (0..workers)
+                        .map(|_| {
+                            let rx = Arc::clone(&rx);
+                            std::thread::spawn(move || {
+                                let mut done = 0u64;
+                                loop {
+                                    let job = rx.lock().unwrap().recv();
+                                    match job {
+                                        Ok(job) => done += black_box(work(job)) & 1,
+                                        Err(_) => return done,
+                                    }
+                                }
+                            })
+                        })
+                        .collect();
+
+                    for job in 0..JOBS {
+                        tx.send(job).unwrap();
+                    }
+                    drop(tx);
+                    let done: u64 = handles.into_iter().map(|h| h.join().unwrap()).sum();
+                    black_box(done)
+                })
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("render_queue", workers),
+            &workers,
+            |b, &workers| {
+                b.iter(|| {
+                    let queue = Arc::new(RenderQueue::new(512, Scheduling::default()));
+                    let handles: Vec<_> = (0..workers)
+                        .map(|_| {
+                            let queue = Arc::clone(&queue);
+                            std::thread::spawn(move || {
+                                let mut done = 0u64;
+                                while let Some(job) = queue.pop() {
+                                    done += black_box(work(job)) & 1;
+                                }
+                                done
+                            })
+                        })
+                        .collect();
+
+                    for job in 0..JOBS {
+                        let mut job = job;
+                        while let Err(PushError::Full(rejected)) =
+                            queue.try_push(Priority::Interactive, job)
+                        {
+                            job = rejected;
+                            std::thread::yield_now();
+                        }
+                    }
+                    queue.close();
+                    let done: u64 = handles.into_iter().map(|h| h.join().unwrap()).sum();
+                    black_box(done)
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
+
+/// Configure criterion for detailed benchmarks
+fn criterion_config() -> Criterion {
+    Criterion::default()
+        .sample_size(100)
+        .measurement_time(Duration::from_secs(5))
+        .warm_up_time(Duration::from_secs(2))
+        .with_plots()
+}
+
+criterion_group! {
+    name = benches;
+    config = criterion_config();
+    targets = bench_work_distribution
+}
+
+criterion_main!(benches);
@@ -7,6 +7,7 @@
 //! - Render throughput (requests per second)
 //! - Latency distribution (p50, p99, p999)
 //! - Concurrent render performance
+//! - Many small renders through the V8 pool
 
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
 use std::time::Duration;
@@ -30,6 +31,7 @@ fn bench_pool_config(c: &mut Criterion) {
                 pin_threads: false,
                 request_timeout: Some(Duration::from_secs(30)),
                 render_function: "renderPage".to_string(),
+                ..Default::default()
             };
             black_box(config)
         })
@@ -205,6 +207,89 @@ fn bench_channel_throughput(c: &mut Criterion) {
     group.finish();
 }
 
+/// Bundle shared by the pool benchmarks
+///
+/// The lookup table stands in for the top-level setup of a real bundle.
+const POOL_BUNDLE: &str = r#"
+    const labels = new Map();
+    for (let i = 0; i < 50000; i++) labels.set('/item/' + i, 'Item ' + i);
+    globalThis.renderPage = (url) => '<p>' + url + '</p>';
+"#;
+
+/// Load [`POOL_BUNDLE`], which can only be done once per process
+fn init_pool_bundle() {
+    static INIT: std::sync::Once = std::sync::Once::new();
+    INIT.call_once(|| {
+        rusty_ssr::v8_pool::init_bundle_from_string(POOL_BUNDLE.to_string())
+            .expect("Failed to load benchmark bundle");
+    });
+}
+
+/// Benchmark many small renders through the V8 pool
+///
+/// To compare two revisions, run this with `--save-baseline before` on the
+/// first and `--baseline before` on the second.
+fn bench_pool_small_renders(c: &mut Criterion) {
+    use rusty_ssr::v8_pool::{V8Pool, V8PoolConfig};
+
+    const RENDERS: usize = 1000;
+
+    init_pool_bundle();
+    let pool = V8Pool::try_new(V8PoolConfig::default()).expect("Failed to create V8 pool");
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+
+    let mut group = c.benchmark_group("pool_small_renders");
+    group.throughput(Throughput::Elements(RENDERS as u64));
+
+    group.bench_function(BenchmarkId::new("render", RENDERS), |b| {
+        b.iter(|| {
+            runtime.block_on(async {
+                let renders = (0..RENDERS).map(|i| pool.render(format!("/item/{}", i)));
+                black_box(futures::future::join_all(renders).await)
+            })
+        })
+    });
+
+    group.finish();
+}
+
+/// Benchmark renders that each get a fresh isolate, against a shared one
+///
+/// `FreshRuntime` restores every isolate from a snapshot of the evaluated
+/// bundle. Compare with a revision that evaluated the bundle per render
+/// using `--save-baseline` / `--baseline`, as for the small renders.
+fn bench_fresh_runtime(c: &mut Criterion) {
+    use rusty_ssr::v8_pool::{V8Pool, V8PoolConfig};
+    use rusty_ssr::RenderIsolation;
+
+    const RENDERS: usize = 100;
+
+    init_pool_bundle();
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+
+    let mut group = c.benchmark_group("fresh_runtime");
+    group.throughput(Throughput::Elements(RENDERS as u64));
+
+    for isolation in [RenderIsolation::Shared, RenderIsolation::FreshRuntime] {
+        let pool = V8Pool::try_new(V8PoolConfig {
+            render_isolation: isolation,
+            ..Default::default()
+        })
+        .expect("Failed to create V8 pool");
+
+        group.bench_function(BenchmarkId::new(format!("{:?}", isolation), RENDERS), |b| {
+            b.iter(|| {
+                runtime.block_on(async {
+                    let renders = (0..RENDERS).map(|i| pool.render(format!("/item/{}", i)));
+                    black_box(futures::future::join_all(renders).await)
+                })
+            })
+        });
+    }
+
+    group.finish();
+}
+
 /// Configure criterion for detailed benchmarks
 fn criterion_config() -> Criterion {
     Criterion::default()
@@ -217,7 +302,8 @@ fn criterion_config() -> Criterion {
 criterion_group! {
     name = benches;
     config = criterion_config();
-    targets = bench_pool_config, bench_string_ops, bench_json_serialization, bench_channel_throughput
+    targets = bench_pool_config, bench_string_ops, bench_json_serialization, bench_channel_throughput,
+        bench_pool_small_renders, bench_fresh_runtime
 }
 
 criterion_main!(benches);
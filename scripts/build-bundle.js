@@ -14,7 +14,7 @@
  *
  * Options:
  *   --iife <name>     IIFE global name if your bundle exports via window/globalThis
- *   --esm             Bundle is ES module format (uses default export)
+ *   --esm             Bundle is ES module format (load with BundleFormat::Module)
  *   --render <name>   Name of render function in bundle (default: renderToString)
  *   --fn <name>       Name of global function to create (default: renderPage)
  */
@@ -38,6 +38,7 @@ Arguments:
 
 Options:
   --iife <name>     IIFE global name (e.g., --iife SSRBundle)
+  --esm             ES module bundle (load with BundleFormat::Module)
   --render <name>   Render function name in bundle (default: renderToString)
   --fn <name>       Global function name to expose (default: renderPage)
   --help, -h        Show this help
@@ -46,6 +47,9 @@ Examples:
   # Wrap IIFE bundle (Preact/React)
   node scripts/build-bundle.js dist/server.js ssr-bundle.js --iife SSRBundle
 
+  # ES module bundle that exports renderToString
+  node scripts/build-bundle.js dist/server.mjs ssr-bundle.mjs --esm --render renderToString
+
   # Simple passthrough (already has globalThis.renderPage)
   node scripts/build-bundle.js dist/server.js ssr-bundle.js
 `);
@@ -58,6 +62,7 @@ const outputPath = args[1];
 // Parse options
 const options = {
     iife: null,
+    esm: false,
     render: 'renderToString',
     fn: 'renderPage',
 };
@@ -65,6 +70,8 @@ const options = {
 for (let i = 2; i < args.length; i++) {
     if (args[i] === '--iife' && args[i + 1]) {
         options.iife = args[++i];
+    } else if (args[i] === '--esm') {
+        options.esm = true;
     } else if (args[i] === '--render' && args[i + 1]) {
         options.render = args[++i];
     } else if (args[i] === '--fn' && args[i + 1]) {
@@ -83,7 +90,17 @@ const inputCode = fs.readFileSync(inputPath, 'utf-8');
 // Generate wrapper
 let outputCode;
 
-if (options.iife) {
+if (options.esm) {
+    // ES module format: Rusty SSR loads the module and calls the export directly.
+    // Relative imports are resolved from the output directory, so chunks must be
+    // copied next to the output file.
+    outputCode = inputCode;
+    const exportPattern = new RegExp(`export\\s+(async\\s+)?(function|const|let|var)\\s+${options.render}\\b|export\\s*\\{[^}]*\\b${options.render}\\b[^}]*\\}`);
+    if (options.render !== 'default' && !exportPattern.test(inputCode)) {
+        console.warn(`Warning: could not find an export named "${options.render}" in ${inputPath}`);
+    }
+    console.log(`ES module bundle: configure BundleFormat::Module with render_function("${options.render}")`);
+} else if (options.iife) {
     // IIFE format: wrap and expose render function
     outputCode = `
 // ============ SSR Bundle (IIFE wrapped) ============
@@ -132,5 +149,5 @@ console.log(`
 Rusty SSR Bundle Created
   Input:  ${inputPath} (${inputSize} KB)
   Output: ${outputPath} (${outputSize} KB)
-  Global: ${options.fn}()
+  ${options.esm ? `Export: ${options.render}()` : `Global: ${options.fn}()`}
 `);
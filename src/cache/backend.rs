@@ -0,0 +1,133 @@
+//! Shared cache backends
+//!
+//! A [`CacheBackend`] is a cache shared between replicas, consulted by
+//! [`SsrCache::get`](super::SsrCache::get) after a local miss and written
+//! through on insert. Backend errors never fail a render; they are counted
+//! in [`CacheMetrics::remote_errors`](super::CacheMetrics::remote_errors).
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::io;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use futures::future::{self, BoxFuture, FutureExt};
+use parking_lot::Mutex;
+
+/// Async cache shared between SSR engine replicas
+///
+/// Keys are the SSR cache keys (URLs); values are rendered HTML.
+///
+/// # Example
+/// ```rust
+/// use std::sync::Arc;
+/// use rusty_ssr::cache::{MemoryBackend, SsrCache};
+///
+/// let shared = Arc::new(MemoryBackend::new());
+/// let cache = SsrCache::new(300).with_backend(shared);
+/// ```
+pub trait CacheBackend: Send + Sync + Debug {
+    /// Look up cached HTML
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, io::Result<Option<Arc<str>>>>;
+
+    /// Store HTML, expiring after `ttl` (`None` = no expiration)
+    fn set<'a>(
+        &'a self,
+        key: &'a str,
+        html: Arc<str>,
+        ttl: Option<Duration>,
+    ) -> BoxFuture<'a, io::Result<()>>;
+
+    /// Remove an entry
+    fn delete<'a>(&'a self, key: &'a str) -> BoxFuture<'a, io::Result<()>>;
+}
+
+/// Stored HTML and its expiry
+type MemoryEntry = (Arc<str>, Option<Instant>);
+
+/// In-process [`CacheBackend`]
+///
+/// Reference implementation, also useful to share one cache between several
+/// engines in the same process.
+#[derive(Debug, Default)]
+pub struct MemoryBackend {
+    entries: Mutex<HashMap<String, MemoryEntry>>,
+}
+
+impl MemoryBackend {
+    /// Create an empty backend
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of stored entries, including expired ones not yet looked up
+    pub fn len(&self) -> usize {
+        self.entries.lock().len()
+    }
+
+    /// Whether the backend holds no entries
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl CacheBackend for MemoryBackend {
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, io::Result<Option<Arc<str>>>> {
+        let mut entries = self.entries.lock();
+        let html = match entries.get(key) {
+            Some((_, Some(expires_at))) if *expires_at <= Instant::now() => {
+                entries.remove(key);
+                None
+            }
+            Some((html, _)) => Some(Arc::clone(html)),
+            None => None,
+        };
+        future::ready(Ok(html)).boxed()
+    }
+
+    fn set<'a>(
+        &'a self,
+        key: &'a str,
+        html: Arc<str>,
+        ttl: Option<Duration>,
+    ) -> BoxFuture<'a, io::Result<()>> {
+        let expires_at = ttl.map(|ttl| Instant::now() + ttl);
+        self.entries
+            .lock()
+            .insert(key.to_string(), (html, expires_at));
+        future::ready(Ok(())).boxed()
+    }
+
+    fn delete<'a>(&'a self, key: &'a str) -> BoxFuture<'a, io::Result<()>> {
+        self.entries.lock().remove(key);
+        future::ready(Ok(())).boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_memory_backend() {
+        let backend = MemoryBackend::new();
+        assert!(backend.get("/a").await.unwrap().is_none());
+
+        backend.set("/a", Arc::from("html"), None).await.unwrap();
+        assert_eq!(backend.get("/a").await.unwrap().as_deref(), Some("html"));
+
+        backend.delete("/a").await.unwrap();
+        assert!(backend.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_memory_backend_ttl() {
+        let backend = MemoryBackend::new();
+        backend
+            .set("/a", Arc::from("html"), Some(Duration::ZERO))
+            .await
+            .unwrap();
+        assert!(backend.get("/a").await.unwrap().is_none());
+        assert!(backend.is_empty());
+    }
+}
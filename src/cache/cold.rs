@@ -5,15 +5,27 @@
 //!
 //! Optimized with 128 shards to minimize contention at 8+ threads.
 //! Benchmarks show 1.8x improvement over default shard count.
+//!
+//! Entries are keyed by URL hash, and the stored URL is compared on
+//! lookup, so a hash collision is a miss rather than another page's HTML.
+//!
+//! Bounded by entry count and, optionally, by the total size of the
+//! cached pages in bytes. Evicts approximately least recently used entries
+//! unless an [`EvictionPolicy`] is set.
 
 use dashmap::DashMap;
 use std::collections::BinaryHeap;
-use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
+use super::eviction::{Eviction, EvictionPolicy};
+use super::inspect::{CacheEntryInfo, EntryStats, SnapshotEntry};
 use super::padded::CachePadded;
 
+#[cfg(feature = "cache-compression")]
+use super::compression::CompressedPage;
+
 /// Optimal shard count for 8+ concurrent threads.
 /// Benchmarked values: 16=51M, 32=57M, 64=59M, 128=60.6M, 256=60.3M elem/s
 const OPTIMAL_SHARD_COUNT: usize = 128;
@@ -23,21 +35,84 @@ const OPTIMAL_SHARD_COUNT: usize = 128;
 const EVICT_BATCH_PERCENT: usize = 2;
 const EVICT_BATCH_MIN: usize = 8;
 
+/// Approximate bookkeeping bytes per entry (map slot, entry, Arc headers)
+const ENTRY_OVERHEAD: usize =
+    std::mem::size_of::<(u64, CacheEntry)>() + 2 * std::mem::size_of::<[usize; 2]>();
+
 /// Cold cache entry with LRU metadata
+///
+/// `html` is only `None` for compressed-only entries.
 struct CacheEntry {
     url: Arc<str>,
-    html: Arc<str>,
+    html: Option<Arc<str>>,
+    #[cfg(feature = "cache-compression")]
+    compressed: Option<Arc<CompressedPage>>,
     last_access: AtomicU64,
-    created_at: Instant,
+    expires_at: Option<Instant>,
+    size: usize,
+    stats: Arc<EntryStats>,
+}
+
+impl CacheEntry {
+    fn is_expired(&self) -> bool {
+        self.expires_at.is_some_and(|at| Instant::now() >= at)
+    }
+
+    /// Raw HTML, decompressing compressed-only entries
+    fn html(&self) -> Option<Arc<str>> {
+        if let Some(html) = &self.html {
+            return Some(Arc::clone(html));
+        }
+
+        #[cfg(feature = "cache-compression")]
+        if let Some(compressed) = &self.compressed {
+            return match compressed.decompress() {
+                Ok(html) => Some(html),
+                Err(e) => {
+                    tracing::warn!("⚠️ Failed to decompress cached {}: {}", self.url, e);
+                    None
+                }
+            };
+        }
+
+        None
+    }
+
+    fn info(&self) -> CacheEntryInfo {
+        let mut info = CacheEntryInfo {
+            url: self.url.to_string(),
+            size_bytes: self.size,
+            expires_in_secs: self
+                .expires_at
+                .map(|at| at.saturating_duration_since(Instant::now()).as_secs()),
+            ..Default::default()
+        };
+        self.stats.describe(&mut info);
+
+        #[cfg(feature = "cache-compression")]
+        if let Some(compressed) = &self.compressed {
+            info.tags.extend(
+                compressed
+                    .encodings()
+                    .into_iter()
+                    .map(|encoding| format!("encoding:{}", encoding.as_str())),
+            );
+        }
+
+        info
+    }
 }
 
 /// Shared cold cache in RAM
 pub struct ColdCache {
     cache: DashMap<u64, CacheEntry>,
     max_entries: usize,
+    max_bytes: Option<usize>,
+    bytes: CachePadded<AtomicUsize>,
     access_counter: CachePadded<AtomicU64>,
     evicting: CachePadded<AtomicBool>,
     ttl: Option<Duration>,
+    policy: Option<Arc<dyn EvictionPolicy>>,
 }
 
 impl ColdCache {
@@ -47,9 +122,12 @@ impl ColdCache {
         Self {
             cache: DashMap::with_capacity_and_shard_amount(max_entries, OPTIMAL_SHARD_COUNT),
             max_entries,
+            max_bytes: None,
+            bytes: CachePadded::new(AtomicUsize::new(0)),
             access_counter: CachePadded::new(AtomicU64::new(0)),
             evicting: CachePadded::new(AtomicBool::new(false)),
             ttl: None,
+            policy: None,
         }
     }
 
@@ -58,6 +136,8 @@ impl ColdCache {
         Self {
             cache: DashMap::with_capacity_and_shard_amount(max_entries, OPTIMAL_SHARD_COUNT),
             max_entries,
+            max_bytes: None,
+            bytes: CachePadded::new(AtomicUsize::new(0)),
             access_counter: CachePadded::new(AtomicU64::new(0)),
             evicting: CachePadded::new(AtomicBool::new(false)),
             ttl: if ttl_secs > 0 {
@@ -65,62 +145,272 @@ impl ColdCache {
             } else {
                 None
             },
+            policy: None,
         }
     }
 
+    /// Bound the total size of cached entries in bytes
+    ///
+    /// Counts URL and HTML bytes plus a small per-entry overhead.
+    pub fn set_max_bytes(&mut self, max_bytes: usize) {
+        self.max_bytes = Some(max_bytes);
+    }
+
+    /// Replace the eviction policy
+    ///
+    /// Must be set before entries are inserted.
+    pub fn set_eviction(&mut self, eviction: &Eviction) {
+        self.policy = eviction.build(self.max_entries);
+    }
+
     /// Get HTML from cache
     ///
-    /// Returns None if not found or expired
+    /// Returns None if not found, expired or cached for another URL with the
+    /// same hash. Compressed-only entries are decompressed.
+    #[inline(always)]
+    pub fn get(&self, url_hash: u64, url: &str) -> Option<Arc<str>> {
+        self.get_with_expiry(url_hash, url).map(|(html, _)| html)
+    }
+
+    /// Get HTML from cache along with when it expires (`None` = never)
+    #[inline(always)]
+    pub fn get_with_expiry(&self, url_hash: u64, url: &str) -> Option<(Arc<str>, Option<Instant>)> {
+        let entry = self.get_entry(url_hash, url)?;
+        Some((entry.html()?, entry.expires_at))
+    }
+
+    /// Like [`ColdCache::get_with_expiry`], also returning the page's access stats
+    #[inline(always)]
+    pub(crate) fn get_with_stats(
+        &self,
+        url_hash: u64,
+        url: &str,
+    ) -> Option<(Arc<str>, Option<Instant>, Arc<EntryStats>)> {
+        let entry = self.get_entry(url_hash, url)?;
+        Some((entry.html()?, entry.expires_at, Arc::clone(&entry.stats)))
+    }
+
+    /// Access stats of a live entry, without marking it as used
+    pub(crate) fn stats(&self, url_hash: u64, url: &str) -> Option<Arc<EntryStats>> {
+        let entry = self.peek_entry(url_hash, url)?;
+        Some(Arc::clone(&entry.stats))
+    }
+
+    /// Metadata and HTML of a live entry, without marking it as used
+    pub fn inspect(&self, url_hash: u64, url: &str) -> Option<(CacheEntryInfo, Arc<str>)> {
+        let entry = self.peek_entry(url_hash, url)?;
+        Some((entry.info(), entry.html()?))
+    }
+
+    /// Metadata of the live entries whose URL starts with `prefix`, unsorted
+    ///
+    /// Does not mark entries as used.
+    pub fn inspect_all(&self, prefix: &str) -> Vec<CacheEntryInfo> {
+        self.cache
+            .iter()
+            .filter(|entry| entry.url.starts_with(prefix) && !entry.is_expired())
+            .map(|entry| entry.info())
+            .collect()
+    }
+
+    /// HTML and remaining TTL of every live entry
+    ///
+    /// Does not mark entries as used.
+    pub fn snapshot(&self) -> Vec<SnapshotEntry> {
+        self.cache
+            .iter()
+            .filter(|entry| !entry.is_expired())
+            .filter_map(|entry| {
+                Some(SnapshotEntry {
+                    url: entry.url.to_string(),
+                    html: entry.html()?.to_string(),
+                    ttl_secs: entry
+                        .expires_at
+                        .map(|at| at.saturating_duration_since(Instant::now()).as_secs()),
+                })
+            })
+            .collect()
+    }
+
+    /// Look up a live entry for `url` without marking it as used
+    fn peek_entry(
+        &self,
+        url_hash: u64,
+        url: &str,
+    ) -> Option<dashmap::mapref::one::Ref<'_, u64, CacheEntry>> {
+        self.cache
+            .get(&url_hash)
+            .filter(|entry| *entry.url == *url && !entry.is_expired())
+    }
+
+    /// Get the compressed variants of a page
+    #[cfg(all(feature = "cache-compression", feature = "v8-pool"))]
+    pub fn get_compressed(&self, url_hash: u64, url: &str) -> Option<Arc<CompressedPage>> {
+        self.get_entry(url_hash, url)?.compressed.clone()
+    }
+
+    /// Like [`ColdCache::get_compressed`], also returning the page's access stats
+    #[cfg(feature = "cache-compression")]
+    pub(crate) fn get_compressed_with_stats(
+        &self,
+        url_hash: u64,
+        url: &str,
+    ) -> Option<(Arc<CompressedPage>, Arc<EntryStats>)> {
+        let entry = self.get_entry(url_hash, url)?;
+        Some((entry.compressed.clone()?, Arc::clone(&entry.stats)))
+    }
+
+    /// Look up a live entry for `url` and mark it as recently used
     #[inline(always)]
-    pub fn get(&self, url_hash: u64) -> Option<Arc<str>> {
+    fn get_entry(
+        &self,
+        url_hash: u64,
+        url: &str,
+    ) -> Option<dashmap::mapref::one::Ref<'_, u64, CacheEntry>> {
+        if let Some(policy) = &self.policy {
+            policy.on_access(url_hash);
+        }
         let entry = self.cache.get(&url_hash)?;
 
+        // Hash collision: the slot holds another URL
+        if *entry.url != *url {
+            return None;
+        }
+
         // Check TTL
-        if let Some(ttl) = self.ttl {
-            if entry.created_at.elapsed() > ttl {
-                drop(entry);
-                self.cache.remove(&url_hash);
-                return None;
-            }
+        if entry.is_expired() {
+            drop(entry);
+            self.remove(url_hash, url);
+            return None;
         }
 
         // Update LRU counter
         let new_access = self.access_counter.fetch_add(1, Ordering::Relaxed);
         entry.last_access.store(new_access, Ordering::Relaxed);
 
-        Some(Arc::clone(&entry.html))
+        Some(entry)
     }
 
-    /// Insert HTML into cache with batch LRU eviction
+    /// Insert HTML into cache with batch eviction
     ///
-    /// Returns the number of evicted entries.
+    /// Returns the number of evicted entries. The eviction policy may
+    /// decline to cache a new page when the cache is full.
     pub fn insert(&self, url_hash: u64, url: &str, html: Arc<str>) -> usize {
-        let evicted = if self.cache.len() >= self.max_entries {
-            self.evict_batch()
-        } else {
-            0
-        };
+        self.insert_with_ttl(url_hash, url, html, None)
+    }
 
-        let new_access = self.access_counter.fetch_add(1, Ordering::Relaxed);
-        self.cache.insert(
+    /// Insert HTML that expires after `ttl` instead of the cache TTL
+    ///
+    /// `None` uses the cache TTL. Returns the number of evicted entries.
+    pub fn insert_with_ttl(
+        &self,
+        url_hash: u64,
+        url: &str,
+        html: Arc<str>,
+        ttl: Option<Duration>,
+    ) -> usize {
+        let size = url.len() + html.len() + ENTRY_OVERHEAD;
+        self.insert_entry(
+            url_hash,
+            CacheEntry {
+                url: Arc::from(url),
+                html: Some(html),
+                #[cfg(feature = "cache-compression")]
+                compressed: None,
+                last_access: AtomicU64::new(0),
+                expires_at: self.expires_at(ttl),
+                size,
+                stats: Arc::new(EntryStats::new()),
+            },
+        )
+    }
+
+    /// Insert compressed variants of a page, with or without its raw HTML
+    ///
+    /// `ttl` overrides the cache TTL like in [`ColdCache::insert_with_ttl`].
+    /// Returns the number of evicted entries.
+    #[cfg(feature = "cache-compression")]
+    pub fn insert_compressed(
+        &self,
+        url_hash: u64,
+        url: &str,
+        html: Option<Arc<str>>,
+        compressed: Arc<CompressedPage>,
+        ttl: Option<Duration>,
+    ) -> usize {
+        let size = url.len()
+            + html.as_ref().map_or(0, |html| html.len())
+            + compressed.size_bytes()
+            + ENTRY_OVERHEAD;
+        self.insert_entry(
             url_hash,
             CacheEntry {
                 url: Arc::from(url),
                 html,
-                last_access: AtomicU64::new(new_access),
-                created_at: Instant::now(),
+                compressed: Some(compressed),
+                last_access: AtomicU64::new(0),
+                expires_at: self.expires_at(ttl),
+                size,
+                stats: Arc::new(EntryStats::new()),
             },
-        );
+        )
+    }
+
+    fn expires_at(&self, ttl: Option<Duration>) -> Option<Instant> {
+        ttl.or(self.ttl).map(|ttl| Instant::now() + ttl)
+    }
+
+    fn insert_entry(&self, url_hash: u64, entry: CacheEntry) -> usize {
+        let size = entry.size;
+        if self.max_bytes.is_some_and(|max| size > max) {
+            // Evicting everything would not make room; drop any stale copy
+            self.remove_where(url_hash, |old| old.url == entry.url);
+            return 0;
+        }
+
+        let over_bytes = self
+            .max_bytes
+            .is_some_and(|max| self.size_bytes() + size > max);
+
+        let full = self.cache.len() >= self.max_entries || over_bytes;
+
+        if full {
+            if let Some(policy) = &self.policy {
+                if !self.cache.contains_key(&url_hash) && !policy.admit(url_hash) {
+                    return 0;
+                }
+            }
+        }
+
+        let evicted = if full { self.evict_batch(size) } else { 0 };
+
+        let new_access = self.access_counter.fetch_add(1, Ordering::Relaxed);
+        entry.last_access.store(new_access, Ordering::Relaxed);
+        self.bytes.fetch_add(size, Ordering::Relaxed);
+        let previous = self.cache.insert(url_hash, entry);
+        match previous {
+            Some(previous) => {
+                self.bytes.fetch_sub(previous.size, Ordering::Relaxed);
+            }
+            None => {
+                if let Some(policy) = &self.policy {
+                    policy.on_insert(url_hash);
+                }
+            }
+        }
 
         evicted
     }
 
-    /// Batch-evict the oldest entries.
+    /// Batch-evict the oldest entries (or the eviction policy's victims).
+    ///
+    /// At the entry limit, a whole batch of the oldest entries is evicted.
+    /// Over the byte budget, the oldest entries are evicted until the
+    /// incoming entry fits with a batch-sized margin, so a few large pages
+    /// can displace many small ones.
     ///
     /// Only one thread evicts at a time — others skip and proceed with insert.
-    /// Uses a bounded max-heap (O(batch) memory) to find the oldest entries
-    /// without allocating for the entire cache.
-    fn evict_batch(&self) -> usize {
+    fn evict_batch(&self, incoming: usize) -> usize {
         // Guard: only one thread evicts at a time to avoid thundering herd
         if self
             .evicting
@@ -130,9 +420,79 @@ impl ColdCache {
             return 0;
         }
 
-        let batch =
-            (self.max_entries * EVICT_BATCH_PERCENT / 100).max(EVICT_BATCH_MIN);
+        let batch = (self.max_entries * EVICT_BATCH_PERCENT / 100).max(EVICT_BATCH_MIN);
+        let mut evicted = 0;
 
+        if let Some(policy) = &self.policy {
+            evicted = self.evict_with_policy(policy.as_ref(), batch, incoming);
+            self.evicting.store(false, Ordering::Release);
+            return evicted;
+        }
+
+        if self.cache.len() >= self.max_entries {
+            for key in self.oldest_entries(batch) {
+                evicted += self.evict(key) as usize;
+            }
+        }
+
+        if let Some(max_bytes) = self.max_bytes {
+            let target = max_bytes
+                .saturating_sub(max_bytes * EVICT_BATCH_PERCENT / 100)
+                .saturating_sub(incoming);
+
+            while self.size_bytes() > target {
+                let oldest = self.oldest_entries(batch);
+                if oldest.is_empty() {
+                    break;
+                }
+                for key in oldest {
+                    if self.size_bytes() <= target {
+                        break;
+                    }
+                    evicted += self.evict(key) as usize;
+                }
+            }
+        }
+
+        self.evicting.store(false, Ordering::Release);
+        evicted
+    }
+
+    /// Evict the policy's victims down to the same targets as `evict_batch`
+    fn evict_with_policy(
+        &self,
+        policy: &dyn EvictionPolicy,
+        batch: usize,
+        incoming: usize,
+    ) -> usize {
+        let mut evicted = 0;
+
+        if self.cache.len() >= self.max_entries {
+            while evicted < batch {
+                let Some(key) = policy.victim() else { break };
+                evicted += self.evict(key) as usize;
+            }
+        }
+
+        if let Some(max_bytes) = self.max_bytes {
+            let target = max_bytes
+                .saturating_sub(max_bytes * EVICT_BATCH_PERCENT / 100)
+                .saturating_sub(incoming);
+
+            while self.size_bytes() > target {
+                let Some(key) = policy.victim() else { break };
+                evicted += self.evict(key) as usize;
+            }
+        }
+
+        evicted
+    }
+
+    /// Keys of the `batch` least recently used entries, oldest first
+    ///
+    /// Uses a bounded max-heap (O(batch) memory) to find the oldest entries
+    /// without allocating for the entire cache.
+    fn oldest_entries(&self, batch: usize) -> Vec<u64> {
         // Max-heap keyed by access time: the top element is the *newest* among candidates.
         // We keep only `batch` entries — if a new entry is older than the top, swap it in.
         let mut heap: BinaryHeap<(u64, u64)> = BinaryHeap::with_capacity(batch + 1);
@@ -151,13 +511,10 @@ impl ColdCache {
             }
         }
 
-        let evicted = heap.len();
-        for (_, key) in heap {
-            self.cache.remove(&key);
-        }
-
-        self.evicting.store(false, Ordering::Release);
-        evicted
+        heap.into_sorted_vec()
+            .into_iter()
+            .map(|(_, key)| key)
+            .collect()
     }
 
     /// Get number of entries
@@ -171,9 +528,30 @@ impl ColdCache {
         self.cache.is_empty()
     }
 
-    /// Remove a single entry by its URL hash
-    pub fn remove(&self, url_hash: u64) -> bool {
-        self.cache.remove(&url_hash).is_some()
+    /// Remove the entry of a URL
+    ///
+    /// An entry of another URL with the same hash is kept.
+    pub fn remove(&self, url_hash: u64, url: &str) -> bool {
+        self.remove_where(url_hash, |entry| *entry.url == *url)
+    }
+
+    /// Remove the entry of `url_hash`, whichever URL it holds
+    fn evict(&self, url_hash: u64) -> bool {
+        self.remove_where(url_hash, |_| true)
+    }
+
+    /// Remove the entry of `url_hash` if `matches` accepts it
+    fn remove_where(&self, url_hash: u64, matches: impl FnOnce(&CacheEntry) -> bool) -> bool {
+        match self.cache.remove_if(&url_hash, |_, entry| matches(entry)) {
+            Some((_, entry)) => {
+                self.bytes.fetch_sub(entry.size, Ordering::Relaxed);
+                if let Some(policy) = &self.policy {
+                    policy.on_remove(url_hash);
+                }
+                true
+            }
+            None => false,
+        }
     }
 
     /// Remove all entries whose URL starts with the given prefix.
@@ -188,22 +566,37 @@ impl ColdCache {
             }
         }
 
-        let count = to_remove.len();
-        for key in to_remove {
-            self.cache.remove(&key);
-        }
-        count
+        to_remove
+            .into_iter()
+            .filter(|&key| self.remove_where(key, |entry| entry.url.starts_with(prefix)))
+            .count()
     }
 
     /// Clear the cache
     pub fn clear(&self) {
-        self.cache.clear();
+        self.cache.retain(|_, entry| {
+            self.bytes.fetch_sub(entry.size, Ordering::Relaxed);
+            false
+        });
+        if let Some(policy) = &self.policy {
+            policy.clear();
+        }
     }
 
     /// Get maximum capacity
     pub fn capacity(&self) -> usize {
         self.max_entries
     }
+
+    /// Total size of cached entries in bytes
+    pub fn size_bytes(&self) -> usize {
+        self.bytes.load(Ordering::Relaxed)
+    }
+
+    /// Byte budget, if any
+    pub fn capacity_bytes(&self) -> Option<usize> {
+        self.max_bytes
+    }
 }
 
 #[cfg(test)]
@@ -217,8 +610,8 @@ mod tests {
 
         cache.insert(123, "/test", Arc::clone(&html));
 
-        assert!(cache.get(123).is_some());
-        assert!(cache.get(456).is_none());
+        assert!(cache.get(123, "/test").is_some());
+        assert!(cache.get(456, "/other").is_none());
     }
 
     #[test]
@@ -248,15 +641,30 @@ mod tests {
         assert!(cache.len() < 8);
     }
 
+    #[test]
+    fn test_hash_collision_is_a_miss() {
+        let cache = ColdCache::new(100);
+        cache.insert(1, "/a", "page a".into());
+
+        assert!(cache.get(1, "/b").is_none());
+        assert_eq!(cache.get(1, "/a").as_deref(), Some("page a"));
+
+        // The colliding URL replaces the entry rather than sharing it
+        cache.insert(1, "/b", "page b".into());
+        assert!(cache.get(1, "/a").is_none());
+        assert_eq!(cache.get(1, "/b").as_deref(), Some("page b"));
+    }
+
     #[test]
     fn test_remove_single() {
         let cache = ColdCache::new(100);
         cache.insert(1, "/a", "html_a".into());
         cache.insert(2, "/b", "html_b".into());
 
-        assert!(cache.remove(1));
-        assert!(cache.get(1).is_none());
-        assert!(cache.get(2).is_some());
+        assert!(!cache.remove(1, "/b"), "another URL with the same hash");
+        assert!(cache.remove(1, "/a"));
+        assert!(cache.get(1, "/a").is_none());
+        assert!(cache.get(2, "/b").is_some());
     }
 
     #[test]
@@ -271,7 +679,130 @@ mod tests {
         let removed = cache.remove_by_prefix("/products");
         assert_eq!(removed, 3);
         assert_eq!(cache.len(), 2);
-        assert!(cache.get(4).is_some());
-        assert!(cache.get(5).is_some());
+        assert!(cache.get(4, "/about").is_some());
+        assert!(cache.get(5, "/home").is_some());
+    }
+
+    #[test]
+    fn test_size_bytes_tracks_entries() {
+        let cache = ColdCache::new(100);
+        cache.insert(1, "/a", "x".repeat(100).into());
+        assert_eq!(cache.size_bytes(), 2 + 100 + ENTRY_OVERHEAD);
+
+        // Replacing an entry accounts for the old one
+        cache.insert(1, "/a", "x".repeat(10).into());
+        assert_eq!(cache.size_bytes(), 2 + 10 + ENTRY_OVERHEAD);
+
+        cache.insert(2, "/b", "y".into());
+        cache.remove(1, "/a");
+        assert_eq!(cache.size_bytes(), 2 + 1 + ENTRY_OVERHEAD);
+
+        cache.clear();
+        assert_eq!(cache.size_bytes(), 0);
+    }
+
+    #[test]
+    fn test_byte_budget_evicts_oldest() {
+        let page = 1000 + 3 + ENTRY_OVERHEAD;
+        let mut cache = ColdCache::new(1000);
+        cache.set_max_bytes(10 * page);
+
+        for i in 0..10 {
+            cache.insert(i, &format!("/p{}", i), "x".repeat(1000).into());
+        }
+        assert_eq!(cache.len(), 10);
+        cache.get(0, "/p0"); // keep /p0 recently used
+
+        // A large page displaces several small ones, oldest first
+        let evicted = cache.insert(100, "/big", "x".repeat(4 * page).into());
+        assert!(evicted >= 4);
+        assert!(cache.size_bytes() <= 10 * page);
+        assert!(cache.get(0, "/p0").is_some());
+        assert!(cache.get(1, "/p1").is_none());
+        assert!(cache.get(100, "/big").is_some());
+    }
+
+    #[test]
+    fn test_page_over_budget_is_not_cached() {
+        let page = 1000 + 3 + ENTRY_OVERHEAD;
+        let mut cache = ColdCache::new(1000);
+        cache.set_max_bytes(10 * page);
+
+        for i in 0..5 {
+            cache.insert(i, &format!("/p{}", i), "x".repeat(1000).into());
+        }
+        cache.insert(100, "/big", "x".repeat(1000).into());
+
+        // Larger than the whole budget: rejected without flushing the cache
+        let evicted = cache.insert(100, "/big", "x".repeat(11 * page).into());
+        assert_eq!(evicted, 0);
+        assert_eq!(cache.len(), 5);
+        assert!(cache.get(100, "/big").is_none());
+        assert!(cache.get(0, "/p0").is_some());
+        assert!(cache.size_bytes() <= 10 * page);
+    }
+
+    #[test]
+    fn test_policies_survive_scan() {
+        for eviction in [Eviction::TinyLfu, Eviction::S3Fifo] {
+            let mut cache = ColdCache::new(100);
+            cache.set_eviction(&eviction);
+
+            // Popular pages, requested repeatedly
+            for _ in 0..3 {
+                for i in 0..50 {
+                    let url = format!("/p{}", i);
+                    if cache.get(i, &url).is_none() {
+                        cache.insert(i, &url, "html".into());
+                    }
+                }
+            }
+
+            // A crawler scan of one-off URLs
+            for i in 1000..1500 {
+                let url = format!("/scan{}", i);
+                if cache.get(i, &url).is_none() {
+                    cache.insert(i, &url, "html".into());
+                }
+            }
+
+            assert!(cache.len() <= 100);
+            let kept = (0..50)
+                .filter(|&i| cache.get(i, &format!("/p{}", i)).is_some())
+                .count();
+            assert!(kept >= 45, "{:?} kept {} popular pages", eviction, kept);
+        }
+    }
+
+    #[test]
+    fn test_per_entry_ttl() {
+        let cache = ColdCache::with_ttl(10, 3600);
+        cache.insert(1, "/default", Arc::from("default"));
+        cache.insert_with_ttl(2, "/short", Arc::from("short"), Some(Duration::ZERO));
+
+        assert!(cache.get(2, "/short").is_none());
+        let (_, expires_at) = cache.get_with_expiry(1, "/default").unwrap();
+        assert!(expires_at.unwrap() > Instant::now() + Duration::from_secs(3500));
+    }
+
+    #[test]
+    fn test_inspect_does_not_touch_lru() {
+        let cache = ColdCache::new(8);
+        for i in 0..8 {
+            cache.insert(i, &format!("/page/{}", i), format!("html{}", i).into());
+        }
+
+        // Inspecting the oldest page must not save it from eviction
+        let (info, html) = cache.inspect(0, "/page/0").unwrap();
+        assert_eq!(info.url, "/page/0");
+        assert_eq!(info.size_bytes, 7 + 5 + ENTRY_OVERHEAD);
+        assert_eq!(info.expires_in_secs, None);
+        assert_eq!(&*html, "html0");
+        assert!(cache.inspect(0, "/other").is_none());
+        assert_eq!(cache.inspect_all("/page/").len(), 8);
+        assert_eq!(cache.snapshot().len(), 8);
+
+        cache.insert(100, "/new", "new".into());
+        assert!(cache.inspect(0, "/page/0").is_none());
     }
 }
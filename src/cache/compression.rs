@@ -0,0 +1,259 @@
+//! Pre-compressed storage of cached HTML
+//!
+//! With [`CacheCompression`] enabled, pages are compressed once when they
+//! are cached, and requests are served the matching variant directly
+//! instead of recompressing the same HTML on every response.
+
+use std::io::{self, Read, Write};
+use std::sync::Arc;
+
+/// Brotli quality for cached pages (compressed once, served many times)
+const BROTLI_QUALITY: u32 = 9;
+const BROTLI_WINDOW: u32 = 22;
+const ZSTD_LEVEL: i32 = 9;
+
+/// HTTP content encoding of a cached variant
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Encoding {
+    /// `br`
+    Brotli,
+    /// `gzip`
+    Gzip,
+    /// `zstd`
+    Zstd,
+}
+
+impl Encoding {
+    /// `Content-Encoding` token
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Encoding::Brotli => "br",
+            Encoding::Gzip => "gzip",
+            Encoding::Zstd => "zstd",
+        }
+    }
+
+    /// Compress `data` with this encoding
+    pub fn compress(self, data: &[u8]) -> io::Result<Vec<u8>> {
+        match self {
+            Encoding::Brotli => {
+                let mut out = Vec::with_capacity(data.len() / 4);
+                {
+                    let mut writer = brotli::CompressorWriter::new(
+                        &mut out,
+                        4096,
+                        BROTLI_QUALITY,
+                        BROTLI_WINDOW,
+                    );
+                    writer.write_all(data)?;
+                }
+                Ok(out)
+            }
+            Encoding::Gzip => {
+                let mut encoder = flate2::write::GzEncoder::new(
+                    Vec::with_capacity(data.len() / 4),
+                    flate2::Compression::best(),
+                );
+                encoder.write_all(data)?;
+                encoder.finish()
+            }
+            Encoding::Zstd => zstd::encode_all(data, ZSTD_LEVEL),
+        }
+    }
+
+    /// Decompress `data` produced by [`Encoding::compress`]
+    pub fn decompress(self, data: &[u8]) -> io::Result<Vec<u8>> {
+        let mut out = Vec::with_capacity(data.len() * 4);
+        match self {
+            Encoding::Brotli => {
+                brotli::Decompressor::new(data, 4096).read_to_end(&mut out)?;
+            }
+            Encoding::Gzip => {
+                flate2::read::GzDecoder::new(data).read_to_end(&mut out)?;
+            }
+            Encoding::Zstd => out = zstd::decode_all(data)?,
+        }
+        Ok(out)
+    }
+
+    /// Pick the best of `available` for an `Accept-Encoding` header
+    ///
+    /// Follows the client's q-values; ties go to the earlier entry of
+    /// `available`. Returns `None` if none is acceptable.
+    pub fn negotiate(accept_encoding: &str, available: &[Encoding]) -> Option<Encoding> {
+        let mut best: Option<(Encoding, f32)> = None;
+
+        for &encoding in available {
+            let q = accept_encoding
+                .split(',')
+                .filter_map(|item| {
+                    let mut parts = item.split(';');
+                    let token = parts.next()?.trim();
+                    if !token.eq_ignore_ascii_case(encoding.as_str()) && token != "*" {
+                        return None;
+                    }
+                    let q = parts
+                        .find_map(|param| param.trim().strip_prefix("q="))
+                        .map_or(Some(1.0), |q| q.trim().parse::<f32>().ok())?;
+                    // An explicit token takes precedence over `*`
+                    Some((token != "*", q))
+                })
+                .max_by(|a, b| a.0.cmp(&b.0))
+                .map(|(_, q)| q)
+                .unwrap_or(0.0);
+
+            if q > 0.0 && best.is_none_or(|(_, best_q)| q > best_q) {
+                best = Some((encoding, q));
+            }
+        }
+
+        best.map(|(encoding, _)| encoding)
+    }
+}
+
+/// Which compressed variants the cache stores
+///
+/// # Example
+/// ```rust
+/// use rusty_ssr::cache::{CacheCompression, Encoding};
+///
+/// // Brotli and gzip variants, no raw HTML
+/// let compression = CacheCompression::new([Encoding::Brotli, Encoding::Gzip]).without_raw();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheCompression {
+    /// Encodings to store, in order of server preference
+    pub encodings: Vec<Encoding>,
+
+    /// Also keep the raw HTML
+    ///
+    /// Without it, raw lookups decompress a variant on every cold hit, which
+    /// trades CPU for memory when most clients accept compression.
+    pub keep_raw: bool,
+}
+
+impl CacheCompression {
+    /// Store the given encodings alongside the raw HTML
+    pub fn new<I: IntoIterator<Item = Encoding>>(encodings: I) -> Self {
+        Self {
+            encodings: encodings.into_iter().collect(),
+            keep_raw: true,
+        }
+    }
+
+    /// Store only the compressed variants
+    pub fn without_raw(mut self) -> Self {
+        self.keep_raw = false;
+        self
+    }
+}
+
+impl Default for CacheCompression {
+    /// Brotli, zstd and gzip alongside the raw HTML
+    fn default() -> Self {
+        Self::new([Encoding::Brotli, Encoding::Zstd, Encoding::Gzip])
+    }
+}
+
+/// Compressed variants of one cached page
+#[derive(Debug, Clone)]
+pub struct CompressedPage {
+    variants: Vec<(Encoding, Arc<[u8]>)>,
+}
+
+impl CompressedPage {
+    /// Compress `html` with each encoding
+    pub fn compress(html: &str, encodings: &[Encoding]) -> io::Result<Self> {
+        let variants = encodings
+            .iter()
+            .map(|&encoding| Ok((encoding, Arc::from(encoding.compress(html.as_bytes())?))))
+            .collect::<io::Result<_>>()?;
+        Ok(Self { variants })
+    }
+
+    /// Stored encodings, in order of server preference
+    pub fn encodings(&self) -> Vec<Encoding> {
+        self.variants
+            .iter()
+            .map(|(encoding, _)| *encoding)
+            .collect()
+    }
+
+    /// The variant for `encoding`, if stored
+    pub fn get(&self, encoding: Encoding) -> Option<&Arc<[u8]>> {
+        self.variants
+            .iter()
+            .find(|(e, _)| *e == encoding)
+            .map(|(_, data)| data)
+    }
+
+    /// The best stored variant for an `Accept-Encoding` header
+    pub fn negotiate(&self, accept_encoding: &str) -> Option<(Encoding, Arc<[u8]>)> {
+        let encoding = Encoding::negotiate(accept_encoding, &self.encodings())?;
+        self.get(encoding).map(|data| (encoding, Arc::clone(data)))
+    }
+
+    /// Total size of the variants in bytes
+    pub fn size_bytes(&self) -> usize {
+        self.variants.iter().map(|(_, data)| data.len()).sum()
+    }
+
+    /// Recover the HTML from the first variant
+    pub fn decompress(&self) -> io::Result<Arc<str>> {
+        let (encoding, data) = self
+            .variants
+            .first()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no compressed variant"))?;
+        let bytes = encoding.decompress(data)?;
+        String::from_utf8(bytes)
+            .map(Arc::from)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+/// Cached page in the encoding chosen for a request
+#[derive(Debug, Clone)]
+pub enum EncodedHtml {
+    /// Uncompressed HTML
+    Raw(Arc<str>),
+    /// A pre-compressed variant
+    Encoded(Encoding, Arc<[u8]>),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip_all_encodings() {
+        let html = "<html><body>".to_string() + &"<p>Hello</p>".repeat(500) + "</body></html>";
+
+        for encoding in [Encoding::Brotli, Encoding::Gzip, Encoding::Zstd] {
+            let compressed = encoding.compress(html.as_bytes()).unwrap();
+            assert!(compressed.len() < html.len() / 10, "{:?}", encoding);
+            assert_eq!(encoding.decompress(&compressed).unwrap(), html.as_bytes());
+        }
+
+        let page = CompressedPage::compress(&html, &[Encoding::Zstd, Encoding::Gzip]).unwrap();
+        assert_eq!(&*page.decompress().unwrap(), html);
+        assert!(page.get(Encoding::Brotli).is_none());
+    }
+
+    #[test]
+    fn test_negotiate() {
+        let all = [Encoding::Brotli, Encoding::Zstd, Encoding::Gzip];
+
+        assert_eq!(
+            Encoding::negotiate("gzip, deflate, br", &all),
+            Some(Encoding::Brotli)
+        );
+        assert_eq!(Encoding::negotiate("gzip", &all), Some(Encoding::Gzip));
+        assert_eq!(
+            Encoding::negotiate("br;q=0.5, gzip", &all),
+            Some(Encoding::Gzip)
+        );
+        assert_eq!(Encoding::negotiate("br;q=0, *", &all), Some(Encoding::Zstd));
+        assert_eq!(Encoding::negotiate("identity", &all), None);
+        assert_eq!(Encoding::negotiate("", &all), None);
+    }
+}
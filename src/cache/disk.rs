@@ -0,0 +1,1201 @@
+//! Persistent "disk" cache tier
+//!
+//! One file per entry under `<dir>/<namespace>/`, so a new bundle version
+//! (namespace) never serves pages rendered by an old one. The index lives in
+//! memory and is rebuilt from the entry headers when the cache is opened.
+//! Access time: file read (~10-100 microseconds)
+//!
+//! Entry file layout (little endian):
+//! `magic (4) | created_at unix secs (8) | ttl secs (8) | encoding (1) | url length (4) | body length (8) | url | body`
+//!
+//! The body is the HTML, zstd-compressed (encoding 1) with the
+//! `cache-compression` feature and raw (encoding 0) otherwise. Builds without
+//! the feature drop compressed entries. A ttl of 0 means the cache TTL applies. Every write goes to a new file,
+//! so concurrent writes never share a path. Files of an older format, and
+//! files whose size does not match their header, are deleted when the cache
+//! is opened or read.
+//!
+//! The methods of [`DiskCache`] do blocking file I/O; [`SsrCache`](crate::cache::SsrCache)
+//! runs them on Tokio's blocking threads.
+
+use parking_lot::Mutex;
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use super::utils::{stable_hash, HashKey};
+
+/// Identifies entry files written by this version of the format
+const MAGIC: &[u8; 4] = b"RSC4";
+
+/// Bytes before the URL in an entry file
+const HEADER_LEN: usize = 4 + 8 + 8 + 1 + 4 + 8;
+
+/// Encoding of an uncompressed entry body
+const RAW: u8 = 0;
+
+/// Encoding of a zstd-compressed entry body
+const ZSTD: u8 = 1;
+
+/// Extension of entry files
+const ENTRY_EXT: &str = "entry";
+
+/// Extension of entry files being written
+const TMP_EXT: &str = "tmp";
+
+/// File holding the [`HashKey`] of a namespace
+const KEY_FILE: &str = "hash.key";
+
+/// Temp files older than this were left behind by a crashed writer
+const STALE_TMP_AGE: Duration = Duration::from_secs(60);
+
+/// Evict down to this share of `max_bytes`, so eviction scans stay rare
+const EVICT_TARGET_PERCENT: u64 = 90;
+
+/// Removals remembered per URL before falling back to a single cutoff
+const MAX_REMOVALS: usize = 4096;
+
+/// Makes entry file names unique within the process
+static WRITE_SEQ: AtomicU64 = AtomicU64::new(1);
+
+/// Index entry for a file on disk
+struct DiskEntry {
+    url: Arc<str>,
+    path: PathBuf,
+    bytes: u64,
+    created_at: u64,
+    /// Per-entry TTL in seconds (0 = cache TTL)
+    ttl_secs: u64,
+    last_access: u64,
+    /// Ticket of the write, 0 for entries found when opening
+    seq: u64,
+}
+
+/// Reserved write of a [`DiskCache`], see [`DiskCache::ticket`]
+pub(crate) struct WriteTicket {
+    seq: u64,
+}
+
+/// Removals that writes ticketed before them must not undo
+///
+/// Each removal takes a sequence number from the same counter as the
+/// tickets, so a write is outdated if a removal covering its URL has a
+/// later number. Removals are forgotten once no ticketed write is pending.
+#[derive(Default)]
+struct Removals {
+    /// Tickets taken but not written yet
+    pending: BTreeSet<u64>,
+    /// Latest removal per URL hash
+    urls: HashMap<u64, u64>,
+    /// Latest removal per URL prefix
+    prefixes: HashMap<String, u64>,
+    /// Writes ticketed before this are all outdated
+    cutoff: u64,
+}
+
+impl Removals {
+    /// Record a removal of `url_hash`
+    fn url(&mut self, url_hash: u64) {
+        if self.pending.is_empty() {
+            return;
+        }
+        let seq = WRITE_SEQ.fetch_add(1, Ordering::Relaxed);
+        self.urls.insert(url_hash, seq);
+        self.bound();
+    }
+
+    /// Record a removal of every URL starting with `prefix`
+    fn prefix(&mut self, prefix: &str) {
+        if self.pending.is_empty() {
+            return;
+        }
+        let seq = WRITE_SEQ.fetch_add(1, Ordering::Relaxed);
+        self.prefixes.insert(prefix.to_string(), seq);
+        self.bound();
+    }
+
+    /// Record a removal of every URL
+    fn all(&mut self) {
+        self.cutoff = WRITE_SEQ.fetch_add(1, Ordering::Relaxed);
+        self.urls.clear();
+        self.prefixes.clear();
+    }
+
+    /// Take a ticket
+    fn ticket(&mut self) -> WriteTicket {
+        let seq = WRITE_SEQ.fetch_add(1, Ordering::Relaxed);
+        self.pending.insert(seq);
+        WriteTicket { seq }
+    }
+
+    /// Check whether a removal since `ticket` covers `url`
+    fn outdates(&self, ticket: &WriteTicket, url_hash: u64, url: &str) -> bool {
+        ticket.seq < self.cutoff
+            || self
+                .urls
+                .get(&url_hash)
+                .is_some_and(|&seq| ticket.seq < seq)
+            || self
+                .prefixes
+                .iter()
+                .any(|(prefix, &seq)| ticket.seq < seq && url.starts_with(prefix.as_str()))
+    }
+
+    /// Settle `ticket`, forgetting the removals once no write is pending
+    fn settle(&mut self, ticket: &WriteTicket) {
+        self.pending.remove(&ticket.seq);
+        if self.pending.is_empty() {
+            self.urls.clear();
+            self.prefixes.clear();
+        }
+    }
+
+    /// Trade the removals for a cutoff once too many are remembered
+    ///
+    /// Pending writes are then dropped, as if every entry was removed.
+    fn bound(&mut self) {
+        if self.urls.len() + self.prefixes.len() > MAX_REMOVALS {
+            self.all();
+        }
+    }
+}
+
+/// Shared cache of rendered pages on local disk
+pub struct DiskCache {
+    dir: PathBuf,
+    index: Mutex<HashMap<u64, DiskEntry>>,
+    total_bytes: AtomicU64,
+    max_bytes: u64,
+    access_counter: AtomicU64,
+    /// Removals since the oldest writes, see [`DiskCache::insert_ticketed`]
+    ///
+    /// Taken after the index lock, never before it.
+    removals: Mutex<Removals>,
+    ttl: Option<Duration>,
+    /// Key of the URL hashes, persisted with the entries
+    hash_key: HashKey,
+}
+
+impl DiskCache {
+    /// Open (or create) the disk cache for `namespace` under `dir`
+    ///
+    /// Existing entries are indexed; expired or unreadable ones are deleted.
+    /// The namespace's hash key is read, or created along with the namespace.
+    ///
+    /// # Arguments
+    /// * `dir` - Root directory shared by all namespaces
+    /// * `namespace` - Cache namespace, typically the bundle version
+    /// * `max_bytes` - Size bound of this namespace on disk
+    /// * `ttl_secs` - Time-to-live in seconds (0 = no expiration)
+    pub fn open<P: AsRef<Path>>(
+        dir: P,
+        namespace: &str,
+        max_bytes: u64,
+        ttl_secs: u64,
+    ) -> io::Result<Self> {
+        let dir = dir.as_ref().join(sanitize_namespace(namespace));
+        fs::create_dir_all(&dir)?;
+        let hash_key = load_or_create_key(&dir)?;
+
+        let cache = Self {
+            dir,
+            index: Mutex::new(HashMap::new()),
+            total_bytes: AtomicU64::new(0),
+            max_bytes,
+            access_counter: AtomicU64::new(0),
+            removals: Mutex::new(Removals::default()),
+            ttl: (ttl_secs > 0).then(|| Duration::from_secs(ttl_secs)),
+            hash_key,
+        };
+        cache.load_index()?;
+
+        tracing::info!(
+            "💾 Opened disk cache {:?} ({} entries, {} bytes)",
+            cache.dir,
+            cache.len(),
+            cache.size_bytes()
+        );
+
+        Ok(cache)
+    }
+
+    /// Delete the namespaces under `dir` other than `keep` that have not
+    /// changed for `idle`
+    ///
+    /// Namespaces of old bundle versions are not reclaimed otherwise. The
+    /// idle time spares namespaces still written to by other processes, e.g.
+    /// during a rolling deploy. Directories holding anything but cache files
+    /// are left alone. Returns the number of deleted namespaces.
+    pub fn prune_namespaces<P: AsRef<Path>>(
+        dir: P,
+        keep: &str,
+        idle: Duration,
+    ) -> io::Result<usize> {
+        let keep = sanitize_namespace(keep);
+        let mut pruned = 0;
+
+        for dir_entry in fs::read_dir(dir)?.flatten() {
+            let path = dir_entry.path();
+            if dir_entry.file_name().to_str() == Some(keep.as_str())
+                || !dir_entry.file_type().is_ok_and(|t| t.is_dir())
+                || !is_idle(&path, idle)
+                || !holds_only_cache_files(&path)
+            {
+                continue;
+            }
+
+            match fs::remove_dir_all(&path) {
+                Ok(()) => pruned += 1,
+                Err(e) => {
+                    tracing::warn!("⚠️ Failed to delete disk cache namespace {:?}: {}", path, e)
+                }
+            }
+        }
+
+        Ok(pruned)
+    }
+
+    /// Index the entry files already in the namespace directory
+    ///
+    /// Of several files for one URL, the newest is kept.
+    fn load_index(&self) -> io::Result<()> {
+        let mut index = self.index.lock();
+        let mut total = 0;
+
+        for dir_entry in fs::read_dir(&self.dir)?.flatten() {
+            let path = dir_entry.path();
+            match path.extension().and_then(|ext| ext.to_str()) {
+                Some(ENTRY_EXT) => {}
+                Some(TMP_EXT) => {
+                    if is_idle(&path, STALE_TMP_AGE) {
+                        let _ = fs::remove_file(&path);
+                    }
+                    continue;
+                }
+                _ => continue,
+            }
+
+            let bytes = dir_entry.metadata().map(|m| m.len()).unwrap_or(0);
+            let header = read_header(&path);
+            let (created_at, ttl_secs, url) = match header {
+                Ok((created_at, ttl_secs, url, body_len))
+                    if (HEADER_LEN + url.len()) as u64 + body_len == bytes
+                        && !self.is_expired(created_at, ttl_secs) =>
+                {
+                    (created_at, ttl_secs, url)
+                }
+                _ => {
+                    let _ = fs::remove_file(&path);
+                    continue;
+                }
+            };
+
+            let entry = DiskEntry {
+                url: Arc::from(url),
+                path,
+                bytes,
+                created_at,
+                ttl_secs,
+                last_access: 0,
+                seq: 0,
+            };
+            let stale = match index.entry(self.hash_key.hash_url(&entry.url)) {
+                Entry::Occupied(mut slot) if slot.get().created_at < entry.created_at => {
+                    total -= slot.get().bytes;
+                    total += entry.bytes;
+                    slot.insert(entry).path
+                }
+                Entry::Occupied(_) => entry.path,
+                Entry::Vacant(slot) => {
+                    total += entry.bytes;
+                    slot.insert(entry);
+                    continue;
+                }
+            };
+            let _ = fs::remove_file(stale);
+        }
+
+        self.total_bytes.store(total, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Hash of a URL, as expected by the other methods
+    ///
+    /// Keyed with the namespace's persisted hash key.
+    pub fn url_hash(&self, url: &str) -> u64 {
+        self.hash_key.hash_url(url)
+    }
+
+    /// Key of the URL hashes of this namespace
+    pub(crate) fn hash_key(&self) -> HashKey {
+        self.hash_key
+    }
+
+    /// Replace the hash key, deleting all entries if it differs
+    ///
+    /// Cache keys may embed hashes made with the old key.
+    pub(crate) fn set_hash_key(&mut self, hash_key: HashKey) -> io::Result<()> {
+        if hash_key == self.hash_key {
+            return Ok(());
+        }
+
+        tracing::info!(
+            "💾 Disk cache {:?} uses a new hash key, clearing it",
+            self.dir
+        );
+        self.clear();
+        write_key(&self.dir, hash_key)?;
+        self.hash_key = hash_key;
+        Ok(())
+    }
+
+    /// Get HTML from disk
+    ///
+    /// Returns None if not found, expired, unreadable or cached for another
+    /// URL with the same hash
+    pub fn get(&self, url_hash: u64, url: &str) -> Option<Arc<str>> {
+        self.get_with_expiry(url_hash, url).map(|(html, _)| html)
+    }
+
+    /// Get HTML from disk along with when it expires (`None` = never)
+    ///
+    /// Entries that fail to read, e.g. truncated files, count as a miss and
+    /// are deleted.
+    pub fn get_with_expiry(&self, url_hash: u64, url: &str) -> Option<(Arc<str>, Option<Instant>)> {
+        let (path, expires_at) = {
+            let mut index = self.index.lock();
+            let entry = index.get_mut(&url_hash).filter(|e| *e.url == *url)?;
+
+            if self.is_expired(entry.created_at, entry.ttl_secs) {
+                let path = entry.path.clone();
+                drop(index);
+                self.discard(url_hash, &path);
+                return None;
+            }
+
+            entry.last_access = self.access_counter.fetch_add(1, Ordering::Relaxed);
+            let expires_at = self.ttl_of(entry.ttl_secs).map(|ttl| {
+                let age = unix_now().saturating_sub(entry.created_at);
+                Instant::now() + Duration::from_secs(ttl.saturating_sub(age))
+            });
+            (entry.path.clone(), expires_at)
+        };
+
+        match read_entry(&path) {
+            Ok((entry_url, html)) if entry_url == url => Some((Arc::from(html), expires_at)),
+            Ok(_) => {
+                tracing::warn!("⚠️ Disk cache entry {:?} holds another URL", path);
+                self.discard(url_hash, &path);
+                None
+            }
+            // Replaced by a newer write since the lookup
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => {
+                tracing::warn!("⚠️ Failed to read disk cache entry {:?}: {}", path, e);
+                self.discard(url_hash, &path);
+                None
+            }
+        }
+    }
+
+    /// Check whether a URL is cached and not expired, without reading its file
+    pub fn contains(&self, url_hash: u64, url: &str) -> bool {
+        self.index
+            .lock()
+            .get(&url_hash)
+            .is_some_and(|e| *e.url == *url && !self.is_expired(e.created_at, e.ttl_secs))
+    }
+
+    /// Write HTML to disk, evicting old entries if over the size bound
+    ///
+    /// Returns the number of evicted entries.
+    pub fn insert(&self, url_hash: u64, url: &str, html: &str) -> usize {
+        self.insert_with_ttl(url_hash, url, html, None)
+    }
+
+    /// Write HTML that expires after `ttl` instead of the cache TTL
+    ///
+    /// `None` uses the cache TTL. Returns the number of evicted entries.
+    pub fn insert_with_ttl(
+        &self,
+        url_hash: u64,
+        url: &str,
+        html: &str,
+        ttl: Option<Duration>,
+    ) -> usize {
+        self.insert_ticketed(self.ticket(), url_hash, url, html, ttl)
+    }
+
+    /// Reserve a write, for [`DiskCache::insert_ticketed`]
+    pub(crate) fn ticket(&self) -> WriteTicket {
+        self.removals.lock().ticket()
+    }
+
+    /// Like [`DiskCache::insert_with_ttl`], as of when `ticket` was taken
+    ///
+    /// The write is dropped if the URL was removed since, or if a write
+    /// with a later ticket already stored the URL. A write running in the
+    /// background would otherwise restore a page invalidated or replaced
+    /// meanwhile.
+    pub(crate) fn insert_ticketed(
+        &self,
+        ticket: WriteTicket,
+        url_hash: u64,
+        url: &str,
+        html: &str,
+        ttl: Option<Duration>,
+    ) -> usize {
+        let path = self.dir.join(format!(
+            "{:016x}-{}-{}.{}",
+            stable_hash(url.as_bytes()),
+            std::process::id(),
+            ticket.seq,
+            ENTRY_EXT
+        ));
+        let created_at = unix_now();
+        // Round up, so a sub-second TTL does not mean the cache TTL
+        let ttl_secs = ttl.map_or(0, |ttl| ttl.as_secs() + u64::from(ttl.subsec_nanos() > 0));
+
+        let bytes = match write_entry(&path, created_at, ttl_secs, url, html) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                tracing::warn!("⚠️ Failed to write disk cache entry {:?}: {}", path, e);
+                return 0;
+            }
+        };
+
+        let mut index = self.index.lock();
+        let mut removals = self.removals.lock();
+        let outdated = removals.outdates(&ticket, url_hash, url)
+            || index
+                .get(&url_hash)
+                .is_some_and(|e| *e.url == *url && e.seq > ticket.seq);
+        removals.settle(&ticket);
+        drop(removals);
+        if outdated {
+            drop(index);
+            tracing::debug!("Dropping outdated disk cache write of {}", url);
+            let _ = fs::remove_file(&path);
+            return 0;
+        }
+
+        let previous = index.insert(
+            url_hash,
+            DiskEntry {
+                url: Arc::from(url),
+                path,
+                bytes,
+                created_at,
+                ttl_secs,
+                last_access: self.access_counter.fetch_add(1, Ordering::Relaxed),
+                seq: ticket.seq,
+            },
+        );
+        // Sizes are only updated under the lock, so they never underflow
+        let mut total = self.total_bytes.fetch_add(bytes, Ordering::Relaxed) + bytes;
+        if let Some(previous) = &previous {
+            total -= previous.bytes;
+            self.total_bytes
+                .fetch_sub(previous.bytes, Ordering::Relaxed);
+        }
+        drop(index);
+
+        if let Some(previous) = previous {
+            let _ = fs::remove_file(&previous.path);
+        }
+
+        if total > self.max_bytes {
+            self.evict()
+        } else {
+            0
+        }
+    }
+
+    /// Evict least recently used entries down to the eviction target
+    ///
+    /// Files are deleted after the index lock is released.
+    fn evict(&self) -> usize {
+        let target = self.max_bytes * EVICT_TARGET_PERCENT / 100;
+        let victims: Vec<PathBuf> = {
+            let mut index = self.index.lock();
+
+            let mut candidates: Vec<(u64, u64)> = index
+                .iter()
+                .map(|(hash, entry)| (entry.last_access, *hash))
+                .collect();
+            candidates.sort_unstable();
+
+            let mut victims = Vec::new();
+            for (_, hash) in candidates {
+                if self.total_bytes.load(Ordering::Relaxed) <= target {
+                    break;
+                }
+                if let Some(entry) = index.remove(&hash) {
+                    self.total_bytes.fetch_sub(entry.bytes, Ordering::Relaxed);
+                    victims.push(entry.path);
+                }
+            }
+            victims
+        };
+
+        delete_files(&victims);
+        victims.len()
+    }
+
+    /// Remove the entry of a URL
+    ///
+    /// An entry of another URL with the same hash is kept.
+    pub fn remove(&self, url_hash: u64, url: &str) -> bool {
+        let removed = self.detach(url_hash, url);
+        delete_files(removed.as_slice());
+        removed.is_some()
+    }
+
+    /// Remove an entry from the index, returning its file to delete
+    ///
+    /// Lookups miss the entry right away; see [`delete_files`].
+    pub(crate) fn detach(&self, url_hash: u64, url: &str) -> Option<PathBuf> {
+        let mut index = self.index.lock();
+        self.removals.lock().url(url_hash);
+        if index.get(&url_hash).is_none_or(|entry| *entry.url != *url) {
+            return None;
+        }
+        let entry = index.remove(&url_hash)?;
+        self.total_bytes.fetch_sub(entry.bytes, Ordering::Relaxed);
+        Some(entry.path)
+    }
+
+    /// Remove the entry of `url_hash` if it still refers to `path`, and delete the file
+    fn discard(&self, url_hash: u64, path: &Path) {
+        {
+            let mut index = self.index.lock();
+            if index.get(&url_hash).is_some_and(|e| e.path == path) {
+                if let Some(entry) = index.remove(&url_hash) {
+                    self.total_bytes.fetch_sub(entry.bytes, Ordering::Relaxed);
+                }
+            }
+        }
+        let _ = fs::remove_file(path);
+    }
+
+    /// Remove all entries whose URL starts with the given prefix.
+    ///
+    /// Returns the number of removed entries.
+    pub fn remove_by_prefix(&self, prefix: &str) -> usize {
+        let removed = self.detach_by_prefix(prefix);
+        delete_files(&removed);
+        removed.len()
+    }
+
+    /// Remove the entries of a URL prefix from the index, returning their files
+    pub(crate) fn detach_by_prefix(&self, prefix: &str) -> Vec<PathBuf> {
+        let mut index = self.index.lock();
+        self.removals.lock().prefix(prefix);
+        let keys: Vec<u64> = index
+            .iter()
+            .filter(|(_, entry)| entry.url.starts_with(prefix))
+            .map(|(hash, _)| *hash)
+            .collect();
+
+        keys.iter()
+            .filter_map(|key| index.remove(key))
+            .map(|entry| {
+                self.total_bytes.fetch_sub(entry.bytes, Ordering::Relaxed);
+                entry.path
+            })
+            .collect()
+    }
+
+    /// Delete every entry of this namespace
+    pub fn clear(&self) {
+        delete_files(&self.detach_all());
+    }
+
+    /// Remove every entry from the index, returning their files
+    pub(crate) fn detach_all(&self) -> Vec<PathBuf> {
+        let mut index = self.index.lock();
+        self.removals.lock().all();
+        self.total_bytes.store(0, Ordering::Relaxed);
+        index.drain().map(|(_, entry)| entry.path).collect()
+    }
+
+    /// Get number of entries
+    pub fn len(&self) -> usize {
+        self.index.lock().len()
+    }
+
+    /// Check if empty
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.index.lock().is_empty()
+    }
+
+    /// Bytes used on disk by this namespace
+    pub fn size_bytes(&self) -> u64 {
+        self.total_bytes.load(Ordering::Relaxed)
+    }
+
+    /// Size bound in bytes
+    pub fn capacity_bytes(&self) -> u64 {
+        self.max_bytes
+    }
+
+    /// TTL in seconds of an entry, `None` if it never expires
+    fn ttl_of(&self, ttl_secs: u64) -> Option<u64> {
+        match ttl_secs {
+            0 => self.ttl.map(|ttl| ttl.as_secs()),
+            ttl_secs => Some(ttl_secs),
+        }
+    }
+
+    fn is_expired(&self, created_at: u64, ttl_secs: u64) -> bool {
+        self.ttl_of(ttl_secs)
+            .is_some_and(|ttl| unix_now().saturating_sub(created_at) > ttl)
+    }
+}
+
+/// Keep namespaces to a single safe path component
+fn sanitize_namespace(namespace: &str) -> String {
+    let name: String = namespace
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    match name.trim_matches('.') {
+        "" => "default".to_string(),
+        _ => name,
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Delete entry files removed from the index
+pub(crate) fn delete_files(paths: &[PathBuf]) {
+    for path in paths {
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// Whether `path` was last modified at least `idle` ago
+fn is_idle(path: &Path, idle: Duration) -> bool {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .is_ok_and(|modified| modified.elapsed().is_ok_and(|age| age >= idle))
+}
+
+/// Whether a directory holds only files written by the disk cache
+fn holds_only_cache_files(dir: &Path) -> bool {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return false;
+    };
+    entries.flatten().all(|entry| {
+        let path = entry.path();
+        entry.file_type().is_ok_and(|t| t.is_file())
+            && (entry.file_name() == KEY_FILE
+                || matches!(
+                    path.extension().and_then(|ext| ext.to_str()),
+                    Some(ENTRY_EXT | TMP_EXT)
+                ))
+    })
+}
+
+/// Read the hash key of a namespace, creating it if missing
+///
+/// A corrupt key is replaced, deleting the entries whose keys may embed
+/// hashes made with it.
+fn load_or_create_key(dir: &Path) -> io::Result<HashKey> {
+    match fs::read(dir.join(KEY_FILE)) {
+        Ok(bytes) => match <[u8; 16]>::try_from(bytes.as_slice()) {
+            Ok(key) => return Ok(HashKey(key)),
+            Err(_) => {
+                tracing::warn!(
+                    "⚠️ Corrupt disk cache hash key in {:?}, clearing the cache",
+                    dir
+                );
+                for dir_entry in fs::read_dir(dir)?.flatten() {
+                    if dir_entry.path().extension().and_then(|ext| ext.to_str()) == Some(ENTRY_EXT)
+                    {
+                        let _ = fs::remove_file(dir_entry.path());
+                    }
+                }
+                let _ = fs::remove_file(dir.join(KEY_FILE));
+            }
+        },
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+
+    let key = HashKey::random();
+    // Another process opening the namespace concurrently may win the race
+    let tmp = key_tmp_path(dir);
+    fs::write(&tmp, key.0)?;
+    let linked = fs::hard_link(&tmp, dir.join(KEY_FILE));
+    let _ = fs::remove_file(&tmp);
+    match linked {
+        Ok(()) => Ok(key),
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => load_or_create_key(dir),
+        Err(_) => {
+            // Hard links are not supported everywhere
+            write_key(dir, key)?;
+            Ok(key)
+        }
+    }
+}
+
+/// Write the hash key of a namespace, replacing any previous one
+fn write_key(dir: &Path, key: HashKey) -> io::Result<()> {
+    let tmp = key_tmp_path(dir);
+    fs::write(&tmp, key.0)?;
+    fs::rename(&tmp, dir.join(KEY_FILE))
+}
+
+fn key_tmp_path(dir: &Path) -> PathBuf {
+    dir.join(format!(
+        "key-{}-{}.{}",
+        std::process::id(),
+        WRITE_SEQ.fetch_add(1, Ordering::Relaxed),
+        TMP_EXT
+    ))
+}
+
+/// Write an entry atomically (temp file + rename), returning its size
+fn write_entry(
+    path: &Path,
+    created_at: u64,
+    ttl_secs: u64,
+    url: &str,
+    html: &str,
+) -> io::Result<u64> {
+    let (encoding, body) = encode(html)?;
+    let mut buf = Vec::with_capacity(HEADER_LEN + url.len() + body.len());
+    buf.extend_from_slice(MAGIC);
+    buf.extend_from_slice(&created_at.to_le_bytes());
+    buf.extend_from_slice(&ttl_secs.to_le_bytes());
+    buf.push(encoding);
+    buf.extend_from_slice(&(url.len() as u32).to_le_bytes());
+    buf.extend_from_slice(&(body.len() as u64).to_le_bytes());
+    buf.extend_from_slice(url.as_bytes());
+    buf.extend_from_slice(&body);
+
+    let tmp = path.with_extension(TMP_EXT);
+    fs::write(&tmp, &buf)?;
+    fs::rename(&tmp, path)?;
+
+    Ok(buf.len() as u64)
+}
+
+/// Encode the body of an entry, returning its encoding
+#[cfg(feature = "cache-compression")]
+fn encode(html: &str) -> io::Result<(u8, std::borrow::Cow<'_, [u8]>)> {
+    let body = super::Encoding::Zstd.compress(html.as_bytes())?;
+    Ok((ZSTD, body.into()))
+}
+
+/// Encode the body of an entry, returning its encoding
+#[cfg(not(feature = "cache-compression"))]
+fn encode(html: &str) -> io::Result<(u8, std::borrow::Cow<'_, [u8]>)> {
+    Ok((RAW, html.as_bytes().into()))
+}
+
+/// Decode the body of an entry to its HTML
+fn decode(encoding: u8, body: &[u8]) -> io::Result<String> {
+    match encoding {
+        #[cfg(feature = "cache-compression")]
+        ZSTD => {
+            let html = super::Encoding::Zstd.decompress(body)?;
+            String::from_utf8(html).map_err(|_| invalid("invalid UTF-8 in disk cache entry"))
+        }
+        _ => Ok(utf8(body)?.to_string()),
+    }
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// Parse an entry header, returning the creation time, TTL, encoding, URL length and body length
+///
+/// Fails for encodings this build cannot decode.
+fn parse_header(header: &[u8]) -> io::Result<(u64, u64, u8, usize, u64)> {
+    if header.len() < HEADER_LEN || &header[..4] != MAGIC {
+        return Err(invalid("not a disk cache entry"));
+    }
+
+    let created_at = u64::from_le_bytes(header[4..12].try_into().unwrap());
+    let ttl_secs = u64::from_le_bytes(header[12..20].try_into().unwrap());
+    let encoding = header[20];
+    if encoding != RAW && (encoding != ZSTD || cfg!(not(feature = "cache-compression"))) {
+        return Err(invalid("unsupported disk cache entry encoding"));
+    }
+    let url_len = u32::from_le_bytes(header[21..25].try_into().unwrap()) as usize;
+    let body_len = u64::from_le_bytes(header[25..33].try_into().unwrap());
+    Ok((created_at, ttl_secs, encoding, url_len, body_len))
+}
+
+fn utf8(bytes: &[u8]) -> io::Result<&str> {
+    std::str::from_utf8(bytes).map_err(|_| invalid("invalid UTF-8 in disk cache entry"))
+}
+
+/// Read the creation time, TTL, URL and body length of an entry, without its body
+fn read_header(path: &Path) -> io::Result<(u64, u64, String, u64)> {
+    let mut file = fs::File::open(path)?;
+    let mut header = [0u8; HEADER_LEN];
+    file.read_exact(&mut header)?;
+    let (created_at, ttl_secs, _, url_len, body_len) = parse_header(&header)?;
+
+    let mut url = vec![0u8; url_len];
+    file.read_exact(&mut url)?;
+    Ok((created_at, ttl_secs, utf8(&url)?.to_string(), body_len))
+}
+
+/// Read the URL and HTML of an entry
+///
+/// Fails if the file is shorter or longer than its header says.
+fn read_entry(path: &Path) -> io::Result<(String, String)> {
+    let buf = fs::read(path)?;
+    let (_, _, encoding, url_len, body_len) = parse_header(&buf)?;
+    if (buf.len() - HEADER_LEN) as u64 != url_len as u64 + body_len {
+        return Err(invalid("disk cache entry size does not match its header"));
+    }
+
+    let (url, body) = buf[HEADER_LEN..].split_at(url_len);
+    Ok((utf8(url)?.to_string(), decode(encoding, body)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn open(dir: &Path, max_bytes: u64) -> DiskCache {
+        DiskCache::open(dir, "v1", max_bytes, 0).unwrap()
+    }
+
+    fn entry_files(cache: &DiskCache) -> Vec<PathBuf> {
+        fs::read_dir(&cache.dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some(ENTRY_EXT))
+            .collect()
+    }
+
+    #[test]
+    fn test_basic_operations() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = open(dir.path(), 1 << 20);
+
+        cache.insert(cache.url_hash("/test"), "/test", "<h1>test</h1>");
+        assert_eq!(
+            cache.get(cache.url_hash("/test"), "/test").as_deref(),
+            Some("<h1>test</h1>")
+        );
+        assert!(cache.get(cache.url_hash("/other"), "/other").is_none());
+        assert_eq!(cache.len(), 1);
+        assert!(cache.size_bytes() > 0);
+    }
+
+    #[test]
+    fn test_survives_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        {
+            let cache = open(dir.path(), 1 << 20);
+            cache.insert(cache.url_hash("/a"), "/a", "html_a");
+            cache.insert(cache.url_hash("/b"), "/b", "html_b");
+        }
+
+        let cache = open(dir.path(), 1 << 20);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(
+            cache.get(cache.url_hash("/a"), "/a").as_deref(),
+            Some("html_a")
+        );
+    }
+
+    #[test]
+    fn test_namespaces_are_separate() {
+        let dir = tempfile::tempdir().unwrap();
+        let v1 = open(dir.path(), 1 << 20);
+        v1.insert(v1.url_hash("/a"), "/a", "v1");
+        drop(v1);
+
+        let v2 = DiskCache::open(dir.path(), "v2", 1 << 20, 0).unwrap();
+        assert!(v2.get(v2.url_hash("/a"), "/a").is_none());
+
+        let v1 = open(dir.path(), 1 << 20);
+        assert_eq!(v1.get(v1.url_hash("/a"), "/a").as_deref(), Some("v1"));
+    }
+
+    #[test]
+    fn test_expired_entries_dropped_on_open() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = DiskCache::open(dir.path(), "v1", 1 << 20, 60).unwrap();
+        let path = cache.dir.join("old.entry");
+        write_entry(&path, unix_now() - 120, 0, "/old", "stale").unwrap();
+        // Written by an older format version
+        let legacy = cache.dir.join("legacy.entry");
+        fs::write(&legacy, b"RSC1\0\0\0\0\0\0\0\0\x07\0\0\0/legacyhtml").unwrap();
+        drop(cache);
+
+        let cache = DiskCache::open(dir.path(), "v1", 1 << 20, 60).unwrap();
+        assert!(cache.get(cache.url_hash("/old"), "/old").is_none());
+        assert!(!path.exists());
+        assert!(!legacy.exists());
+    }
+
+    #[test]
+    fn test_per_entry_ttl() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = DiskCache::open(dir.path(), "v1", 1 << 20, 60).unwrap();
+        let path = cache.dir.join("short.entry");
+        write_entry(&path, unix_now() - 30, 10, "/short", "stale").unwrap();
+        cache.insert_with_ttl(
+            cache.url_hash("/long"),
+            "/long",
+            "html",
+            Some(Duration::from_secs(3600)),
+        );
+        drop(cache);
+
+        // The entry's own TTL applies, not the cache TTL
+        let cache = DiskCache::open(dir.path(), "v1", 1 << 20, 60).unwrap();
+        assert!(cache.get(cache.url_hash("/short"), "/short").is_none());
+        let (_, expires_at) = cache
+            .get_with_expiry(cache.url_hash("/long"), "/long")
+            .unwrap();
+        assert!(expires_at.unwrap() > Instant::now() + Duration::from_secs(3000));
+    }
+
+    #[test]
+    fn test_size_bound_evicts_least_recent() {
+        let dir = tempfile::tempdir().unwrap();
+        let html = "x".repeat(1000);
+        // Entries may be compressed; bound the cache to 3.5 of them
+        let probe = DiskCache::open(dir.path(), "probe", u64::MAX, 0).unwrap();
+        probe.insert(probe.url_hash("/0"), "/0", &html);
+        let max_bytes = probe.size_bytes() * 7 / 2;
+        let cache = open(dir.path(), max_bytes);
+
+        cache.insert(cache.url_hash("/1"), "/1", &html);
+        cache.insert(cache.url_hash("/2"), "/2", &html);
+        cache.insert(cache.url_hash("/3"), "/3", &html);
+        let _ = cache.get(cache.url_hash("/1"), "/1");
+
+        let evicted = cache.insert(cache.url_hash("/4"), "/4", &html);
+        assert!(evicted >= 1);
+        assert!(cache.size_bytes() <= max_bytes);
+        assert!(
+            cache.get(cache.url_hash("/2"), "/2").is_none(),
+            "least recent is evicted"
+        );
+        assert!(cache.get(cache.url_hash("/4"), "/4").is_some());
+    }
+
+    #[test]
+    fn test_remove_by_prefix_and_clear() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = open(dir.path(), 1 << 20);
+
+        cache.insert(cache.url_hash("/products/1"), "/products/1", "p1");
+        cache.insert(cache.url_hash("/products/2"), "/products/2", "p2");
+        cache.insert(cache.url_hash("/about"), "/about", "about");
+
+        assert_eq!(cache.remove_by_prefix("/products"), 2);
+        assert_eq!(cache.len(), 1);
+
+        cache.clear();
+        assert!(cache.is_empty());
+        assert_eq!(cache.size_bytes(), 0);
+        assert_eq!(entry_files(&cache).len(), 0);
+    }
+
+    #[test]
+    fn test_truncated_entry_is_a_miss() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = open(dir.path(), 1 << 20);
+        cache.insert(cache.url_hash("/a"), "/a", "<h1>complete</h1>");
+
+        let path = entry_files(&cache).remove(0);
+        let len = fs::metadata(&path).unwrap().len();
+        fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_len(len - 4)
+            .unwrap();
+
+        assert!(cache.get(cache.url_hash("/a"), "/a").is_none());
+        assert!(cache.is_empty());
+        assert!(!path.exists());
+    }
+
+    #[test]
+    #[cfg(feature = "cache-compression")]
+    fn test_entries_are_compressed() {
+        let dir = tempfile::tempdir().unwrap();
+        let html = "<p>Hello</p>".repeat(1000);
+        let cache = open(dir.path(), 1 << 20);
+        cache.insert(cache.url_hash("/a"), "/a", &html);
+        assert!(cache.size_bytes() < html.len() as u64 / 4);
+
+        let cache = open(dir.path(), 1 << 20);
+        assert_eq!(
+            cache.get(cache.url_hash("/a"), "/a").as_deref(),
+            Some(html.as_str())
+        );
+    }
+
+    #[test]
+    fn test_concurrent_writes_use_separate_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = open(dir.path(), 1 << 20);
+
+        std::thread::scope(|scope| {
+            for i in 0..8 {
+                let cache = &cache;
+                scope.spawn(move || cache.insert(cache.url_hash("/a"), "/a", &format!("html {i}")));
+            }
+        });
+
+        let html = cache.get(cache.url_hash("/a"), "/a").unwrap();
+        assert!(html.starts_with("html "));
+        assert_eq!(cache.len(), 1);
+        assert_eq!(entry_files(&cache).len(), 1);
+    }
+
+    #[test]
+    fn test_write_after_removal_is_dropped() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = open(dir.path(), 1 << 20);
+
+        let ticket = cache.ticket();
+        cache.remove_by_prefix("/a");
+        cache.insert_ticketed(ticket, cache.url_hash("/a"), "/a", "stale", None);
+
+        assert!(cache.is_empty());
+        assert_eq!(entry_files(&cache).len(), 0);
+    }
+
+    #[test]
+    fn test_removal_keeps_writes_of_other_urls() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = open(dir.path(), 1 << 20);
+
+        let a = cache.ticket();
+        let b = cache.ticket();
+        cache.remove(cache.url_hash("/b"), "/b");
+        cache.remove_by_prefix("/c");
+        cache.insert_ticketed(a, cache.url_hash("/a"), "/a", "fresh", None);
+        cache.insert_ticketed(b, cache.url_hash("/b"), "/b", "stale", None);
+
+        assert_eq!(
+            cache.get(cache.url_hash("/a"), "/a").as_deref(),
+            Some("fresh")
+        );
+        assert!(cache.get(cache.url_hash("/b"), "/b").is_none());
+
+        // A later write stores the URL again
+        cache.insert(cache.url_hash("/b"), "/b", "new");
+        assert_eq!(
+            cache.get(cache.url_hash("/b"), "/b").as_deref(),
+            Some("new")
+        );
+    }
+
+    #[test]
+    fn test_later_write_wins() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = open(dir.path(), 1 << 20);
+
+        let first = cache.ticket();
+        let second = cache.ticket();
+        cache.insert_ticketed(second, cache.url_hash("/a"), "/a", "new", None);
+        cache.insert_ticketed(first, cache.url_hash("/a"), "/a", "old", None);
+
+        assert_eq!(
+            cache.get(cache.url_hash("/a"), "/a").as_deref(),
+            Some("new")
+        );
+        assert_eq!(entry_files(&cache).len(), 1);
+    }
+
+    #[test]
+    fn test_prune_namespaces() {
+        let dir = tempfile::tempdir().unwrap();
+        let v1 = open(dir.path(), 1 << 20);
+        v1.insert(v1.url_hash("/a"), "/a", "v1");
+        DiskCache::open(dir.path(), "v2", 1 << 20, 0).unwrap();
+        let foreign = dir.path().join("uploads");
+        fs::create_dir(&foreign).unwrap();
+        fs::write(foreign.join("photo.jpg"), b"jpg").unwrap();
+
+        // Recently used namespaces are kept
+        let pruned = DiskCache::prune_namespaces(dir.path(), "v2", Duration::from_secs(3600));
+        assert_eq!(pruned.unwrap(), 0);
+
+        let pruned = DiskCache::prune_namespaces(dir.path(), "v2", Duration::ZERO);
+        assert_eq!(pruned.unwrap(), 1);
+        assert!(!dir.path().join("v1").exists());
+        assert!(dir.path().join("v2").exists());
+        assert!(foreign.exists());
+    }
+
+    #[test]
+    fn test_hash_key_persists() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = open(dir.path(), 1 << 20);
+        let hash = cache.url_hash("/a");
+        cache.insert(hash, "/a", "html");
+        drop(cache);
+
+        let cache = open(dir.path(), 1 << 20);
+        assert_eq!(cache.url_hash("/a"), hash);
+        assert_eq!(cache.get(hash, "/a").as_deref(), Some("html"));
+        drop(cache);
+
+        // Another namespace gets its own key
+        let v2 = DiskCache::open(dir.path(), "v2", 1 << 20, 0).unwrap();
+        assert_ne!(v2.url_hash("/a"), hash);
+    }
+
+    #[test]
+    fn test_new_hash_key_clears_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut cache = open(dir.path(), 1 << 20);
+        cache.insert(cache.url_hash("/a"), "/a", "html");
+
+        let key = HashKey::random();
+        cache.set_hash_key(key).unwrap();
+        assert!(cache.is_empty());
+        assert_eq!(open(dir.path(), 1 << 20).hash_key(), key);
+
+        // A corrupt key file is replaced
+        fs::write(cache.dir.join(KEY_FILE), b"short").unwrap();
+        cache.insert(cache.url_hash("/b"), "/b", "html");
+        drop(cache);
+        let cache = open(dir.path(), 1 << 20);
+        assert_ne!(cache.hash_key(), key);
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_sanitize_namespace() {
+        assert_eq!(sanitize_namespace("v1.2.3"), "v1.2.3");
+        assert_eq!(sanitize_namespace("../etc"), ".._etc");
+        assert_eq!(sanitize_namespace(".."), "default");
+    }
+}
@@ -0,0 +1,549 @@
+//! Eviction policies for the cold cache
+//!
+//! By default the cold cache evicts approximately least recently used
+//! entries. LRU is easily flushed by scans of long-tail URLs (e.g.
+//! crawlers), so two scan-resistant policies are available:
+//!
+//! - [`TinyLfu`]: admits a new page only if it is requested at least as
+//!   often as the pages it would displace, and evicts the least frequently
+//!   used of a random sample of entries
+//! - [`S3Fifo`]: new pages enter a small probationary FIFO queue and are only
+//!   promoted to the main queue if requested again
+//!
+//! Select one with [`Eviction`], or implement [`EvictionPolicy`].
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::{self, Debug};
+use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use dashmap::DashMap;
+use parking_lot::Mutex;
+
+/// Eviction policy of the cold cache
+///
+/// Keys are URL hashes. All methods may be called concurrently.
+pub trait EvictionPolicy: Send + Sync + Debug {
+    /// A key was looked up, whether or not it is cached
+    fn on_access(&self, key: u64);
+
+    /// A new key was inserted
+    fn on_insert(&self, key: u64);
+
+    /// A key was removed outside of [`EvictionPolicy::victim`]
+    /// (invalidation or expiry). May be called for keys that are not tracked.
+    fn on_remove(&self, key: u64);
+
+    /// Whether a new key should be cached when the cache is full
+    fn admit(&self, _key: u64) -> bool {
+        true
+    }
+
+    /// Choose the next key to evict and stop tracking it
+    ///
+    /// Returns `None` if no key is tracked.
+    fn victim(&self) -> Option<u64>;
+
+    /// Stop tracking all keys
+    fn clear(&self);
+}
+
+/// Cold cache eviction policy selection
+///
+/// # Example
+/// ```rust
+/// use rusty_ssr::cache::{Eviction, SsrCache};
+///
+/// let cache = SsrCache::new(10_000).with_eviction(Eviction::S3Fifo);
+/// ```
+#[derive(Clone, Default)]
+pub enum Eviction {
+    /// Approximate LRU based on access timestamps (default)
+    #[default]
+    Lru,
+    /// Frequency-based admission and eviction, see [`TinyLfu`]
+    TinyLfu,
+    /// Small/main FIFO queues with quick demotion, see [`S3Fifo`]
+    S3Fifo,
+    /// A custom policy
+    Custom(Arc<dyn EvictionPolicy>),
+}
+
+impl Eviction {
+    /// Build the policy for a cache of `capacity` entries
+    ///
+    /// Returns `None` for [`Eviction::Lru`], which the cold cache implements
+    /// on its own access timestamps.
+    pub(crate) fn build(&self, capacity: usize) -> Option<Arc<dyn EvictionPolicy>> {
+        match self {
+            Eviction::Lru => None,
+            Eviction::TinyLfu => Some(Arc::new(TinyLfu::new(capacity))),
+            Eviction::S3Fifo => Some(Arc::new(S3Fifo::new(capacity))),
+            Eviction::Custom(policy) => Some(Arc::clone(policy)),
+        }
+    }
+}
+
+impl Debug for Eviction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Eviction::Lru => f.write_str("Lru"),
+            Eviction::TinyLfu => f.write_str("TinyLfu"),
+            Eviction::S3Fifo => f.write_str("S3Fifo"),
+            Eviction::Custom(policy) => f.debug_tuple("Custom").field(policy).finish(),
+        }
+    }
+}
+
+/// Number of entries compared when choosing a [`TinyLfu`] victim
+const SAMPLE_SIZE: usize = 8;
+
+/// Saturation value of the 4-bit frequency counters
+const MAX_FREQUENCY: u8 = 15;
+
+/// Odd multipliers deriving the four counter positions of a key
+const SKETCH_SEEDS: [u64; 4] = [
+    0x9E37_79B9_7F4A_7C15,
+    0xC2B2_AE3D_27D4_EB4F,
+    0x1656_67B1_9E37_79F9,
+    0xFF51_AFD7_ED55_8CCD,
+];
+
+/// Count-min sketch of approximate access frequencies
+///
+/// Counters saturate at 15 and are halved every `10 × capacity`
+/// increments, so the sketch follows changes in popularity.
+struct FrequencySketch {
+    counters: Box<[AtomicU8]>,
+    shift: u32,
+    additions: AtomicUsize,
+    reset_after: usize,
+}
+
+impl FrequencySketch {
+    fn new(capacity: usize) -> Self {
+        let width = (capacity.max(16) * 16).next_power_of_two();
+        Self {
+            counters: (0..width).map(|_| AtomicU8::new(0)).collect(),
+            shift: 64 - width.trailing_zeros(),
+            additions: AtomicUsize::new(0),
+            reset_after: capacity.max(16) * 10,
+        }
+    }
+
+    #[inline]
+    fn positions(&self, key: u64) -> impl Iterator<Item = usize> + '_ {
+        // splitmix64 finalizer, so that similar keys spread out
+        let mut key = key;
+        key = (key ^ (key >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        key = (key ^ (key >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        key ^= key >> 31;
+        SKETCH_SEEDS
+            .iter()
+            .map(move |seed| (key.wrapping_mul(*seed) >> self.shift) as usize)
+    }
+
+    /// Conservative update: only the smallest counters of the key grow,
+    /// which limits overestimation from collisions
+    fn increment(&self, key: u64) {
+        let min = self.estimate(key);
+        if min < MAX_FREQUENCY {
+            for i in self.positions(key) {
+                let _ = self.counters[i].compare_exchange(
+                    min,
+                    min + 1,
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                );
+            }
+        }
+
+        if self.additions.fetch_add(1, Ordering::Relaxed) + 1 >= self.reset_after
+            && self
+                .additions
+                .compare_exchange(self.reset_after, 0, Ordering::Relaxed, Ordering::Relaxed)
+                .is_ok()
+        {
+            // Age all counters; racing increments may be lost, which is fine
+            for counter in self.counters.iter() {
+                counter.store(counter.load(Ordering::Relaxed) >> 1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    fn estimate(&self, key: u64) -> u8 {
+        self.positions(key)
+            .map(|i| self.counters[i].load(Ordering::Relaxed))
+            .min()
+            .unwrap_or(0)
+    }
+
+    fn clear(&self) {
+        for counter in self.counters.iter() {
+            counter.store(0, Ordering::Relaxed);
+        }
+        self.additions.store(0, Ordering::Relaxed);
+    }
+}
+
+/// Cached keys with O(1) insert, removal and random sampling
+#[derive(Default)]
+struct SampledKeys {
+    keys: Vec<u64>,
+    index: HashMap<u64, usize>,
+    rng: u64,
+}
+
+impl SampledKeys {
+    fn insert(&mut self, key: u64) {
+        if !self.index.contains_key(&key) {
+            self.index.insert(key, self.keys.len());
+            self.keys.push(key);
+        }
+    }
+
+    fn remove(&mut self, key: u64) {
+        if let Some(i) = self.index.remove(&key) {
+            self.keys.swap_remove(i);
+            if let Some(&moved) = self.keys.get(i) {
+                self.index.insert(moved, i);
+            }
+        }
+    }
+
+    /// xorshift64*
+    fn next_index(&mut self) -> usize {
+        self.rng ^= self.rng >> 12;
+        self.rng ^= self.rng << 25;
+        self.rng ^= self.rng >> 27;
+        (self.rng.wrapping_mul(0x2545_F491_4F6C_DD1D) % self.keys.len() as u64) as usize
+    }
+
+    /// The sampled key with the lowest frequency
+    fn coldest(&mut self, sketch: &FrequencySketch) -> Option<(u64, u8)> {
+        if self.keys.is_empty() {
+            return None;
+        }
+        (0..SAMPLE_SIZE.min(self.keys.len()))
+            .map(|_| {
+                let i = self.next_index();
+                let key = self.keys[i];
+                (key, sketch.estimate(key))
+            })
+            .min_by_key(|&(_, frequency)| frequency)
+    }
+}
+
+/// TinyLFU admission with sampled LFU eviction
+///
+/// Access frequencies are tracked in a compact count-min sketch that
+/// also remembers pages which are not cached. When the cache is full, a
+/// new page is only admitted if it has been requested at least as often as
+/// the least frequent of a random sample of cached pages, so one-off
+/// requests cannot displace popular pages. Recording an access is lock-free.
+pub struct TinyLfu {
+    sketch: FrequencySketch,
+    keys: Mutex<SampledKeys>,
+}
+
+impl TinyLfu {
+    /// Create a policy for a cache of `capacity` entries
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            sketch: FrequencySketch::new(capacity),
+            keys: Mutex::new(SampledKeys {
+                rng: 0x853C_49E6_748F_EA9B,
+                ..SampledKeys::default()
+            }),
+        }
+    }
+}
+
+impl Debug for TinyLfu {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TinyLfu")
+            .field("entries", &self.keys.lock().keys.len())
+            .finish()
+    }
+}
+
+impl EvictionPolicy for TinyLfu {
+    fn on_access(&self, key: u64) {
+        self.sketch.increment(key);
+    }
+
+    fn on_insert(&self, key: u64) {
+        self.keys.lock().insert(key);
+    }
+
+    fn on_remove(&self, key: u64) {
+        self.keys.lock().remove(key);
+    }
+
+    fn admit(&self, key: u64) -> bool {
+        let candidate = self.sketch.estimate(key);
+        self.keys
+            .lock()
+            .coldest(&self.sketch)
+            .is_none_or(|(_, victim)| candidate >= victim)
+    }
+
+    fn victim(&self) -> Option<u64> {
+        let mut keys = self.keys.lock();
+        let (key, _) = keys.coldest(&self.sketch)?;
+        keys.remove(key);
+        Some(key)
+    }
+
+    fn clear(&self) {
+        let mut keys = self.keys.lock();
+        keys.keys.clear();
+        keys.index.clear();
+        self.sketch.clear();
+    }
+}
+
+/// Share of the capacity reserved for the [`S3Fifo`] probationary queue
+const SMALL_QUEUE_PERCENT: usize = 10;
+
+/// Saturation value of the [`S3Fifo`] access counters
+const MAX_S3_FREQUENCY: u8 = 3;
+
+/// Queue position of a tracked [`S3Fifo`] key
+struct S3Entry {
+    /// Matches the queue slot that currently owns the key
+    id: u64,
+    frequency: AtomicU8,
+    main: bool,
+}
+
+#[derive(Default)]
+struct S3Queues {
+    /// Probationary queue of `(key, id)`, oldest first
+    small: VecDeque<(u64, u64)>,
+    /// Main queue of `(key, id)`, oldest first
+    main: VecDeque<(u64, u64)>,
+    /// Recently evicted keys, oldest first
+    ghost: VecDeque<u64>,
+    ghost_keys: HashSet<u64>,
+    small_len: usize,
+    next_id: u64,
+}
+
+/// S3-FIFO: simple, scalable eviction with three static FIFO queues
+///
+/// New pages enter a small probationary queue (10% of the capacity). Pages
+/// requested again before they reach its end move to the main queue, the
+/// rest are evicted early and remembered in a ghost queue, so a page that
+/// returns soon after goes straight to the main queue. Main-queue pages
+/// that were requested since their last pass are reinserted instead of
+/// evicted. Recording an access is a single atomic increment.
+pub struct S3Fifo {
+    entries: DashMap<u64, S3Entry>,
+    queues: Mutex<S3Queues>,
+    small_capacity: usize,
+    ghost_capacity: usize,
+}
+
+impl S3Fifo {
+    /// Create a policy for a cache of `capacity` entries
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: DashMap::new(),
+            queues: Mutex::new(S3Queues::default()),
+            small_capacity: (capacity * SMALL_QUEUE_PERCENT / 100).max(1),
+            ghost_capacity: capacity.max(1),
+        }
+    }
+
+    /// Evict from the probationary queue, promoting re-requested keys
+    fn evict_small(&self, queues: &mut S3Queues) -> Option<u64> {
+        while let Some((key, id)) = queues.small.pop_front() {
+            let Some(mut entry) = self.entries.get_mut(&key).filter(|e| e.id == id) else {
+                continue; // removed or re-inserted since
+            };
+            queues.small_len -= 1;
+
+            if entry.frequency.load(Ordering::Relaxed) > 1 {
+                entry.main = true;
+                entry.frequency.store(0, Ordering::Relaxed);
+                queues.main.push_back((key, id));
+                continue;
+            }
+
+            drop(entry);
+            self.entries.remove(&key);
+            if queues.ghost_keys.insert(key) {
+                queues.ghost.push_back(key);
+                if queues.ghost.len() > self.ghost_capacity {
+                    if let Some(old) = queues.ghost.pop_front() {
+                        queues.ghost_keys.remove(&old);
+                    }
+                }
+            }
+            return Some(key);
+        }
+        None
+    }
+
+    /// Evict from the main queue, giving re-requested keys another pass
+    fn evict_main(&self, queues: &mut S3Queues) -> Option<u64> {
+        while let Some((key, id)) = queues.main.pop_front() {
+            let Some(entry) = self.entries.get(&key).filter(|e| e.id == id) else {
+                continue;
+            };
+
+            let frequency = entry.frequency.load(Ordering::Relaxed);
+            if frequency > 0 {
+                entry.frequency.store(frequency - 1, Ordering::Relaxed);
+                queues.main.push_back((key, id));
+                continue;
+            }
+
+            drop(entry);
+            self.entries.remove(&key);
+            return Some(key);
+        }
+        None
+    }
+}
+
+impl Debug for S3Fifo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("S3Fifo")
+            .field("entries", &self.entries.len())
+            .field("small_capacity", &self.small_capacity)
+            .finish()
+    }
+}
+
+impl EvictionPolicy for S3Fifo {
+    fn on_access(&self, key: u64) {
+        if let Some(entry) = self.entries.get(&key) {
+            let _ = entry
+                .frequency
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |f| {
+                    (f < MAX_S3_FREQUENCY).then_some(f + 1)
+                });
+        }
+    }
+
+    fn on_insert(&self, key: u64) {
+        let mut queues = self.queues.lock();
+        let id = queues.next_id;
+        queues.next_id += 1;
+
+        let main = queues.ghost_keys.remove(&key);
+        if main {
+            queues.main.push_back((key, id));
+        } else {
+            queues.small.push_back((key, id));
+            queues.small_len += 1;
+        }
+
+        let entry = S3Entry {
+            id,
+            frequency: AtomicU8::new(0),
+            main,
+        };
+        if let Some(previous) = self.entries.insert(key, entry) {
+            if !previous.main {
+                queues.small_len -= 1;
+            }
+        }
+    }
+
+    fn on_remove(&self, key: u64) {
+        let mut queues = self.queues.lock();
+        if let Some((_, entry)) = self.entries.remove(&key) {
+            if !entry.main {
+                queues.small_len -= 1;
+            }
+        }
+    }
+
+    fn victim(&self) -> Option<u64> {
+        let mut queues = self.queues.lock();
+        if queues.small_len >= self.small_capacity || queues.small_len == self.entries.len() {
+            self.evict_small(&mut queues)
+                .or_else(|| self.evict_main(&mut queues))
+        } else {
+            self.evict_main(&mut queues)
+                .or_else(|| self.evict_small(&mut queues))
+        }
+    }
+
+    fn clear(&self) {
+        let mut queues = self.queues.lock();
+        *queues = S3Queues::default();
+        self.entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tinylfu_rejects_one_off_keys() {
+        let policy = TinyLfu::new(100);
+        for key in 0..100 {
+            for _ in 0..5 {
+                policy.on_access(key);
+            }
+            policy.on_insert(key);
+        }
+
+        // A key seen once does not displace popular ones
+        policy.on_access(1000);
+        assert!(!policy.admit(1000));
+
+        for _ in 0..10 {
+            policy.on_access(1001);
+        }
+        assert!(policy.admit(1001));
+
+        let victim = policy.victim().unwrap();
+        assert!(victim < 100);
+        policy.on_remove(victim); // already untracked, no-op
+        assert_eq!(policy.keys.lock().keys.len(), 99);
+    }
+
+    #[test]
+    fn test_s3fifo_demotes_scans_quickly() {
+        let policy = S3Fifo::new(10);
+
+        // Popular keys, requested again while in the probationary queue
+        for key in 0..5 {
+            policy.on_insert(key);
+            policy.on_access(key);
+            policy.on_access(key);
+        }
+        // A scan of one-off keys
+        for key in 100..105 {
+            policy.on_insert(key);
+        }
+
+        let victims: Vec<u64> = (0..5).map(|_| policy.victim().unwrap()).collect();
+        assert_eq!(victims, vec![100, 101, 102, 103, 104]);
+
+        // Evicted keys returning soon after go straight to the main queue
+        policy.on_insert(100);
+        assert!(policy.entries.get(&100).unwrap().main);
+    }
+
+    #[test]
+    fn test_s3fifo_remove_and_clear() {
+        let policy = S3Fifo::new(10);
+        policy.on_insert(1);
+        policy.on_insert(2);
+        policy.on_remove(1);
+
+        assert_eq!(policy.victim(), Some(2));
+        assert_eq!(policy.victim(), None);
+
+        policy.on_insert(3);
+        policy.clear();
+        assert_eq!(policy.victim(), None);
+    }
+}
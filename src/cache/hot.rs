@@ -5,11 +5,16 @@
 //! - Hot: HashMap for O(1) lookup on more entries (~5-10ns access)
 //!
 //! Total capacity: 128 entries per thread
+//!
+//! Entries are keyed by URL hash, and the URL is compared on lookup, so a
+//! hash collision is a miss rather than another page's HTML.
 
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
+use super::inspect::EntryStats;
+
 /// Maximum entries in ultra-hot array (fits in 2 cache lines)
 const ULTRA_HOT_SIZE: usize = 8;
 
@@ -37,8 +42,11 @@ pub struct HotCache {
 #[derive(Clone)]
 struct HotEntry {
     url_hash: u64,
+    url: Box<str>,
     html: Arc<str>,
-    created_at: Instant,
+    expires_at: Option<Instant>,
+    /// Access stats shared with the cold cache entry
+    stats: Option<Arc<EntryStats>>,
 }
 
 impl HotCache {
@@ -68,31 +76,33 @@ impl HotCache {
         }
     }
 
-    /// Look up HTML by URL hash
+    /// Look up HTML by URL hash, verifying the URL
     ///
     /// Checks ultra-hot array first (fastest), then HashMap
     #[inline(always)]
-    pub fn get(&mut self, url_hash: u64) -> Option<Arc<str>> {
+    pub fn get(&mut self, url_hash: u64, url: &str) -> Option<Arc<str>> {
         // Tier 1: Check ultra-hot array first (linear scan, but only 8 entries)
         for entry in self.ultra_hot.iter().flatten() {
-            if entry.url_hash == url_hash {
-                if self.is_expired(entry) {
+            if entry.matches(url_hash, url) {
+                if entry.is_expired() {
                     return None;
                 }
+                entry.record_hit();
                 return Some(Arc::clone(&entry.html));
             }
         }
 
         // Tier 2: Check HashMap (O(1) lookup)
-        if let Some(entry) = self.hot_map.get(&url_hash) {
-            if self.is_expired(entry) {
+        if let Some(entry) = self.hot_map.get(&url_hash).filter(|e| *e.url == *url) {
+            if entry.is_expired() {
                 self.hot_map.remove(&url_hash);
                 return None;
             }
 
             // Promote to ultra-hot on access (LRU behavior)
+            entry.record_hit();
             let html = Arc::clone(&entry.html);
-            self.promote_to_ultra_hot(url_hash, Arc::clone(&html));
+            self.promote_to_ultra_hot(url_hash);
             return Some(html);
         }
 
@@ -101,36 +111,94 @@ impl HotCache {
 
     /// Look up without promotion (for read-only access)
     #[inline(always)]
-    pub fn peek(&self, url_hash: u64) -> Option<Arc<str>> {
+    pub fn peek(&self, url_hash: u64, url: &str) -> Option<Arc<str>> {
+        self.peek_with_expiry(url_hash, url).map(|(html, _)| html)
+    }
+
+    /// Look up without promotion, also returning when the entry expires
+    ///
+    /// Counts the hit in the page's shared access stats, if any.
+    #[inline(always)]
+    pub fn peek_with_expiry(
+        &self,
+        url_hash: u64,
+        url: &str,
+    ) -> Option<(Arc<str>, Option<Instant>)> {
         // Check ultra-hot first
-        for entry in self.ultra_hot.iter().flatten() {
-            if entry.url_hash == url_hash {
-                if self.is_expired(entry) {
-                    return None;
-                }
-                return Some(Arc::clone(&entry.html));
-            }
-        }
+        let entry = match self
+            .ultra_hot
+            .iter()
+            .flatten()
+            .find(|e| e.matches(url_hash, url))
+        {
+            Some(entry) => entry,
+            // Check HashMap
+            None => self.hot_map.get(&url_hash).filter(|e| *e.url == *url)?,
+        };
 
-        // Check HashMap
-        if let Some(entry) = self.hot_map.get(&url_hash) {
-            if self.is_expired(entry) {
-                return None;
-            }
-            return Some(Arc::clone(&entry.html));
+        if entry.is_expired() {
+            return None;
         }
-
-        None
+        entry.record_hit();
+        Some((Arc::clone(&entry.html), entry.expires_at))
     }
 
     /// Insert a new entry
     #[inline(always)]
-    pub fn insert(&mut self, url_hash: u64, html: Arc<str>) {
-        let entry = HotEntry {
+    pub fn insert(&mut self, url_hash: u64, url: &str, html: Arc<str>) {
+        self.insert_with_ttl(url_hash, url, html, None);
+    }
+
+    /// Insert a new entry that expires after `ttl` instead of the cache TTL
+    ///
+    /// `None` uses the cache TTL.
+    #[inline(always)]
+    pub fn insert_with_ttl(
+        &mut self,
+        url_hash: u64,
+        url: &str,
+        html: Arc<str>,
+        ttl: Option<Duration>,
+    ) {
+        self.insert_with_stats(url_hash, url, html, ttl, None);
+    }
+
+    /// Insert a copy of a cold cache entry, sharing its access stats
+    #[inline(always)]
+    pub(crate) fn insert_with_stats(
+        &mut self,
+        url_hash: u64,
+        url: &str,
+        html: Arc<str>,
+        ttl: Option<Duration>,
+        stats: Option<Arc<EntryStats>>,
+    ) {
+        let expires_at = ttl.or(self.ttl).map(|ttl| Instant::now() + ttl);
+        self.insert_entry(HotEntry {
             url_hash,
+            url: Box::from(url),
             html,
-            created_at: Instant::now(),
-        };
+            expires_at,
+            stats,
+        });
+    }
+
+    #[inline(always)]
+    fn insert_entry(&mut self, entry: HotEntry) {
+        // Replace an entry of the same URL hash rather than shadowing it
+        if let Some(slot) = self
+            .ultra_hot
+            .iter_mut()
+            .find(|slot| slot.as_ref().is_some_and(|e| e.url_hash == entry.url_hash))
+        {
+            *slot = Some(entry);
+            return;
+        }
+        if self.hot_map.remove(&entry.url_hash).is_some() {
+            if let Some(pos) = self.access_order.iter().position(|&k| k == entry.url_hash) {
+                self.access_order.remove(pos);
+            }
+        }
 
         // Always insert into ultra-hot first
         // Evicted entry goes to hot_map
@@ -157,26 +225,18 @@ impl HotCache {
         self.hot_map.insert(entry.url_hash, entry);
     }
 
-    /// Promote an entry from hot_map to ultra-hot
-    fn promote_to_ultra_hot(&mut self, url_hash: u64, html: Arc<str>) {
+    /// Promote an entry from hot_map to ultra-hot, keeping its expiry
+    fn promote_to_ultra_hot(&mut self, url_hash: u64) {
         // Remove from hot_map
-        self.hot_map.remove(&url_hash);
+        let Some(entry) = self.hot_map.remove(&url_hash) else {
+            return;
+        };
         if let Some(pos) = self.access_order.iter().position(|&k| k == url_hash) {
             self.access_order.remove(pos);
         }
 
         // Insert into ultra-hot (this will move current ultra-hot entry to hot_map)
-        self.insert(url_hash, html);
-    }
-
-    /// Check if entry is expired
-    #[inline(always)]
-    fn is_expired(&self, entry: &HotEntry) -> bool {
-        if let Some(ttl) = self.ttl {
-            entry.created_at.elapsed() > ttl
-        } else {
-            false
-        }
+        self.insert_entry(entry);
     }
 
     /// Get total number of cached entries
@@ -202,6 +262,28 @@ impl HotCache {
     }
 }
 
+impl HotEntry {
+    /// Whether this entry holds `url` (not merely a URL with the same hash)
+    #[inline(always)]
+    fn matches(&self, url_hash: u64, url: &str) -> bool {
+        self.url_hash == url_hash && *self.url == *url
+    }
+
+    /// Check if entry is expired
+    #[inline(always)]
+    fn is_expired(&self) -> bool {
+        self.expires_at.is_some_and(|at| Instant::now() >= at)
+    }
+
+    /// Count a hit in the shared access stats
+    #[inline(always)]
+    fn record_hit(&self) {
+        if let Some(stats) = &self.stats {
+            stats.record_hit();
+        }
+    }
+}
+
 impl Default for HotCache {
     fn default() -> Self {
         Self::new()
@@ -217,10 +299,10 @@ mod tests {
         let mut cache = HotCache::new();
         let html: Arc<str> = "test".into();
 
-        cache.insert(123, Arc::clone(&html));
+        cache.insert(123, "/test", Arc::clone(&html));
 
-        assert!(cache.get(123).is_some());
-        assert!(cache.get(456).is_none());
+        assert!(cache.get(123, "/test").is_some());
+        assert!(cache.get(456, "/other").is_none());
     }
 
     #[test]
@@ -230,14 +312,17 @@ mod tests {
         // Insert more than 8 entries
         for i in 0..10u64 {
             let html: Arc<str> = format!("html{}", i).into();
-            cache.insert(i, html);
+            cache.insert(i, &format!("/{}", i), html);
         }
 
         // First 2 should be in hot_map, not ultra_hot
         // But still accessible via get()
-        assert!(cache.get(0).is_some(), "Entry 0 should be in hot_map");
-        assert!(cache.get(1).is_some(), "Entry 1 should be in hot_map");
-        assert!(cache.get(9).is_some(), "Entry 9 should be in ultra_hot");
+        assert!(cache.get(0, "/0").is_some(), "Entry 0 should be in hot_map");
+        assert!(cache.get(1, "/1").is_some(), "Entry 1 should be in hot_map");
+        assert!(
+            cache.get(9, "/9").is_some(),
+            "Entry 9 should be in ultra_hot"
+        );
     }
 
     #[test]
@@ -246,20 +331,20 @@ mod tests {
 
         // Fill ultra_hot
         for i in 0..8u64 {
-            cache.insert(i, format!("html{}", i).into());
+            cache.insert(i, &format!("/{}", i), format!("html{}", i).into());
         }
 
         // Add more to push to hot_map
         for i in 8..16u64 {
-            cache.insert(i, format!("html{}", i).into());
+            cache.insert(i, &format!("/{}", i), format!("html{}", i).into());
         }
 
         // Entry 0 should be in hot_map now
         // Accessing it should promote it back to ultra_hot
-        let _ = cache.get(0);
+        let _ = cache.get(0, "/0");
 
         // Verify it's accessible
-        assert!(cache.peek(0).is_some());
+        assert!(cache.peek(0, "/0").is_some());
     }
 
     #[test]
@@ -268,7 +353,7 @@ mod tests {
 
         // Insert 200 entries (more than 128 capacity)
         for i in 0..200u64 {
-            cache.insert(i, format!("html{}", i).into());
+            cache.insert(i, &format!("/{}", i), format!("html{}", i).into());
         }
 
         // Should have at most 128 + 8 = 136 entries
@@ -281,11 +366,46 @@ mod tests {
 
         // Fill with 100 entries
         for i in 0..100u64 {
-            cache.insert(i, format!("html{}", i).into());
+            cache.insert(i, &format!("/{}", i), format!("html{}", i).into());
         }
 
         // Access entry that's definitely in hot_map
         // This should be O(1), not O(n)
-        assert!(cache.peek(50).is_some());
+        assert!(cache.peek(50, "/50").is_some());
+    }
+
+    #[test]
+    fn test_hash_collision_is_a_miss() {
+        let mut cache = HotCache::new();
+        cache.insert(7, "/a", "page a".into());
+
+        // Same hash, different URL
+        assert!(cache.get(7, "/b").is_none());
+        assert!(cache.peek(7, "/b").is_none());
+
+        // Push /a into the HashMap tier
+        for i in 100..110u64 {
+            cache.insert(i, &format!("/{}", i), "filler".into());
+        }
+        assert!(cache.peek(7, "/b").is_none());
+        assert_eq!(cache.get(7, "/a").as_deref(), Some("page a"));
+    }
+
+    #[test]
+    fn test_per_entry_ttl() {
+        let mut cache = HotCache::with_ttl(3600);
+        cache.insert(1, "/default", "default".into());
+        cache.insert_with_ttl(2, "/short", "short".into(), Some(Duration::ZERO));
+
+        assert!(cache.peek(1, "/default").is_some());
+        assert!(cache.peek(2, "/short").is_none());
+
+        let (_, expires_at) = cache.peek_with_expiry(1, "/default").unwrap();
+        assert!(expires_at.unwrap() > Instant::now() + Duration::from_secs(3500));
+
+        // Re-inserting replaces the entry
+        cache.insert(1, "/default", "updated".into());
+        assert_eq!(cache.peek(1, "/default").as_deref(), Some("updated"));
+        assert_eq!(cache.len(), 2);
     }
 }
@@ -0,0 +1,163 @@
+//! Inspection of cached pages
+//!
+//! Per-page access statistics, entry listings and snapshots for admin
+//! tooling. Listing and peeking never mark pages as used, so inspecting
+//! the cache does not change what gets evicted.
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+
+/// Default page size of [`EntryQuery`]
+const DEFAULT_LIMIT: usize = 100;
+
+/// Access statistics of a cached page
+///
+/// Shared between the cold entry and its hot cache copies, so hits in
+/// every tier are counted.
+pub(crate) struct EntryStats {
+    created_at: Instant,
+    hits: AtomicU64,
+    /// Milliseconds after `created_at` of the last hit
+    last_hit_ms: AtomicU64,
+}
+
+impl EntryStats {
+    pub(crate) fn new() -> Self {
+        Self {
+            created_at: Instant::now(),
+            hits: AtomicU64::new(0),
+            last_hit_ms: AtomicU64::new(0),
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn record_hit(&self) {
+        self.hits.fetch_add(1, Ordering::Relaxed);
+        self.last_hit_ms.store(
+            self.created_at.elapsed().as_millis() as u64,
+            Ordering::Relaxed,
+        );
+    }
+
+    /// Fill in the age, hit count and idle time of `info`
+    pub(crate) fn describe(&self, info: &mut CacheEntryInfo) {
+        let age_ms = self.created_at.elapsed().as_millis() as u64;
+        info.age_secs = age_ms / 1000;
+        info.hits = self.hits.load(Ordering::Relaxed);
+        info.idle_secs = age_ms.saturating_sub(self.last_hit_ms.load(Ordering::Relaxed)) / 1000;
+    }
+}
+
+/// Metadata of a cached page
+#[derive(Debug, Clone, Default, Serialize, PartialEq, Eq)]
+pub struct CacheEntryInfo {
+    /// Cache key (the URL, plus any route variant suffix)
+    pub url: String,
+    /// Approximate memory used by the entry, compressed variants included
+    pub size_bytes: usize,
+    /// Seconds since the page was cached
+    pub age_secs: u64,
+    /// Hits in the hot and cold tiers
+    pub hits: u64,
+    /// Seconds since the last hit, or since the page was cached
+    pub idle_secs: u64,
+    /// Seconds until the page expires (`None` = never)
+    pub expires_in_secs: Option<u64>,
+    /// `route:<pattern>` of the matching cache route and `encoding:<name>`
+    /// of each stored compressed variant
+    pub tags: Vec<String>,
+}
+
+/// A cached page with its metadata, see [`SsrCache::peek_entry`](super::SsrCache::peek_entry)
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct CachedPage {
+    /// Entry metadata
+    #[serde(flatten)]
+    pub info: CacheEntryInfo,
+    /// Cached HTML
+    pub html: String,
+}
+
+/// Filter and page of [`SsrCache::entries`](super::SsrCache::entries)
+///
+/// Deserializes from query strings like `?prefix=/blog&offset=100&limit=50`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct EntryQuery {
+    /// Only list URLs starting with this prefix
+    pub prefix: Option<String>,
+    /// Number of matching entries to skip
+    pub offset: usize,
+    /// Maximum number of entries to return (default 100)
+    pub limit: usize,
+}
+
+impl Default for EntryQuery {
+    fn default() -> Self {
+        Self {
+            prefix: None,
+            offset: 0,
+            limit: DEFAULT_LIMIT,
+        }
+    }
+}
+
+impl EntryQuery {
+    /// List the first 100 entries
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only list URLs starting with `prefix`
+    pub fn prefix<S: Into<String>>(mut self, prefix: S) -> Self {
+        self.prefix = Some(prefix.into());
+        self
+    }
+
+    /// Skip the first `offset` matching entries
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Return at most `limit` entries
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+}
+
+/// One page of cache entries, sorted by URL
+#[derive(Debug, Clone, Serialize)]
+pub struct EntryPage {
+    /// Number of entries matching the prefix
+    pub total: usize,
+    /// Offset of the first returned entry
+    pub offset: usize,
+    /// The entries
+    pub entries: Vec<CacheEntryInfo>,
+}
+
+/// Portable copy of the in-memory cache
+///
+/// Export with [`SsrCache::export_snapshot`](super::SsrCache::export_snapshot)
+/// and load into another instance (or after a restart) with
+/// [`SsrCache::import_snapshot`](super::SsrCache::import_snapshot).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheSnapshot {
+    /// The cached pages
+    pub entries: Vec<SnapshotEntry>,
+}
+
+/// A page in a [`CacheSnapshot`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotEntry {
+    /// Cache key
+    pub url: String,
+    /// Cached HTML
+    pub html: String,
+    /// Seconds the page had left to live (`None` = no expiry, imported
+    /// with the cache TTL)
+    pub ttl_secs: Option<u64>,
+}
@@ -0,0 +1,138 @@
+//! Cross-instance cache invalidation
+//!
+//! An [`InvalidationBus`] carries invalidations between replicas: the
+//! engine publishes every `invalidate`, `invalidate_prefix` and
+//! `clear_cache`, and applies the ones received from other replicas to its
+//! local cache.
+
+use std::fmt::Debug;
+use std::io;
+
+use futures::future::{self, BoxFuture, FutureExt};
+use futures::stream::{self, BoxStream, StreamExt};
+use tokio::sync::broadcast;
+
+/// A cache invalidation sent between replicas
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Invalidation {
+    /// A single URL
+    Url(String),
+    /// Every URL starting with the prefix
+    Prefix(String),
+    /// The whole cache
+    All,
+}
+
+impl Invalidation {
+    /// Encode as a text message (`url:/page`, `prefix:/blog`, `all`)
+    pub fn to_message(&self) -> String {
+        match self {
+            Invalidation::Url(url) => format!("url:{}", url),
+            Invalidation::Prefix(prefix) => format!("prefix:{}", prefix),
+            Invalidation::All => "all".to_string(),
+        }
+    }
+
+    /// Decode a message produced by [`Invalidation::to_message`]
+    pub fn from_message(message: &str) -> Option<Self> {
+        if let Some(url) = message.strip_prefix("url:") {
+            Some(Invalidation::Url(url.to_string()))
+        } else if let Some(prefix) = message.strip_prefix("prefix:") {
+            Some(Invalidation::Prefix(prefix.to_string()))
+        } else if message == "all" {
+            Some(Invalidation::All)
+        } else {
+            None
+        }
+    }
+}
+
+/// Channel that broadcasts cache invalidations to every replica
+///
+/// A replica may receive its own invalidations back; applying an
+/// invalidation twice is harmless. Implementations that can miss messages
+/// (e.g. while reconnecting) should deliver [`Invalidation::All`] once they
+/// recover.
+pub trait InvalidationBus: Send + Sync + Debug {
+    /// Send an invalidation to all subscribers
+    fn publish<'a>(&'a self, invalidation: &'a Invalidation) -> BoxFuture<'a, io::Result<()>>;
+
+    /// Receive invalidations published from now on
+    fn subscribe(&self) -> BoxStream<'static, Invalidation>;
+}
+
+/// In-process [`InvalidationBus`]
+///
+/// Connects engines within the same process, e.g. in tests.
+#[derive(Debug)]
+pub struct InProcessBus {
+    sender: broadcast::Sender<Invalidation>,
+}
+
+impl InProcessBus {
+    /// Create a bus buffering up to 1024 invalidations per subscriber
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(1024);
+        Self { sender }
+    }
+}
+
+impl Default for InProcessBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InvalidationBus for InProcessBus {
+    fn publish<'a>(&'a self, invalidation: &'a Invalidation) -> BoxFuture<'a, io::Result<()>> {
+        // No subscribers is not an error
+        let _ = self.sender.send(invalidation.clone());
+        future::ready(Ok(())).boxed()
+    }
+
+    fn subscribe(&self) -> BoxStream<'static, Invalidation> {
+        stream::unfold(self.sender.subscribe(), |mut receiver| async move {
+            match receiver.recv().await {
+                Ok(invalidation) => Some((invalidation, receiver)),
+                // Invalidations were dropped, so anything may be stale
+                Err(broadcast::error::RecvError::Lagged(_)) => Some((Invalidation::All, receiver)),
+                Err(broadcast::error::RecvError::Closed) => None,
+            }
+        })
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_roundtrip() {
+        for invalidation in [
+            Invalidation::Url("/products/1?a=b".into()),
+            Invalidation::Prefix("/blog".into()),
+            Invalidation::All,
+        ] {
+            assert_eq!(
+                Invalidation::from_message(&invalidation.to_message()),
+                Some(invalidation)
+            );
+        }
+        assert_eq!(Invalidation::from_message("bogus"), None);
+    }
+
+    #[tokio::test]
+    async fn test_in_process_bus() {
+        let bus = InProcessBus::new();
+        let mut a = bus.subscribe();
+        let mut b = bus.subscribe();
+
+        bus.publish(&Invalidation::Url("/page".into()))
+            .await
+            .unwrap();
+
+        assert_eq!(a.next().await, Some(Invalidation::Url("/page".into())));
+        assert_eq!(b.next().await, Some(Invalidation::Url("/page".into())));
+    }
+}
@@ -3,21 +3,61 @@
 //! ## Architecture
 //!
 //! ```text
-//! Request ──► Hot Cache (L1/L2 CPU) ──► Cold Cache (RAM) ──► Miss
-//!              │ ~1-3ns latency        │ ~100ns latency
-//!              │ 8 entries/thread      │ N entries shared
-//!              └───────────────────────┘
+//! Request ──► Hot Cache (L1/L2 CPU) ──► Cold Cache (RAM) ──► Disk Cache ──► Miss
+//!              │ ~1-3ns latency        │ ~100ns latency     │ ~10-100µs (optional)
+//!              │ 8 entries/thread      │ N entries shared   │ byte-bounded, persistent
+//!              └───────────────────────┴────────────────────┘
 //! ```
 //!
 //! - **Hot Cache**: Thread-local, fits in L1/L2 CPU cache (~4KB per thread)
-//! - **Cold Cache**: Shared RAM cache with DashMap for lock-free access
-//! - **Auto-promotion**: Cold hits are promoted to hot cache
+//! - **Cold Cache**: Shared RAM cache with DashMap for lock-free access,
+//!   approximate LRU or a scan-resistant [`Eviction`](crate::cache::Eviction) policy
+//! - **Disk Cache**: Optional persistent tier that survives restarts
+//! - **Shared Backend**: Optional [`CacheBackend`](crate::cache::CacheBackend)
+//!   shared between replicas (e.g. `RedisBackend` with the `redis-backend`
+//!   feature), consulted after a local miss
+//! - **Invalidation Bus**: Optional [`InvalidationBus`](crate::cache::InvalidationBus)
+//!   that broadcasts invalidations to every replica
+//! - **Compression**: Optional pre-compressed variants (brotli, gzip, zstd)
+//!   computed once per page with the `cache-compression` feature, see
+//!   `SsrCache::with_compression`
+//! - **Route Policies**: Optional per-route TTL, stale window, entry limit,
+//!   cache key and no-store rules, see [`CacheRoutes`](crate::cache::CacheRoutes)
+//! - **Inspection**: List, peek at, export and import cached pages without
+//!   disturbing eviction, see [`SsrCache::entries`](crate::cache::SsrCache::entries)
+//! - **Auto-promotion**: Cold hits are promoted to hot cache, disk hits to both
 
+mod backend;
 mod cold;
+#[cfg(feature = "cache-compression")]
+mod compression;
+mod disk;
+mod eviction;
+mod inspect;
+mod invalidation;
 pub mod hot;  // Public for benchmarking
 mod padded;
+#[cfg(feature = "redis-backend")]
+mod redis;
+mod routes;
 mod ssr;
 mod utils;
 
-pub use ssr::{SsrCache, CacheMetrics};
+pub use ssr::{SsrCache, CacheMetrics, RefreshGuard, RouteKey, RouteLookup};
 pub use hot::HotCache;
+pub use disk::DiskCache;
+#[cfg(feature = "bench")]
+#[doc(hidden)]
+pub use cold::ColdCache;
+pub use backend::{CacheBackend, MemoryBackend};
+#[cfg(feature = "cache-compression")]
+pub use compression::{CacheCompression, CompressedPage, EncodedHtml, Encoding};
+pub use eviction::{Eviction, EvictionPolicy, S3Fifo, TinyLfu};
+pub use inspect::{
+    CacheEntryInfo, CacheSnapshot, CachedPage, EntryPage, EntryQuery, SnapshotEntry,
+};
+pub use invalidation::{InProcessBus, Invalidation, InvalidationBus};
+pub use routes::{CacheRoutes, RouteMetrics, RoutePolicy};
+#[cfg(feature = "redis-backend")]
+pub use redis::{RedisBackend, RedisInvalidationBus};
+pub(crate) use utils::stable_hash;
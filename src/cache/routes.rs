@@ -0,0 +1,673 @@
+//! Per-route cache policies
+//!
+//! A [`RoutePolicy`] overrides how the pages of matching URLs are cached:
+//! their TTL, a stale window, a per-route entry limit, which parts of the
+//! request vary the page, or that they are never cached at all.
+//!
+//! Patterns are matched against the URL path (query string ignored), in the
+//! order the routes were added; the first match wins:
+//! - `products` matches that literal segment
+//! - `:id` or `*` matches any single segment
+//! - `**` matches any number of segments, including none
+//!
+//! e.g. `/`, `/products/:id`, `/checkout/**`.
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::{HashSet, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use super::utils::HashKey;
+
+/// How the pages of a route are cached
+///
+/// # Example
+/// ```rust
+/// use std::time::Duration;
+/// use rusty_ssr::cache::RoutePolicy;
+///
+/// // Fresh for a minute, then served stale for up to 5 minutes while
+/// // re-rendering; `?page=` is part of the cache key, other params are not
+/// let policy = RoutePolicy::new()
+///     .ttl(Duration::from_secs(60))
+///     .stale_while_revalidate(Duration::from_secs(300))
+///     .max_entries(10_000)
+///     .vary_query(["page"]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RoutePolicy {
+    /// TTL of the route's pages (`None` = the cache TTL)
+    pub ttl: Option<Duration>,
+
+    /// Never cache the route's pages
+    pub no_store: bool,
+
+    /// How long an expired page may still be served while it is re-rendered
+    ///
+    /// One request re-renders the page; concurrent requests get the stale
+    /// page meanwhile, and so does the re-rendering request if it fails.
+    pub stale_while_revalidate: Option<Duration>,
+
+    /// Maximum cached pages of the route; the oldest are evicted first
+    pub max_entries: Option<usize>,
+
+    /// Query parameters that select a different page
+    ///
+    /// `None` keys pages by the full URL. `Some` keys them by the path and
+    /// the listed parameters only, so e.g. tracking parameters share a page.
+    pub vary_query: Option<Vec<String>>,
+
+    /// Cache a page per render data, not only per URL
+    ///
+    /// The data is hashed into the cache key; `invalidate` of the URL
+    /// removes every data variant of the page.
+    pub vary_data: bool,
+}
+
+impl RoutePolicy {
+    /// Cache like every other page
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Never cache the route's pages
+    pub fn no_store() -> Self {
+        Self {
+            no_store: true,
+            ..Self::default()
+        }
+    }
+
+    /// Set the TTL of the route's pages
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Serve expired pages for up to `window` while re-rendering them
+    pub fn stale_while_revalidate(mut self, window: Duration) -> Self {
+        self.stale_while_revalidate = Some(window);
+        self
+    }
+
+    /// Keep at most `max_entries` pages of the route
+    pub fn max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = Some(max_entries);
+        self
+    }
+
+    /// Key pages by the path and these query parameters only
+    pub fn vary_query<I, S>(mut self, params: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.vary_query = Some(params.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Cache a page per render data
+    pub fn vary_data(mut self) -> Self {
+        self.vary_data = true;
+        self
+    }
+
+    /// Check the policy for values that cannot work
+    pub fn validate(&self) -> Result<(), String> {
+        if self.ttl == Some(Duration::ZERO) {
+            return Err("route ttl must be greater than 0 (use RoutePolicy::no_store)".into());
+        }
+        if self.max_entries == Some(0) {
+            return Err("route max_entries must be greater than 0".into());
+        }
+        Ok(())
+    }
+}
+
+/// Segment of a route pattern
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    /// `:name` or `*`
+    Any,
+    /// `**`
+    Rest,
+}
+
+/// Parsed route pattern
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RoutePattern {
+    segments: Vec<Segment>,
+}
+
+impl RoutePattern {
+    /// Parse a pattern such as `/products/:id` or `/checkout/**`
+    pub(crate) fn parse(pattern: &str) -> Result<Self, String> {
+        let path = pattern
+            .strip_prefix('/')
+            .ok_or_else(|| format!("route pattern must start with '/': {}", pattern))?;
+
+        let segments = path
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .map(|segment| match segment {
+                "**" => Ok(Segment::Rest),
+                "*" => Ok(Segment::Any),
+                ":" => Err(format!("unnamed parameter in route pattern: {}", pattern)),
+                s if s.starts_with(':') => Ok(Segment::Any),
+                s if s.contains('*') => Err(format!(
+                    "wildcards must be a whole segment in route pattern: {}",
+                    pattern
+                )),
+                s => Ok(Segment::Literal(s.to_string())),
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { segments })
+    }
+
+    /// Check whether a URL's path matches
+    pub(crate) fn matches(&self, url: &str) -> bool {
+        match_segments(&self.segments, path_parts(url))
+    }
+}
+
+/// Non-empty segments of a URL's path
+fn path_parts(url: &str) -> impl Iterator<Item = &str> + Clone {
+    path_of(url).split('/').filter(|part| !part.is_empty())
+}
+
+fn match_segments<'a>(
+    pattern: &[Segment],
+    mut parts: impl Iterator<Item = &'a str> + Clone,
+) -> bool {
+    match pattern.split_first() {
+        None => parts.next().is_none(),
+        Some((Segment::Rest, rest)) => loop {
+            if match_segments(rest, parts.clone()) {
+                return true;
+            }
+            if parts.next().is_none() {
+                return false;
+            }
+        },
+        Some((segment, rest)) => parts.next().is_some_and(|part| {
+            let matched = match segment {
+                Segment::Literal(literal) => literal == part,
+                _ => true,
+            };
+            matched && match_segments(rest, parts)
+        }),
+    }
+}
+
+/// The URL without its query string and fragment
+fn path_of(url: &str) -> &str {
+    url.split(['?', '#']).next().unwrap_or(url)
+}
+
+/// Name of a `name=value` query parameter
+fn param_name(pair: &str) -> &str {
+    pair.split('=').next().unwrap_or(pair)
+}
+
+/// A route of a [`CacheRoutes`] table, with its counters
+pub(crate) struct Route {
+    pub(crate) pattern: String,
+    matcher: RoutePattern,
+    pub(crate) policy: RoutePolicy,
+    hits: AtomicU64,
+    stale_hits: AtomicU64,
+    misses: AtomicU64,
+    bypasses: AtomicU64,
+    evictions: AtomicU64,
+    /// Cached keys in insertion order, tracked only with `max_entries`
+    keys: Mutex<(VecDeque<String>, HashSet<String>)>,
+    /// Keys being re-rendered after going stale
+    refreshing: Mutex<HashSet<String>>,
+}
+
+/// What a route lookup is counted as
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum RouteEvent {
+    Hit,
+    StaleHit,
+    Miss,
+    Bypass,
+}
+
+impl Route {
+    pub(crate) fn record(&self, event: RouteEvent) {
+        let counter = match event {
+            RouteEvent::Hit => &self.hits,
+            RouteEvent::StaleHit => &self.stale_hits,
+            RouteEvent::Miss => &self.misses,
+            RouteEvent::Bypass => &self.bypasses,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Cache key of a request to this route, hashing data with `hash_key`
+    fn key(&self, url: &str, data: &str, hash_key: &HashKey) -> String {
+        let mut key = self.page_key(url);
+        if self.policy.vary_data {
+            key.push_str(&format!("#data={:016x}", hash_key.hash(data.as_bytes())));
+        }
+        key
+    }
+
+    /// Cache key of a URL before any render data suffix
+    ///
+    /// With `vary_query`, the kept parameters are sorted by name, so their
+    /// order in the URL does not matter.
+    fn page_key(&self, url: &str) -> String {
+        let Some(params) = &self.policy.vary_query else {
+            return url.to_string();
+        };
+
+        let mut key = path_of(url).to_string();
+        let query = url.split_once('?').map_or("", |(_, query)| query);
+        let query = query.split('#').next().unwrap_or(query);
+        let mut kept: Vec<&str> = query
+            .split('&')
+            .filter(|pair| params.iter().any(|param| param == param_name(pair)))
+            .collect();
+        kept.sort_by_key(|pair| param_name(pair));
+        if !kept.is_empty() {
+            key.push('?');
+            key.push_str(&kept.join("&"));
+        }
+        key
+    }
+
+    /// Track a newly cached key, returning the keys over `max_entries`
+    ///
+    /// Keys no longer cached (`is_cached` is false), e.g. expired or evicted
+    /// by the cache, are dropped without counting as evictions once they are
+    /// the oldest.
+    pub(crate) fn track(&self, key: &str, is_cached: impl Fn(&str) -> bool) -> Vec<String> {
+        let Some(max_entries) = self.policy.max_entries else {
+            return Vec::new();
+        };
+
+        let mut keys = self.keys.lock();
+        let (order, known) = &mut *keys;
+        if known.insert(key.to_string()) {
+            order.push_back(key.to_string());
+        }
+
+        let mut evicted = Vec::new();
+        while order.len() > max_entries {
+            let Some(oldest) = order.pop_front() else {
+                break;
+            };
+            known.remove(&oldest);
+            if is_cached(&oldest) {
+                evicted.push(oldest);
+            }
+        }
+        while order.front().is_some_and(|oldest| !is_cached(oldest)) {
+            if let Some(oldest) = order.pop_front() {
+                known.remove(&oldest);
+            }
+        }
+        self.evictions
+            .fetch_add(evicted.len() as u64, Ordering::Relaxed);
+        evicted
+    }
+
+    /// Stop tracking a key that left the cache
+    pub(crate) fn forget(&self, key: &str) {
+        let mut keys = self.keys.lock();
+        let (order, known) = &mut *keys;
+        if known.remove(key) {
+            order.retain(|tracked| tracked != key);
+        }
+    }
+
+    /// Stop tracking the keys that `keep` rejects
+    fn forget_where(&self, keep: impl Fn(&str) -> bool) {
+        let mut keys = self.keys.lock();
+        let (order, known) = &mut *keys;
+        order.retain(|key| keep(key));
+        known.retain(|key| keep(key));
+    }
+
+    /// Claim the re-render of a stale key; `false` if already claimed
+    pub(crate) fn start_refresh(&self, key: &str) -> bool {
+        self.refreshing.lock().insert(key.to_string())
+    }
+
+    pub(crate) fn finish_refresh(&self, key: &str) {
+        self.refreshing.lock().remove(key);
+    }
+
+    fn metrics(&self) -> RouteMetrics {
+        RouteMetrics {
+            pattern: self.pattern.clone(),
+            ttl_secs: self.policy.ttl.map(|ttl| ttl.as_secs()),
+            stale_secs: self.policy.stale_while_revalidate.map(|w| w.as_secs()),
+            no_store: self.policy.no_store,
+            max_entries: self.policy.max_entries,
+            vary_query: self.policy.vary_query.clone(),
+            vary_data: self.policy.vary_data,
+            hits: self.hits.load(Ordering::Relaxed),
+            stale_hits: self.stale_hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            bypasses: self.bypasses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+            entries: self.policy.max_entries.map(|_| self.keys.lock().0.len()),
+        }
+    }
+}
+
+/// Ordered table of route patterns and their cache policies
+///
+/// # Example
+/// ```rust
+/// use std::time::Duration;
+/// use rusty_ssr::cache::{CacheRoutes, RoutePolicy};
+///
+/// let routes = CacheRoutes::new([
+///     ("/checkout/**", RoutePolicy::no_store()),
+///     ("/products/:id", RoutePolicy::new().ttl(Duration::from_secs(60))),
+///     ("/", RoutePolicy::new().ttl(Duration::from_secs(10))),
+/// ])
+/// .unwrap();
+/// ```
+#[derive(Default)]
+pub struct CacheRoutes {
+    routes: Vec<Route>,
+}
+
+impl CacheRoutes {
+    /// Build a table from `(pattern, policy)` pairs, in matching order
+    pub fn new<I, S>(routes: I) -> Result<Self, String>
+    where
+        I: IntoIterator<Item = (S, RoutePolicy)>,
+        S: Into<String>,
+    {
+        let routes = routes
+            .into_iter()
+            .map(|(pattern, policy)| {
+                let pattern = pattern.into();
+                policy
+                    .validate()
+                    .map_err(|e| format!("{} (route {})", e, pattern))?;
+                Ok(Route {
+                    matcher: RoutePattern::parse(&pattern)?,
+                    pattern,
+                    policy,
+                    hits: AtomicU64::new(0),
+                    stale_hits: AtomicU64::new(0),
+                    misses: AtomicU64::new(0),
+                    bypasses: AtomicU64::new(0),
+                    evictions: AtomicU64::new(0),
+                    keys: Mutex::new((VecDeque::new(), HashSet::new())),
+                    refreshing: Mutex::new(HashSet::new()),
+                })
+            })
+            .collect::<Result<_, String>>()?;
+
+        Ok(Self { routes })
+    }
+
+    /// Check whether the table has no routes
+    pub fn is_empty(&self) -> bool {
+        self.routes.is_empty()
+    }
+
+    /// The policy of the first route matching `url`, if any
+    pub fn policy(&self, url: &str) -> Option<&RoutePolicy> {
+        self.find(url).map(|route| &route.policy)
+    }
+
+    /// Resolve the route and cache key of a request
+    ///
+    /// Render data is hashed with `hash_key` for routes varying on it.
+    pub(crate) fn resolve(
+        &self,
+        url: &str,
+        data: &str,
+        hash_key: &HashKey,
+    ) -> (Option<usize>, String) {
+        match self
+            .routes
+            .iter()
+            .position(|route| route.matcher.matches(url))
+        {
+            Some(index) => (Some(index), self.routes[index].key(url, data, hash_key)),
+            None => (None, url.to_string()),
+        }
+    }
+
+    pub(crate) fn get(&self, index: usize) -> Option<&Route> {
+        self.routes.get(index)
+    }
+
+    /// Pattern of the first route matching `url`, if any
+    pub(crate) fn pattern(&self, url: &str) -> Option<&str> {
+        self.find(url).map(|route| route.pattern.as_str())
+    }
+
+    fn find(&self, url: &str) -> Option<&Route> {
+        self.routes.iter().find(|route| route.matcher.matches(url))
+    }
+
+    /// Per-route policies and counters
+    pub fn metrics(&self) -> Vec<RouteMetrics> {
+        self.routes.iter().map(Route::metrics).collect()
+    }
+
+    /// Cache key of a URL on its route, without render data
+    ///
+    /// Differs from the URL for routes with `vary_query`.
+    pub(crate) fn page_key(&self, url: &str) -> String {
+        match self.find(url) {
+            Some(route) => route.page_key(url),
+            None => url.to_string(),
+        }
+    }
+
+    /// Stop tracking a key that left the cache
+    pub(crate) fn forget(&self, key: &str) {
+        if let Some(route) = self.find(key) {
+            route.forget(key);
+        }
+    }
+
+    /// Stop tracking every key starting with `prefix`
+    pub(crate) fn forget_prefix(&self, prefix: &str) {
+        for route in &self.routes {
+            route.forget_where(|key| !key.starts_with(prefix));
+        }
+    }
+
+    /// Stop tracking the keys that are no longer cached
+    ///
+    /// Catches keys evicted by the cache itself.
+    pub(crate) fn prune(&self, is_cached: impl Fn(&str) -> bool) {
+        for route in self
+            .routes
+            .iter()
+            .filter(|r| r.policy.max_entries.is_some())
+        {
+            route.forget_where(&is_cached);
+        }
+    }
+}
+
+impl std::fmt::Debug for CacheRoutes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map()
+            .entries(
+                self.routes
+                    .iter()
+                    .map(|route| (&route.pattern, &route.policy)),
+            )
+            .finish()
+    }
+}
+
+/// Policy and counters of one route
+#[derive(Clone, Debug, Serialize)]
+pub struct RouteMetrics {
+    /// Route pattern
+    pub pattern: String,
+    /// TTL in seconds (`None` = the cache TTL)
+    pub ttl_secs: Option<u64>,
+    /// Stale-while-revalidate window in seconds
+    pub stale_secs: Option<u64>,
+    /// Pages of the route are never cached
+    pub no_store: bool,
+    /// Maximum cached pages of the route
+    pub max_entries: Option<usize>,
+    /// Query parameters in the cache key (`None` = the full URL)
+    pub vary_query: Option<Vec<String>>,
+    /// Render data is part of the cache key
+    pub vary_data: bool,
+    /// Fresh cache hits
+    pub hits: u64,
+    /// Lookups that found a stale page (re-rendered by the first of them)
+    pub stale_hits: u64,
+    /// Cache misses
+    pub misses: u64,
+    /// Requests not cached because of `no_store`
+    pub bypasses: u64,
+    /// Pages evicted to stay within `max_entries`
+    pub evictions: u64,
+    /// Tracked cached pages (only with `max_entries`)
+    ///
+    /// Expired pages count until they are looked up or become the oldest.
+    pub entries: Option<usize>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &str, url: &str) -> bool {
+        RoutePattern::parse(pattern).unwrap().matches(url)
+    }
+
+    #[test]
+    fn test_pattern_matching() {
+        assert!(matches("/", "/"));
+        assert!(matches("/", "/?utm_source=x"));
+        assert!(!matches("/", "/about"));
+
+        assert!(matches("/products/:id", "/products/42"));
+        assert!(matches("/products/:id", "/products/42/?tab=reviews"));
+        assert!(!matches("/products/:id", "/products"));
+        assert!(!matches("/products/:id", "/products/42/reviews"));
+        assert!(matches("/products/*/reviews", "/products/42/reviews"));
+
+        assert!(matches("/checkout/**", "/checkout"));
+        assert!(matches("/checkout/**", "/checkout/pay/card"));
+        assert!(!matches("/checkout/**", "/checkouts"));
+        assert!(matches("/**/edit", "/a/b/edit"));
+        assert!(matches("/**", "/anything/at/all"));
+
+        assert!(RoutePattern::parse("products").is_err());
+        assert!(RoutePattern::parse("/products/:").is_err());
+        assert!(RoutePattern::parse("/products/*.html").is_err());
+    }
+
+    #[test]
+    fn test_first_match_wins() {
+        let routes = CacheRoutes::new([
+            ("/products/featured", RoutePolicy::no_store()),
+            (
+                "/products/:id",
+                RoutePolicy::new().ttl(Duration::from_secs(60)),
+            ),
+        ])
+        .unwrap();
+
+        assert!(routes.policy("/products/featured").unwrap().no_store);
+        assert_eq!(
+            routes.policy("/products/1").unwrap().ttl,
+            Some(Duration::from_secs(60))
+        );
+        assert!(routes.policy("/about").is_none());
+        assert!(CacheRoutes::new([("/", RoutePolicy::new().max_entries(0))]).is_err());
+    }
+
+    #[test]
+    fn test_vary_keys() {
+        let routes = CacheRoutes::new([
+            ("/search", RoutePolicy::new().vary_query(["q", "page"])),
+            ("/feed", RoutePolicy::new().vary_data()),
+        ])
+        .unwrap();
+        let hash_key = HashKey::random();
+        let resolve = |url, data| routes.resolve(url, data, &hash_key);
+
+        let (route, key) = resolve("/search?utm_source=x&q=shoes&page=2", "{}");
+        assert_eq!(route, Some(0));
+        assert_eq!(key, "/search?page=2&q=shoes");
+        assert_eq!(resolve("/search?page=2&q=shoes", "{}").1, key);
+        assert_eq!(resolve("/search?utm_source=x", "{}").1, "/search");
+        assert_eq!(routes.page_key("/search?q=shoes&page=2&utm_source=x"), key);
+
+        let (_, a) = resolve("/feed", r#"{"user":1}"#);
+        let (_, b) = resolve("/feed", r#"{"user":2}"#);
+        assert_ne!(a, b);
+        assert!(a.starts_with("/feed"));
+
+        // Unrouted URLs keep the full URL as key
+        assert_eq!(
+            resolve("/about?x=1", "{}"),
+            (None, "/about?x=1".to_string())
+        );
+    }
+
+    #[test]
+    fn test_max_entries_evicts_oldest() {
+        let routes = CacheRoutes::new([("/p/:id", RoutePolicy::new().max_entries(2))]).unwrap();
+        let route = routes.get(0).unwrap();
+
+        let cached = |_: &str| true;
+
+        assert!(route.track("/p/1", cached).is_empty());
+        assert!(route.track("/p/2", cached).is_empty());
+        assert!(
+            route.track("/p/1", cached).is_empty(),
+            "re-inserting is not a new page"
+        );
+        assert_eq!(route.track("/p/3", cached), vec!["/p/1".to_string()]);
+
+        let metrics = &routes.metrics()[0];
+        assert_eq!(metrics.evictions, 1);
+        assert_eq!(metrics.entries, Some(2));
+    }
+
+    #[test]
+    fn test_removed_keys_are_forgotten() {
+        let routes = CacheRoutes::new([("/p/:id", RoutePolicy::new().max_entries(2))]).unwrap();
+        let route = routes.get(0).unwrap();
+        let cached = |_: &str| true;
+
+        route.track("/p/1", cached);
+        route.track("/p/2", cached);
+        routes.forget("/p/1");
+        assert!(
+            route.track("/p/3", cached).is_empty(),
+            "an invalidated page frees its slot"
+        );
+
+        routes.forget_prefix("/p/");
+        assert_eq!(routes.metrics()[0].entries, Some(0));
+
+        // Keys that left the cache on their own are dropped, not evicted
+        route.track("/p/4", cached);
+        route.track("/p/5", cached);
+        assert!(route.track("/p/6", |key: &str| key != "/p/4").is_empty());
+        routes.prune(|key| key == "/p/6");
+
+        let metrics = &routes.metrics()[0];
+        assert_eq!(metrics.evictions, 0);
+        assert_eq!(metrics.entries, Some(1));
+    }
+}
@@ -4,26 +4,59 @@
 
 use serde::Serialize;
 use std::cell::RefCell;
+use std::future::Future;
+use std::io;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use thread_local::ThreadLocal;
 
+use super::backend::CacheBackend;
 use super::cold::ColdCache;
+use super::disk::{self, DiskCache};
+use super::eviction::Eviction;
 use super::hot::HotCache;
+use super::inspect::{CacheEntryInfo, CacheSnapshot, CachedPage, EntryPage, EntryQuery};
 use super::padded::CachePadded;
-use super::utils::hash_url;
+use super::routes::{CacheRoutes, Route, RouteEvent, RouteMetrics};
+use super::utils::HashKey;
+
+#[cfg(feature = "cache-compression")]
+use super::compression::{CacheCompression, CompressedPage, EncodedHtml};
+#[cfg(all(feature = "cache-compression", any(feature = "v8-pool", test)))]
+use super::compression::Encoding;
 
 /// Multi-tier SSR cache
 ///
 /// ## Architecture
 /// 1. **Hot cache** (L1/L2): Thread-local, 8 entries per thread
-/// 2. **Cold cache** (RAM): Shared DashMap with LRU eviction
+/// 2. **Cold cache** (RAM): Shared DashMap with LRU eviction, or another
+///    policy, see [`SsrCache::with_eviction`]
+/// 3. **Disk cache** (optional): Persistent files, see [`SsrCache::with_disk_cache`]
+/// 4. **Shared backend** (optional): Async cache shared between replicas,
+///    see [`SsrCache::with_backend`]
+///
+/// Entries found in cold cache are automatically promoted to hot cache,
+/// and entries found on disk or in the backend to the local tiers.
 ///
-/// Entries found in cold cache are automatically promoted to hot cache.
+/// Per-route policies (see [`SsrCache::with_routes`]) apply to lookups and
+/// inserts through [`SsrCache::route_key`].
 pub struct SsrCache {
     hot_cache: ThreadLocal<RefCell<HotCacheState>>,
     cold_cache: Arc<ColdCache>,
+    disk_cache: Option<Arc<DiskCache>>,
+    backend: Option<Arc<dyn CacheBackend>>,
+    max_entry_bytes: Option<usize>,
+    #[cfg(feature = "cache-compression")]
+    compression: Option<CacheCompression>,
+    routes: CacheRoutes,
+    /// Key of URL and render data hashes, see [`SsrCache::with_hash_key`]
+    hash_key: HashKey,
+    /// Whether the hash key was set explicitly, rather than by the disk tier
+    fixed_hash_key: bool,
+    /// Replaces the URL hash, e.g. to force collisions in tests
+    #[cfg(test)]
+    hasher: Option<fn(&str) -> u64>,
     ttl_secs: u64,
     generation: AtomicU64,
     metrics: Arc<CacheMetricsInner>,
@@ -38,6 +71,13 @@ struct CacheMetricsInner {
     promotions: CachePadded<AtomicU64>,
     insertions: CachePadded<AtomicU64>,
     evictions: CachePadded<AtomicU64>,
+    disk_hits: CachePadded<AtomicU64>,
+    disk_evictions: CachePadded<AtomicU64>,
+    remote_hits: CachePadded<AtomicU64>,
+    remote_misses: CachePadded<AtomicU64>,
+    remote_errors: CachePadded<AtomicU64>,
+    oversized: CachePadded<AtomicU64>,
+    encoded_hits: CachePadded<AtomicU64>,
     last_access_ns: CachePadded<AtomicU64>,
 }
 
@@ -64,8 +104,95 @@ pub struct CacheMetrics {
     pub cold_size: usize,
     /// Cold cache capacity
     pub cold_capacity: usize,
+    /// Bytes used by the cold cache
+    pub cold_bytes: usize,
+    /// Cold cache byte budget (0 = bounded by entry count only)
+    pub cold_capacity_bytes: usize,
+    /// Pages not cached because they exceeded the maximum entry size
+    pub oversized: u64,
+    /// Cold hits served as a pre-compressed variant
+    pub encoded_hits: u64,
     /// Hit rate percentage
     pub hit_rate: f64,
+    /// Disk cache hits
+    pub disk_hits: u64,
+    /// Disk cache evictions (size bound)
+    pub disk_evictions: u64,
+    /// Current disk cache entries
+    pub disk_size: usize,
+    /// Bytes used by the disk cache
+    pub disk_bytes: u64,
+    /// Disk cache capacity in bytes (0 = no disk tier)
+    pub disk_capacity_bytes: u64,
+    /// Shared backend hits (not included in `hit_rate`)
+    pub remote_hits: u64,
+    /// Shared backend misses
+    pub remote_misses: u64,
+    /// Failed shared backend operations (lookups and writes)
+    pub remote_errors: u64,
+    /// Policy and counters of each cache route, in matching order
+    pub routes: Vec<RouteMetrics>,
+}
+
+/// Cache key of a request, resolved by [`SsrCache::route_key`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteKey {
+    key: String,
+    route: Option<usize>,
+    no_store: bool,
+}
+
+impl RouteKey {
+    /// The key pages are cached under
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Whether a cache route matched the request
+    pub fn is_routed(&self) -> bool {
+        self.route.is_some()
+    }
+
+    /// Whether the page may be cached
+    pub fn is_cacheable(&self) -> bool {
+        !self.no_store
+    }
+}
+
+/// Result of [`SsrCache::lookup`]
+#[derive(Debug)]
+pub enum RouteLookup<'a> {
+    /// A page to serve, possibly stale while another request re-renders it
+    Hit(Arc<str>),
+    /// A stale page this request should re-render and cache
+    ///
+    /// Serve the stale page if rendering fails. Other requests get the
+    /// stale page until the guard is dropped.
+    Stale(Arc<str>, RefreshGuard<'a>),
+    /// Not cached
+    Miss,
+    /// The route is never cached
+    Bypass,
+}
+
+/// Marks a stale page as being re-rendered until dropped
+pub struct RefreshGuard<'a> {
+    route: &'a Route,
+    key: String,
+}
+
+impl std::fmt::Debug for RefreshGuard<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RefreshGuard")
+            .field("key", &self.key)
+            .finish()
+    }
+}
+
+impl Drop for RefreshGuard<'_> {
+    fn drop(&mut self) {
+        self.route.finish_refresh(&self.key);
+    }
 }
 
 struct HotCacheState {
@@ -101,76 +228,652 @@ impl SsrCache {
         Self {
             hot_cache: ThreadLocal::new(),
             cold_cache: Arc::new(ColdCache::with_ttl(max_cold_entries, ttl_secs)),
+            disk_cache: None,
+            backend: None,
+            max_entry_bytes: None,
+            #[cfg(feature = "cache-compression")]
+            compression: None,
+            routes: CacheRoutes::default(),
+            hash_key: HashKey::random(),
+            fixed_hash_key: false,
+            #[cfg(test)]
+            hasher: None,
             ttl_secs,
             generation: AtomicU64::new(0),
             metrics: Arc::new(CacheMetricsInner::default()),
         }
     }
 
-    /// Try to get cached HTML
+    /// Add a persistent disk tier behind the cold cache
+    ///
+    /// Cold misses fall back to the disk cache, and inserts are written
+    /// through to it. The cache adopts the hash key persisted with the disk
+    /// cache, so keys of render data variants stay valid across restarts,
+    /// unless one was set with [`SsrCache::with_hash_key`]. Add the disk
+    /// tier before inserting pages.
+    pub fn with_disk_cache(mut self, mut disk_cache: DiskCache) -> Self {
+        if !self.fixed_hash_key {
+            self.hash_key = disk_cache.hash_key();
+        } else if let Err(e) = disk_cache.set_hash_key(self.hash_key) {
+            tracing::warn!("⚠️ Failed to store the cache hash key on disk: {}", e);
+        }
+        self.disk_cache = Some(Arc::new(disk_cache));
+        self
+    }
+
+    /// Set the secret key of URL and render data hashes
+    ///
+    /// Cache keys of routes varying on render data, and of render entries,
+    /// embed a keyed hash of the data. Replicas sharing a cache backend need
+    /// the same key to share those pages; so do snapshots imported into
+    /// another process. Keep the key secret: with it, render data can be
+    /// crafted to collide.
+    ///
+    /// Default: the key persisted with the disk cache, if any, and otherwise
+    /// a random key per process
+    pub fn with_hash_key(mut self, key: [u8; 16]) -> Self {
+        self.hash_key = HashKey(key);
+        self.fixed_hash_key = true;
+        if let Some(disk) = self.disk_cache.as_mut().and_then(Arc::get_mut) {
+            if let Err(e) = disk.set_hash_key(self.hash_key) {
+                tracing::warn!("⚠️ Failed to store the cache hash key on disk: {}", e);
+            }
+        }
+        self
+    }
+
+    /// Bound the cold cache by the total size of cached pages
+    ///
+    /// The entry count limit still applies. When the budget is exceeded,
+    /// the least recently used pages are evicted until the new one fits.
+    pub fn with_max_bytes(mut self, max_bytes: usize) -> Self {
+        Arc::get_mut(&mut self.cold_cache)
+            .expect("cold cache is not shared before construction completes")
+            .set_max_bytes(max_bytes);
+        self
+    }
+
+    /// Select the cold cache eviction policy
+    ///
+    /// Default: [`Eviction::Lru`]
+    pub fn with_eviction(mut self, eviction: Eviction) -> Self {
+        Arc::get_mut(&mut self.cold_cache)
+            .expect("cold cache is not shared before construction completes")
+            .set_eviction(&eviction);
+        self
+    }
+
+    /// Do not cache pages larger than `max_entry_bytes` (HTML length)
+    ///
+    /// Oversized pages are still returned to the caller but skip every
+    /// tier; they are counted in `oversized`.
+    pub fn with_max_entry_bytes(mut self, max_entry_bytes: usize) -> Self {
+        self.max_entry_bytes = Some(max_entry_bytes);
+        self
+    }
+
+    /// Store pre-compressed variants of cached pages
+    ///
+    /// Pages are compressed once when they enter the cold cache; serve them
+    /// with [`SsrCache::try_get_encoded`].
+    #[cfg(feature = "cache-compression")]
+    pub fn with_compression(mut self, compression: CacheCompression) -> Self {
+        self.compression = Some(compression);
+        self
+    }
+
+    /// Add a shared cache backend behind the local tiers
+    ///
+    /// [`SsrCache::get`] consults the backend after a local miss, and
+    /// inserts are written through to it in the background. Backend
+    /// failures are logged and counted in `remote_errors`, never returned.
+    pub fn with_backend(mut self, backend: Arc<dyn CacheBackend>) -> Self {
+        self.backend = Some(backend);
+        self
+    }
+
+    /// Apply per-route cache policies
+    ///
+    /// Only lookups and inserts through [`SsrCache::route_key`] consult the
+    /// routes; [`SsrCache::get`] and [`SsrCache::insert`] use the URL as is.
+    pub fn with_routes(mut self, routes: CacheRoutes) -> Self {
+        self.routes = routes;
+        self
+    }
+
+    /// The per-route cache policies
+    pub fn routes(&self) -> &CacheRoutes {
+        &self.routes
+    }
+
+    /// Replace the URL hash function, e.g. to force collisions in tests
+    #[cfg(test)]
+    fn with_hasher(mut self, hasher: fn(&str) -> u64) -> Self {
+        self.hasher = Some(hasher);
+        self
+    }
+
+    /// Hash of a URL in every local tier
+    fn url_hash(&self, url: &str) -> u64 {
+        #[cfg(test)]
+        if let Some(hasher) = self.hasher {
+            return hasher(url);
+        }
+        self.hash_key.hash_url(url)
+    }
+
+    /// Get cached HTML, falling back to the shared backend
+    ///
+    /// Like [`SsrCache::try_get`], then looks the URL up in the disk cache
+    /// and the backend (if any). Disk and backend hits are promoted to the
+    /// memory tiers.
+    pub async fn get(&self, url: &str) -> Option<Arc<str>> {
+        if let Some((html, _)) = self.get_local(url).await {
+            return Some(html);
+        }
+        self.fetch_remote(url, None).await
+    }
+
+    /// Resolve the cache key and route of a request
+    ///
+    /// The key is the URL unless the matching route varies on a subset of
+    /// the query parameters or on the render data.
+    pub fn route_key(&self, url: &str, data: &str) -> RouteKey {
+        let (route, key) = self.routes.resolve(url, data, &self.hash_key);
+        RouteKey {
+            key,
+            route,
+            no_store: route
+                .and_then(|index| self.routes.get(index))
+                .is_some_and(|route| route.policy.no_store),
+        }
+    }
+
+    /// Resolve the cache key and route of a render with a named render entry
+    ///
+    /// Like [`SsrCache::route_key`], followed by the entry name and a hash of
+    /// the render data: entry renders are cached per data, under the route
+    /// of their URL. Like data variants of a page, they are removed by
+    /// [`SsrCache::invalidate`] of the URL.
+    pub fn entry_key(&self, entry: &str, url: &str, data: &str) -> RouteKey {
+        let mut key = self.route_key(url, data);
+        let varies_data = key
+            .route
+            .and_then(|index| self.routes.get(index))
+            .is_some_and(|route| route.policy.vary_data);
+
+        key.key.push_str("#entry=");
+        key.key.push_str(entry);
+        if !varies_data {
+            key.key
+                .push_str(&format!("#data={:016x}", self.hash_key.hash(data.as_bytes())));
+        }
+        key
+    }
+
+    /// Get a cached page following its route's policy
+    ///
+    /// Like [`SsrCache::get`], and counts the lookup in the route's
+    /// metrics. Pages within their route's stale window are returned as
+    /// [`RouteLookup::Stale`] to the first request, which should re-render
+    /// them; concurrent requests get them as hits meanwhile.
+    pub async fn lookup(&self, key: &RouteKey) -> RouteLookup<'_> {
+        let Some(route) = key.route.and_then(|index| self.routes.get(index)) else {
+            return match self.get(&key.key).await {
+                Some(html) => RouteLookup::Hit(html),
+                None => RouteLookup::Miss,
+            };
+        };
+
+        if route.policy.no_store {
+            route.record(RouteEvent::Bypass);
+            return RouteLookup::Bypass;
+        }
+
+        let found = match self.get_local(&key.key).await {
+            Some(found) => Some(found),
+            // Backend hits count as fresh
+            None => self
+                .fetch_remote(&key.key, self.route_ttl(route))
+                .await
+                .map(|html| (html, None)),
+        };
+        let Some((html, expires_at)) = found else {
+            route.record(RouteEvent::Miss);
+            // Expired or evicted by the cache
+            route.forget(&key.key);
+            return RouteLookup::Miss;
+        };
+
+        let stale = route.policy.stale_while_revalidate.is_some_and(|window| {
+            expires_at.is_some_and(|at| at.saturating_duration_since(Instant::now()) < window)
+        });
+        if !stale {
+            route.record(RouteEvent::Hit);
+            return RouteLookup::Hit(html);
+        }
+
+        route.record(RouteEvent::StaleHit);
+        if route.start_refresh(&key.key) {
+            tracing::debug!("♻️ Serving stale {} while re-rendering", key.key);
+            RouteLookup::Stale(
+                html,
+                RefreshGuard {
+                    route,
+                    key: key.key.clone(),
+                },
+            )
+        } else {
+            RouteLookup::Hit(html)
+        }
+    }
+
+    /// Insert a page following its route's policy
+    ///
+    /// Pages of `no_store` routes are not cached. The route's TTL (plus its
+    /// stale window) applies, and its oldest pages are evicted beyond
+    /// `max_entries`.
+    pub fn insert_routed(&self, key: &RouteKey, html: Arc<str>) {
+        let Some(route) = key.route.and_then(|index| self.routes.get(index)) else {
+            return self.insert(&key.key, html);
+        };
+        if route.policy.no_store {
+            return;
+        }
+
+        self.insert_with_ttl(&key.key, html, self.route_ttl(route));
+        for evicted in route.track(&key.key, |key| self.is_cached(key)) {
+            tracing::debug!("Evicting {} (route {} is full)", evicted, route.pattern);
+            self.remove_local(&evicted);
+        }
+    }
+
+    /// Look a route key up in the shared backend only, promoting hits locally
+    #[cfg(feature = "v8-pool")]
+    pub(crate) async fn fetch_remote_routed(&self, key: &RouteKey) -> Option<Arc<str>> {
+        let ttl = key
+            .route
+            .and_then(|index| self.routes.get(index))
+            .and_then(|route| self.route_ttl(route));
+        self.fetch_remote(&key.key, ttl).await
+    }
+
+    /// How long a route's pages are kept: its TTL plus its stale window
+    ///
+    /// `None` means the cache TTL.
+    fn route_ttl(&self, route: &Route) -> Option<Duration> {
+        let ttl = route.policy.ttl.or(self.default_ttl())?;
+        Some(ttl + route.policy.stale_while_revalidate.unwrap_or_default())
+    }
+
+    fn default_ttl(&self) -> Option<Duration> {
+        (self.ttl_secs > 0).then(|| Duration::from_secs(self.ttl_secs))
+    }
+
+    /// Look a URL up in the shared backend only, promoting hits locally
+    ///
+    /// Promoted pages expire after `ttl` (`None` = the cache TTL).
+    async fn fetch_remote(&self, url: &str, ttl: Option<Duration>) -> Option<Arc<str>> {
+        let backend = self.backend.as_ref()?;
+        match backend.get(url).await {
+            Ok(Some(html)) => {
+                self.metrics.remote_hits.fetch_add(1, Ordering::Relaxed);
+                if !self.is_oversized(url, &html) {
+                    self.insert_local(url, Arc::clone(&html), ttl);
+                }
+                Some(html)
+            }
+            Ok(None) => {
+                self.metrics.remote_misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+            Err(e) => {
+                self.metrics.remote_errors.fetch_add(1, Ordering::Relaxed);
+                tracing::warn!("⚠️ Cache backend lookup failed for {}: {}", url, e);
+                None
+            }
+        }
+    }
+
+    /// Try to get a cached page in the best encoding for `accept_encoding`
+    ///
+    /// Returns a pre-compressed variant from the cold cache if the client
+    /// accepts one, and otherwise the raw HTML like [`SsrCache::try_get`].
+    #[cfg(feature = "cache-compression")]
+    pub fn try_get_encoded(&self, url: &str, accept_encoding: &str) -> Option<EncodedHtml> {
+        let variant = self.compression.as_ref().and_then(|_| {
+            let (compressed, stats) = self
+                .cold_cache
+                .get_compressed_with_stats(self.url_hash(url), url)?;
+            let variant = compressed.negotiate(accept_encoding)?;
+            stats.record_hit();
+            Some(variant)
+        });
+
+        if let Some((encoding, data)) = variant {
+            self.metrics.lookups.fetch_add(1, Ordering::Relaxed);
+            self.metrics.cold_hits.fetch_add(1, Ordering::Relaxed);
+            self.metrics.encoded_hits.fetch_add(1, Ordering::Relaxed);
+            return Some(EncodedHtml::Encoded(encoding, data));
+        }
+
+        self.try_get(url).map(EncodedHtml::Raw)
+    }
+
+    /// Look up an acceptable compressed variant without touching metrics
+    #[cfg(all(feature = "cache-compression", feature = "v8-pool"))]
+    pub(crate) fn peek_encoded(
+        &self,
+        url: &str,
+        accept_encoding: &str,
+    ) -> Option<(Encoding, Arc<[u8]>)> {
+        self.compression.as_ref()?;
+        self.cold_cache
+            .get_compressed(self.url_hash(url), url)?
+            .negotiate(accept_encoding)
+    }
+
+    /// Try to get cached HTML from memory
     ///
-    /// Checks hot cache first, then cold cache.
-    /// Cold hits are promoted to hot cache.
+    /// Checks hot cache first, then cold cache. Cold hits are promoted to
+    /// hot cache. Pages only on disk (e.g. after a restart) are found by
+    /// [`SsrCache::get`], which reads the disk without blocking.
     pub fn try_get(&self, url: &str) -> Option<Arc<str>> {
-        let url_hash = hash_url(url);
+        self.metrics.lookups.fetch_add(1, Ordering::Relaxed);
+        let found = self.get_memory(self.url_hash(url), url, Instant::now());
+        if found.is_none() {
+            self.metrics.misses.fetch_add(1, Ordering::Relaxed);
+        }
+        found.map(|(html, _)| html)
+    }
+
+    /// Get a page from the hot, cold or disk tier, with when it expires
+    ///
+    /// Disk reads run on Tokio's blocking threads.
+    async fn get_local(&self, url: &str) -> Option<(Arc<str>, Option<Instant>)> {
+        let url_hash = self.url_hash(url);
         let start = Instant::now();
         self.metrics.lookups.fetch_add(1, Ordering::Relaxed);
 
+        let found = match self.get_memory(url_hash, url, start) {
+            Some(found) => Some(found),
+            None => self.get_disk(url_hash, url, start).await,
+        };
+        if found.is_none() {
+            self.metrics.misses.fetch_add(1, Ordering::Relaxed);
+        }
+        found
+    }
+
+    /// Look a page up in the hot and cold tiers, without counting the lookup
+    fn get_memory(
+        &self,
+        url_hash: u64,
+        url: &str,
+        start: Instant,
+    ) -> Option<(Arc<str>, Option<Instant>)> {
         // 1. Check hot cache (L1/L2) - use peek() for read-only access
         let hot = self.get_or_init_hot_cache();
-        if let Some(html) = hot.borrow().cache.peek(url_hash) {
+        if let Some(found) = hot.borrow().cache.peek_with_expiry(url_hash, url) {
             self.metrics.hot_hits.fetch_add(1, Ordering::Relaxed);
             self.metrics
                 .last_access_ns
                 .store(start.elapsed().as_nanos() as u64, Ordering::Relaxed);
-            return Some(html);
+            return Some(found);
         }
 
         // 2. Check cold cache (RAM)
-        if let Some(html) = self.cold_cache.get(url_hash) {
-            self.metrics.cold_hits.fetch_add(1, Ordering::Relaxed);
+        let (html, expires_at, stats) = self.cold_cache.get_with_stats(url_hash, url)?;
+        self.metrics.cold_hits.fetch_add(1, Ordering::Relaxed);
+        stats.record_hit();
 
-            // Promote to hot cache
-            let mut hot_ref = hot.borrow_mut();
-            hot_ref.cache.insert(url_hash, Arc::clone(&html));
-            self.metrics.promotions.fetch_add(1, Ordering::Relaxed);
+        // Promote to hot cache, keeping the expiry
+        let mut hot_ref = hot.borrow_mut();
+        hot_ref.cache.insert_with_stats(
+            url_hash,
+            url,
+            Arc::clone(&html),
+            remaining(expires_at),
+            Some(stats),
+        );
+        self.metrics.promotions.fetch_add(1, Ordering::Relaxed);
 
-            self.metrics
-                .last_access_ns
-                .store(start.elapsed().as_nanos() as u64, Ordering::Relaxed);
-            return Some(html);
+        self.metrics
+            .last_access_ns
+            .store(start.elapsed().as_nanos() as u64, Ordering::Relaxed);
+        Some((html, expires_at))
+    }
+
+    /// Look a page up in the disk tier, promoting hits to cold and hot caches
+    async fn get_disk(
+        &self,
+        url_hash: u64,
+        url: &str,
+        start: Instant,
+    ) -> Option<(Arc<str>, Option<Instant>)> {
+        let disk = self.disk_cache.as_ref()?;
+        // Skip the blocking thread for pages not on disk
+        if !disk.contains(url_hash, url) {
+            return None;
         }
 
-        self.metrics.misses.fetch_add(1, Ordering::Relaxed);
-        None
+        let read = {
+            let disk = Arc::clone(disk);
+            let url = url.to_string();
+            move || disk.get_with_expiry(url_hash, &url)
+        };
+        let (html, expires_at) = match tokio::runtime::Handle::try_current() {
+            Ok(runtime) => runtime.spawn_blocking(read).await.ok()??,
+            Err(_) => read()?,
+        };
+        self.metrics.disk_hits.fetch_add(1, Ordering::Relaxed);
+
+        // Promote to cold and hot caches, keeping the expiry
+        let ttl = remaining(expires_at);
+        let evicted = self.insert_cold(url_hash, url, Arc::clone(&html), ttl);
+        self.record_evictions(evicted);
+        let stats = self.cold_cache.stats(url_hash, url);
+        if let Some(stats) = &stats {
+            stats.record_hit();
+        }
+        self.get_or_init_hot_cache()
+            .borrow_mut()
+            .cache
+            .insert_with_stats(url_hash, url, Arc::clone(&html), ttl, stats);
+        self.metrics.promotions.fetch_add(1, Ordering::Relaxed);
+
+        self.metrics
+            .last_access_ns
+            .store(start.elapsed().as_nanos() as u64, Ordering::Relaxed);
+        Some((html, expires_at))
+    }
+
+    /// Check whether a URL is cached, without touching hit/miss metrics
+    pub fn contains(&self, url: &str) -> bool {
+        let url_hash = self.url_hash(url);
+        self.cold_cache.get(url_hash, url).is_some()
+            || self
+                .disk_cache
+                .as_ref()
+                .is_some_and(|d| d.contains(url_hash, url))
+    }
+
+    /// Check whether a key is in a local tier, without marking it as used
+    fn is_cached(&self, key: &str) -> bool {
+        let url_hash = self.url_hash(key);
+        self.cold_cache.stats(url_hash, key).is_some()
+            || self
+                .disk_cache
+                .as_ref()
+                .is_some_and(|d| d.contains(url_hash, key))
     }
 
     /// Insert HTML into cache
+    ///
+    /// With a shared backend, the page is also written to it in the
+    /// background when called within a Tokio runtime.
     pub fn insert(&self, url: &str, html: Arc<str>) {
-        let url_hash = hash_url(url);
+        self.insert_with_ttl(url, html, None);
+    }
 
-        // Insert into cold cache
-        let evicted = self.cold_cache.insert(url_hash, url, Arc::clone(&html));
-        self.metrics.insertions.fetch_add(1, Ordering::Relaxed);
+    /// Insert HTML that expires after `ttl` instead of the cache TTL
+    ///
+    /// `None` uses the cache TTL.
+    pub fn insert_with_ttl(&self, url: &str, html: Arc<str>, ttl: Option<Duration>) {
+        if self.is_oversized(url, &html) {
+            return;
+        }
+
+        if let Some(backend) = &self.backend {
+            let ttl = ttl.or(self.default_ttl());
+            let backend = Arc::clone(backend);
+            let key = url.to_string();
+            let html = Arc::clone(&html);
+            self.spawn_remote("write", url, async move {
+                backend.set(&key, html, ttl).await
+            });
+        }
+
+        self.insert_local(url, html, ttl);
+    }
+
+    /// Check the maximum entry size, dropping any stale cached version
+    ///
+    /// Without a per-entry limit, pages larger than the whole cold cache
+    /// budget are oversized. The stale version is removed from every tier,
+    /// including the shared backend.
+    fn is_oversized(&self, url: &str, html: &str) -> bool {
+        let max = self.max_entry_bytes.or(self.cold_cache.capacity_bytes());
+        if max.is_none_or(|max| html.len() <= max) {
+            return false;
+        }
+
+        self.metrics.oversized.fetch_add(1, Ordering::Relaxed);
+        tracing::debug!("Not caching {} ({} bytes exceeds max entry size)", url, html.len());
+        if let Some(backend) = &self.backend {
+            let backend = Arc::clone(backend);
+            let owned = url.to_string();
+            self.spawn_remote("delete", url, async move { backend.delete(&owned).await });
+        }
+        self.remove_local(url);
+        true
+    }
+
+    /// Count cold cache evictions, and stop tracking the route keys they removed
+    fn record_evictions(&self, evicted: usize) {
         if evicted > 0 {
             self.metrics.evictions.fetch_add(evicted as u64, Ordering::Relaxed);
+            self.routes.prune(|key| self.is_cached(key));
+        }
+    }
+
+    /// Insert into the cold cache, compressing if configured
+    ///
+    /// Returns the number of evicted entries.
+    fn insert_cold(
+        &self,
+        url_hash: u64,
+        url: &str,
+        html: Arc<str>,
+        ttl: Option<Duration>,
+    ) -> usize {
+        #[cfg(feature = "cache-compression")]
+        if let Some(compression) = &self.compression {
+            match CompressedPage::compress(&html, &compression.encodings) {
+                Ok(compressed) => {
+                    let raw = compression.keep_raw.then_some(html);
+                    return self.cold_cache.insert_compressed(
+                        url_hash,
+                        url,
+                        raw,
+                        Arc::new(compressed),
+                        ttl,
+                    );
+                }
+                Err(e) => tracing::warn!("⚠️ Failed to compress {}: {}", url, e),
+            }
+        }
+
+        self.cold_cache.insert_with_ttl(url_hash, url, html, ttl)
+    }
+
+    /// Insert into the hot, cold and disk tiers
+    fn insert_local(&self, url: &str, html: Arc<str>, ttl: Option<Duration>) {
+        let url_hash = self.url_hash(url);
+
+        // Insert into cold cache
+        let evicted = self.insert_cold(url_hash, url, Arc::clone(&html), ttl);
+        self.metrics.insertions.fetch_add(1, Ordering::Relaxed);
+        self.record_evictions(evicted);
+
+        // Write through to disk cache, in the background
+        if let Some(disk) = &self.disk_cache {
+            let ticket = disk.ticket();
+            let disk = Arc::clone(disk);
+            let metrics = Arc::clone(&self.metrics);
+            let url = url.to_string();
+            let html = Arc::clone(&html);
+            spawn_blocking_io(move || {
+                let evicted = disk.insert_ticketed(ticket, url_hash, &url, &html, ttl);
+                if evicted > 0 {
+                    metrics
+                        .disk_evictions
+                        .fetch_add(evicted as u64, Ordering::Relaxed);
+                }
+            });
         }
 
-        // Insert into hot cache
+        // Insert into hot cache, sharing the cold entry's access stats
+        let stats = self.cold_cache.stats(url_hash, url);
         let hot = self.get_or_init_hot_cache();
-        let mut hot_ref = hot.borrow_mut();
-        hot_ref.cache.insert(url_hash, html);
+        hot.borrow_mut()
+            .cache
+            .insert_with_stats(url_hash, url, html, ttl, stats);
     }
 
     /// Invalidate a single cached URL
     ///
     /// Removes from cold cache and bumps generation to clear hot caches.
     /// Other hot-cached entries will be re-promoted from cold on next access.
+    /// Variants of the page keyed `{url}#...`, i.e. per render data or render
+    /// entry, are removed too. On a route with `vary_query`, the page is also
+    /// removed under its route key. The shared backend entry of the URL is
+    /// deleted in the background; its variants there expire with their TTL.
     pub fn invalidate(&self, url: &str) {
-        let url_hash = hash_url(url);
-        if self.cold_cache.remove(url_hash) {
+        let page_key = self.routes.page_key(url);
+        let keys = if page_key == url {
+            vec![url]
+        } else {
+            vec![url, page_key.as_str()]
+        };
+
+        for key in keys {
+            if let Some(backend) = &self.backend {
+                let backend = Arc::clone(backend);
+                let owned = key.to_string();
+                self.spawn_remote("delete", key, async move { backend.delete(&owned).await });
+            }
+
+            self.remove_local(key);
+            self.invalidate_prefix(&format!("{}#", key));
+        }
+    }
+
+    /// Remove a URL from the local tiers
+    fn remove_local(&self, url: &str) {
+        let url_hash = self.url_hash(url);
+        let on_disk = self
+            .disk_cache
+            .as_ref()
+            .and_then(|d| d.detach(url_hash, url));
+        let removed = on_disk.is_some();
+        if let Some(path) = on_disk {
+            spawn_blocking_io(move || disk::delete_files(&[path]));
+        }
+        if self.cold_cache.remove(url_hash, url) || removed {
             self.generation.fetch_add(1, Ordering::Relaxed);
         }
+        self.routes.forget(url);
     }
 
     /// Invalidate all cached URLs that start with the given prefix
@@ -180,8 +883,16 @@ impl SsrCache {
     ///
     /// Also bumps the generation counter to clear all hot caches,
     /// ensuring stale entries don't survive in thread-local caches.
+    /// The shared backend is not affected; rely on its TTL.
     pub fn invalidate_prefix(&self, prefix: &str) -> usize {
-        let removed = self.cold_cache.remove_by_prefix(prefix);
+        self.routes.forget_prefix(prefix);
+        let mut removed = self.cold_cache.remove_by_prefix(prefix);
+        if let Some(disk) = &self.disk_cache {
+            // Pages may be on disk only (e.g. after a restart)
+            let paths = disk.detach_by_prefix(prefix);
+            removed = removed.max(paths.len());
+            spawn_blocking_io(move || disk::delete_files(&paths));
+        }
         if removed > 0 {
             // Bump generation to invalidate hot caches that may hold stale entries
             self.generation.fetch_add(1, Ordering::Relaxed);
@@ -189,11 +900,18 @@ impl SsrCache {
         removed
     }
 
-    /// Clear the cache, including hot caches and metrics
+    /// Clear the cache, including hot caches and the disk cache
+    ///
+    /// Metrics keep counting, so they stay monotonic for scrapers. The
+    /// shared backend is not affected.
     pub fn clear(&self) {
         self.cold_cache.clear();
+        if let Some(disk) = &self.disk_cache {
+            let paths = disk.detach_all();
+            spawn_blocking_io(move || disk::delete_files(&paths));
+        }
         self.generation.fetch_add(1, Ordering::Relaxed);
-        self.reset_metrics();
+        self.routes.forget_prefix("");
     }
 
     /// Get current cold cache size
@@ -201,12 +919,94 @@ impl SsrCache {
         self.cold_cache.len()
     }
 
+    /// List cached pages, sorted by URL
+    ///
+    /// Lists the in-memory tier, which holds every page cached or hit since
+    /// startup; pages only on disk are not listed. Listing does not mark
+    /// pages as used.
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::sync::Arc;
+    /// use rusty_ssr::cache::{EntryQuery, SsrCache};
+    ///
+    /// let cache = SsrCache::new(100);
+    /// cache.insert("/blog/a", Arc::from("<html>a</html>"));
+    /// cache.insert("/about", Arc::from("<html>about</html>"));
+    ///
+    /// let page = cache.entries(&EntryQuery::new().prefix("/blog").limit(10));
+    /// assert_eq!(page.total, 1);
+    /// assert_eq!(page.entries[0].url, "/blog/a");
+    /// ```
+    pub fn entries(&self, query: &EntryQuery) -> EntryPage {
+        let mut entries = self
+            .cold_cache
+            .inspect_all(query.prefix.as_deref().unwrap_or(""));
+        entries.sort_unstable_by(|a, b| a.url.cmp(&b.url));
+
+        EntryPage {
+            total: entries.len(),
+            offset: query.offset,
+            entries: entries
+                .into_iter()
+                .skip(query.offset)
+                .take(query.limit)
+                .map(|info| self.tag_route(info))
+                .collect(),
+        }
+    }
+
+    /// Look at a cached page without marking it as used or counting a hit
+    pub fn peek_entry(&self, url: &str) -> Option<CachedPage> {
+        let (info, html) = self.cold_cache.inspect(self.url_hash(url), url)?;
+        Some(CachedPage {
+            info: self.tag_route(info),
+            html: html.to_string(),
+        })
+    }
+
+    /// Export the in-memory tier, sorted by URL
+    pub fn export_snapshot(&self) -> CacheSnapshot {
+        let mut entries = self.cold_cache.snapshot();
+        entries.sort_unstable_by(|a, b| a.url.cmp(&b.url));
+        CacheSnapshot { entries }
+    }
+
+    /// Load the pages of a snapshot into the local tiers
+    ///
+    /// Pages keep the TTL they had left when exported. Expired and
+    /// oversized pages are skipped, and the shared backend is not written.
+    /// Returns the number of imported pages.
+    pub fn import_snapshot(&self, snapshot: &CacheSnapshot) -> usize {
+        let mut imported = 0;
+        for entry in &snapshot.entries {
+            if entry.ttl_secs == Some(0) || self.is_oversized(&entry.url, &entry.html) {
+                continue;
+            }
+            let ttl = entry.ttl_secs.map(Duration::from_secs);
+            self.insert_local(&entry.url, Arc::from(entry.html.as_str()), ttl);
+            imported += 1;
+        }
+
+        tracing::info!("📦 Imported {} cached pages from snapshot", imported);
+        imported
+    }
+
+    /// Add the matching route to an entry's tags
+    fn tag_route(&self, mut info: CacheEntryInfo) -> CacheEntryInfo {
+        if let Some(pattern) = self.routes.pattern(&info.url) {
+            info.tags.insert(0, format!("route:{}", pattern));
+        }
+        info
+    }
+
     /// Get cache metrics
     pub fn metrics(&self) -> CacheMetrics {
         let lookups = self.metrics.lookups.load(Ordering::Relaxed);
         let hot_hits = self.metrics.hot_hits.load(Ordering::Relaxed);
         let cold_hits = self.metrics.cold_hits.load(Ordering::Relaxed);
-        let total_hits = hot_hits + cold_hits;
+        let disk_hits = self.metrics.disk_hits.load(Ordering::Relaxed);
+        let total_hits = hot_hits + cold_hits + disk_hits;
 
         CacheMetrics {
             lookups,
@@ -219,25 +1019,45 @@ impl SsrCache {
             last_access_ns: self.metrics.last_access_ns.load(Ordering::Relaxed),
             cold_size: self.cold_cache.len(),
             cold_capacity: self.cold_cache.capacity(),
+            cold_bytes: self.cold_cache.size_bytes(),
+            cold_capacity_bytes: self.cold_cache.capacity_bytes().unwrap_or(0),
+            oversized: self.metrics.oversized.load(Ordering::Relaxed),
+            encoded_hits: self.metrics.encoded_hits.load(Ordering::Relaxed),
             hit_rate: if lookups > 0 {
                 (total_hits as f64 / lookups as f64) * 100.0
             } else {
                 0.0
             },
+            disk_hits,
+            disk_evictions: self.metrics.disk_evictions.load(Ordering::Relaxed),
+            disk_size: self.disk_cache.as_ref().map_or(0, |d| d.len()),
+            disk_bytes: self.disk_cache.as_ref().map_or(0, |d| d.size_bytes()),
+            disk_capacity_bytes: self.disk_cache.as_ref().map_or(0, |d| d.capacity_bytes()),
+            remote_hits: self.metrics.remote_hits.load(Ordering::Relaxed),
+            remote_misses: self.metrics.remote_misses.load(Ordering::Relaxed),
+            remote_errors: self.metrics.remote_errors.load(Ordering::Relaxed),
+            routes: self.routes.metrics(),
         }
     }
 
-    fn reset_metrics(&self) {
-        self.metrics.lookups.store(0, Ordering::Relaxed);
-        self.metrics.hot_hits.store(0, Ordering::Relaxed);
-        self.metrics.cold_hits.store(0, Ordering::Relaxed);
-        self.metrics.misses.store(0, Ordering::Relaxed);
-        self.metrics.promotions.store(0, Ordering::Relaxed);
-        self.metrics.insertions.store(0, Ordering::Relaxed);
-        self.metrics.evictions.store(0, Ordering::Relaxed);
-        self.metrics
-            .last_access_ns
-            .store(0, Ordering::Relaxed);
+    /// Run a backend operation in the background, counting failures
+    fn spawn_remote<F>(&self, op: &'static str, url: &str, task: F)
+    where
+        F: Future<Output = io::Result<()>> + Send + 'static,
+    {
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            tracing::debug!("No Tokio runtime, skipping cache backend {} for {}", op, url);
+            return;
+        };
+
+        let metrics = Arc::clone(&self.metrics);
+        let url = url.to_string();
+        runtime.spawn(async move {
+            if let Err(e) = task.await {
+                metrics.remote_errors.fetch_add(1, Ordering::Relaxed);
+                tracing::warn!("⚠️ Cache backend {} failed for {}: {}", op, url, e);
+            }
+        });
     }
 
     fn get_or_init_hot_cache(&self) -> &RefCell<HotCacheState> {
@@ -261,9 +1081,27 @@ impl SsrCache {
     }
 }
 
+/// Run blocking disk I/O on Tokio's blocking threads, or inline outside a runtime
+fn spawn_blocking_io(task: impl FnOnce() + Send + 'static) {
+    match tokio::runtime::Handle::try_current() {
+        Ok(runtime) => {
+            runtime.spawn_blocking(task);
+        }
+        Err(_) => task(),
+    }
+}
+
+/// Time left until `expires_at`, as a TTL for promoting an entry
+fn remaining(expires_at: Option<Instant>) -> Option<Duration> {
+    expires_at.map(|at| at.saturating_duration_since(Instant::now()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cache::{MemoryBackend, RoutePolicy};
+    use futures::executor::block_on;
+    use futures::future::{self, BoxFuture, FutureExt};
 
     #[test]
     fn test_basic_caching() {
@@ -288,6 +1126,46 @@ mod tests {
         assert_eq!(metrics.misses, 1);
     }
 
+    #[test]
+    fn test_hash_collisions_never_serve_another_page() {
+        let dir = tempfile::tempdir().unwrap();
+        let disk = DiskCache::open(dir.path(), "test", 1 << 20, 0).unwrap();
+        let cache = SsrCache::new(100).with_disk_cache(disk).with_hasher(|_| 42);
+
+        cache.insert("/a", Arc::from("page a"));
+        // Hot, cold and disk tiers all hold /a under the shared hash
+        assert!(cache.try_get("/b").is_none());
+        assert!(!cache.contains("/b"));
+
+        cache.insert("/b", Arc::from("page b"));
+        assert_eq!(cache.try_get("/b").as_deref(), Some("page b"));
+        // /a may have been displaced, but never resolves to /b's page
+        assert_ne!(cache.try_get("/a").as_deref(), Some("page b"));
+
+        // Same on disk, for a cache with empty memory tiers
+        let disk = DiskCache::open(dir.path(), "test", 1 << 20, 0).unwrap();
+        let cache = SsrCache::new(100).with_disk_cache(disk).with_hasher(|_| 42);
+        assert_ne!(block_on(cache.get("/a")).as_deref(), Some("page b"));
+
+        // Invalidating a URL keeps the page of another URL with its hash
+        let dir = tempfile::tempdir().unwrap();
+        // Reopened with the real hashes, as the index is rebuilt with them
+        let open = || {
+            SsrCache::new(100)
+                .with_disk_cache(DiskCache::open(dir.path(), "test", 1 << 20, 0).unwrap())
+        };
+        let disk = DiskCache::open(dir.path(), "test", 1 << 20, 0).unwrap();
+        let cache = SsrCache::new(100).with_disk_cache(disk).with_hasher(|_| 42);
+        cache.insert("/a", Arc::from("page a"));
+        cache.invalidate("/b");
+        assert_eq!(cache.try_get("/a").as_deref(), Some("page a"));
+        assert_eq!(block_on(open().get("/a")).as_deref(), Some("page a"));
+
+        cache.invalidate("/a");
+        assert!(cache.try_get("/a").is_none());
+        assert!(block_on(open().get("/a")).is_none());
+    }
+
     #[test]
     fn test_invalidate_single() {
         let cache = SsrCache::new(100);
@@ -301,6 +1179,41 @@ mod tests {
         assert!(cache.try_get("/b").is_some());
     }
 
+    #[test]
+    fn test_invalidate_removes_variants() {
+        let cache = SsrCache::new(100);
+
+        let entry = cache.entry_key("renderEmail", "/a", r#"{"name":"Ann"}"#);
+        cache.insert("/a", Arc::from("html_a"));
+        cache.insert(entry.key(), Arc::from("email_a"));
+        cache.insert("/ab", Arc::from("html_ab"));
+
+        cache.invalidate("/a");
+
+        assert!(cache.try_get("/a").is_none());
+        assert!(cache.try_get(entry.key()).is_none());
+        assert!(cache.try_get("/ab").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_entry_keys_follow_routes() {
+        let routes = CacheRoutes::new([("/checkout/**", RoutePolicy::no_store())]).unwrap();
+        let cache = SsrCache::new(100).with_routes(routes);
+
+        let ann = cache.entry_key("renderEmail", "/welcome", r#"{"name":"Ann"}"#);
+        let bob = cache.entry_key("renderEmail", "/welcome", r#"{"name":"Bob"}"#);
+        assert_ne!(ann.key(), bob.key());
+        assert_ne!(ann.key(), cache.route_key("/welcome", "{}").key());
+        assert_eq!(
+            ann,
+            cache.entry_key("renderEmail", "/welcome", r#"{"name":"Ann"}"#)
+        );
+
+        let checkout = cache.entry_key("renderEmail", "/checkout/pay", "{}");
+        assert!(!checkout.is_cacheable());
+        assert!(matches!(cache.lookup(&checkout).await, RouteLookup::Bypass));
+    }
+
     #[test]
     fn test_invalidate_prefix() {
         let cache = SsrCache::new(100);
@@ -318,7 +1231,7 @@ mod tests {
     }
 
     #[test]
-    fn test_clear_removes_hot_and_resets_metrics() {
+    fn test_clear_removes_hot_and_keeps_metrics() {
         let cache = SsrCache::with_ttl(16, 10);
 
         cache.insert("/hot", Arc::from("html"));
@@ -326,11 +1239,429 @@ mod tests {
 
         cache.clear();
 
-        // After clearing, both caches should miss and metrics reset
+        // After clearing, both caches should miss and counters keep counting
         assert!(cache.try_get("/hot").is_none(), "hot cache should be cleared");
         let metrics = cache.metrics();
-        assert_eq!(metrics.insertions, 0);
-        assert_eq!(metrics.lookups, 1);
+        assert_eq!(metrics.insertions, 1);
+        assert_eq!(metrics.lookups, 2);
         assert_eq!(metrics.misses, 1);
     }
+
+    #[test]
+    fn test_disk_tier_survives_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let open = || {
+            SsrCache::new(100)
+                .with_disk_cache(DiskCache::open(dir.path(), "v1", 1 << 20, 0).unwrap())
+        };
+
+        open().insert("/page", Arc::from("html"));
+
+        // A new cache (e.g. after a restart) falls back to disk on cold miss
+        let cache = open();
+        assert!(cache.contains("/page"));
+        assert!(cache.try_get("/page").is_none(), "try_get never reads the disk");
+        assert_eq!(block_on(cache.get("/page")).as_deref(), Some("html"));
+        assert_eq!(block_on(cache.get("/page")).as_deref(), Some("html"));
+
+        let metrics = cache.metrics();
+        assert_eq!(metrics.disk_hits, 1, "second lookup is served from RAM");
+        assert_eq!(metrics.disk_size, 1);
+        assert!(metrics.disk_bytes > 0);
+        assert_eq!(metrics.disk_capacity_bytes, 1 << 20);
+
+        cache.invalidate("/page");
+        assert!(block_on(open().get("/page")).is_none());
+        assert_eq!(open().metrics().disk_size, 0);
+    }
+
+    #[test]
+    fn test_data_keys_survive_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let routes = || CacheRoutes::new([("/feed", RoutePolicy::new().vary_data())]).unwrap();
+        let open = || {
+            SsrCache::new(100)
+                .with_routes(routes())
+                .with_disk_cache(DiskCache::open(dir.path(), "v1", 1 << 20, 0).unwrap())
+        };
+
+        let key = open().route_key("/feed", r#"{"user":1}"#).key;
+        assert_eq!(open().route_key("/feed", r#"{"user":1}"#).key, key);
+        assert_eq!(
+            open().entry_key("Card", "/p", "{}").key,
+            open().entry_key("Card", "/p", "{}").key
+        );
+
+        // Without a disk tier, or under another key, data hashes differ
+        let memory = SsrCache::new(100).with_routes(routes());
+        assert_ne!(memory.route_key("/feed", r#"{"user":1}"#).key, key);
+
+        // Replicas with the same key agree
+        let replica = || SsrCache::new(100).with_routes(routes()).with_hash_key([7; 16]);
+        assert_eq!(
+            replica().route_key("/feed", "{}").key,
+            replica().route_key("/feed", "{}").key
+        );
+        let with_disk = open().with_hash_key([7; 16]);
+        assert_eq!(
+            with_disk.route_key("/feed", "{}").key,
+            replica().route_key("/feed", "{}").key
+        );
+        assert_eq!(
+            open().route_key("/feed", "{}").key,
+            replica().route_key("/feed", "{}").key,
+            "the explicit key is persisted"
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_disk_reads_in_runtime() {
+        let dir = tempfile::tempdir().unwrap();
+        // Written outside the runtime, as by a previous process
+        let previous = DiskCache::open(dir.path(), "v1", 1 << 20, 0).unwrap();
+        previous.insert(previous.url_hash("/page"), "/page", "html");
+        drop(previous);
+
+        let disk = DiskCache::open(dir.path(), "v1", 1 << 20, 0).unwrap();
+        let cache = SsrCache::new(100).with_disk_cache(disk);
+        assert_eq!(cache.get("/page").await.as_deref(), Some("html"));
+        assert_eq!(cache.metrics().disk_hits, 1);
+        assert!(cache.get("/missing").await.is_none());
+        assert_eq!(cache.metrics().misses, 1);
+    }
+
+    #[derive(Debug)]
+    struct FailingBackend;
+
+    impl CacheBackend for FailingBackend {
+        fn get<'a>(&'a self, _: &'a str) -> BoxFuture<'a, io::Result<Option<Arc<str>>>> {
+            future::ready(Err(io::ErrorKind::ConnectionRefused.into())).boxed()
+        }
+
+        fn set<'a>(
+            &'a self,
+            _: &'a str,
+            _: Arc<str>,
+            _: Option<Duration>,
+        ) -> BoxFuture<'a, io::Result<()>> {
+            future::ready(Err(io::ErrorKind::ConnectionRefused.into())).boxed()
+        }
+
+        fn delete<'a>(&'a self, _: &'a str) -> BoxFuture<'a, io::Result<()>> {
+            future::ready(Err(io::ErrorKind::ConnectionRefused.into())).boxed()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_backend_shared_between_replicas() {
+        let shared = Arc::new(MemoryBackend::new());
+        let replica_a = SsrCache::new(100).with_backend(shared.clone());
+        let replica_b = SsrCache::new(100).with_backend(shared.clone());
+
+        assert!(replica_b.get("/page").await.is_none());
+        replica_a.insert("/page", Arc::from("html"));
+        // Write-through runs in the background
+        tokio::task::yield_now().await;
+
+        assert_eq!(replica_b.get("/page").await.as_deref(), Some("html"));
+        // Promoted locally: the second lookup does not reach the backend
+        assert_eq!(replica_b.get("/page").await.as_deref(), Some("html"));
+
+        let metrics = replica_b.metrics();
+        assert_eq!(metrics.remote_misses, 1);
+        assert_eq!(metrics.remote_hits, 1);
+        assert_eq!(metrics.cold_hits + metrics.hot_hits, 1);
+
+        replica_a.invalidate("/page");
+        tokio::task::yield_now().await;
+        assert!(shared.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_backend_failures_are_not_fatal() {
+        let cache = SsrCache::new(100).with_backend(Arc::new(FailingBackend));
+
+        assert!(cache.get("/page").await.is_none());
+        cache.insert("/page", Arc::from("html"));
+        tokio::task::yield_now().await;
+
+        assert_eq!(cache.get("/page").await.as_deref(), Some("html"));
+        assert_eq!(cache.metrics().remote_errors, 2);
+    }
+
+    #[test]
+    fn test_byte_budget_and_max_entry_size() {
+        let cache = SsrCache::new(100)
+            .with_max_bytes(64 * 1024)
+            .with_max_entry_bytes(16 * 1024);
+
+        cache.insert("/small", Arc::from("x".repeat(1024)));
+        cache.insert("/huge", Arc::from("x".repeat(32 * 1024)));
+        assert!(cache.try_get("/small").is_some());
+        assert!(cache.try_get("/huge").is_none());
+
+        for i in 0..10 {
+            cache.insert(&format!("/page/{}", i), Arc::from("x".repeat(10 * 1024)));
+        }
+
+        let metrics = cache.metrics();
+        assert_eq!(metrics.oversized, 1);
+        assert_eq!(metrics.cold_capacity_bytes, 64 * 1024);
+        assert!(metrics.cold_bytes <= 64 * 1024);
+        assert!(metrics.evictions > 0);
+    }
+
+    #[test]
+    fn test_growing_page_leaves_every_tier() {
+        let dir = tempfile::tempdir().unwrap();
+        let open = || {
+            SsrCache::new(100)
+                .with_max_bytes(64 * 1024)
+                .with_disk_cache(DiskCache::open(dir.path(), "v1", 1 << 20, 0).unwrap())
+        };
+
+        let cache = open();
+        cache.insert("/page", Arc::from("small"));
+        assert!(cache.contains("/page"));
+
+        // Larger than the whole budget, with no per-entry limit set
+        cache.insert("/page", Arc::from("x".repeat(128 * 1024)));
+        assert!(cache.try_get("/page").is_none());
+        assert!(block_on(cache.get("/page")).is_none());
+        // The disk copy is gone too, not just the memory tiers
+        assert!(block_on(open().get("/page")).is_none());
+        assert_eq!(cache.metrics().oversized, 1);
+    }
+
+    #[tokio::test]
+    async fn test_route_policies() {
+        let routes = CacheRoutes::new([
+            ("/checkout/**", RoutePolicy::no_store()),
+            ("/products/:id", RoutePolicy::new().max_entries(2)),
+        ])
+        .unwrap();
+        let cache = SsrCache::with_ttl(100, 3600).with_routes(routes);
+
+        let checkout = cache.route_key("/checkout/pay", "{}");
+        assert!(!checkout.is_cacheable());
+        cache.insert_routed(&checkout, Arc::from("private"));
+        assert!(matches!(cache.lookup(&checkout).await, RouteLookup::Bypass));
+        assert!(cache.try_get("/checkout/pay").is_none());
+
+        for id in 1..=3 {
+            let key = cache.route_key(&format!("/products/{}", id), "{}");
+            assert!(matches!(cache.lookup(&key).await, RouteLookup::Miss));
+            cache.insert_routed(&key, Arc::from(format!("product {}", id)));
+        }
+        let key = cache.route_key("/products/3", "{}");
+        assert!(matches!(cache.lookup(&key).await, RouteLookup::Hit(_)));
+        // Oldest page of the route was evicted
+        assert!(cache.try_get("/products/1").is_none());
+        assert!(cache.try_get("/products/2").is_some());
+
+        // Unrouted URLs are cached as usual
+        let about = cache.route_key("/about", "{}");
+        assert!(!about.is_routed());
+        cache.insert_routed(&about, Arc::from("about"));
+        assert!(matches!(cache.lookup(&about).await, RouteLookup::Hit(_)));
+
+        let metrics = cache.metrics();
+        assert_eq!(metrics.routes.len(), 2);
+        assert_eq!(metrics.routes[0].pattern, "/checkout/**");
+        assert_eq!(metrics.routes[0].bypasses, 1);
+        assert_eq!(metrics.routes[1].misses, 3);
+        assert_eq!(metrics.routes[1].hits, 1);
+        assert_eq!(metrics.routes[1].evictions, 1);
+        assert_eq!(metrics.routes[1].entries, Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_route_entries_follow_the_cache() {
+        let routes = CacheRoutes::new([
+            (
+                "/search",
+                RoutePolicy::new().vary_query(["q", "page"]).max_entries(2),
+            ),
+            (
+                "/news/:id",
+                RoutePolicy::new()
+                    .ttl(Duration::from_millis(20))
+                    .max_entries(2),
+            ),
+        ])
+        .unwrap();
+        let cache = SsrCache::with_ttl(100, 3600).with_routes(routes);
+
+        let shoes = cache.route_key("/search?q=shoes&page=1", "{}");
+        let hats = cache.route_key("/search?q=hats", "{}");
+        cache.insert_routed(&shoes, Arc::from("shoes"));
+        cache.insert_routed(&hats, Arc::from("hats"));
+
+        // Invalidating the raw URL removes the page under its route key
+        cache.invalidate("/search?utm_source=x&page=1&q=shoes");
+        assert!(matches!(cache.lookup(&shoes).await, RouteLookup::Miss));
+        assert_eq!(cache.metrics().routes[0].entries, Some(1));
+
+        let boots = cache.route_key("/search?q=boots", "{}");
+        cache.insert_routed(&boots, Arc::from("boots"));
+        assert!(
+            matches!(cache.lookup(&hats).await, RouteLookup::Hit(_)),
+            "the invalidated page freed its slot"
+        );
+
+        cache.invalidate_prefix("/search");
+        assert_eq!(cache.metrics().routes[0].entries, Some(0));
+
+        // Expired pages are dropped without counting as evictions
+        for id in 1..=2 {
+            let key = cache.route_key(&format!("/news/{}", id), "{}");
+            cache.insert_routed(&key, Arc::from("news"));
+        }
+        tokio::time::sleep(Duration::from_millis(40)).await;
+
+        let key = cache.route_key("/news/3", "{}");
+        cache.insert_routed(&key, Arc::from("news"));
+        let metrics = cache.metrics();
+        assert_eq!(metrics.routes[0].evictions, 0);
+        assert_eq!(metrics.routes[1].evictions, 0);
+        assert_eq!(metrics.routes[1].entries, Some(1));
+    }
+
+    #[test]
+    fn test_cold_evictions_free_route_slots() {
+        let routes = CacheRoutes::new([("/p/:id", RoutePolicy::new().max_entries(100))]).unwrap();
+        let cache = SsrCache::new(10).with_routes(routes);
+
+        for id in 0..5 {
+            let key = cache.route_key(&format!("/p/{}", id), "{}");
+            cache.insert_routed(&key, Arc::from("page"));
+        }
+        // Pages of no route push the route's pages out of the cold cache
+        for id in 0..20 {
+            cache.insert(&format!("/other/{}", id), Arc::from("page"));
+        }
+
+        let metrics = cache.metrics();
+        assert!(metrics.evictions >= 5);
+        assert_eq!(metrics.routes[0].evictions, 0);
+        assert_eq!(metrics.routes[0].entries, Some(0));
+    }
+
+    #[tokio::test]
+    async fn test_route_ttl_and_stale_window() {
+        let routes = CacheRoutes::new([(
+            "/news",
+            RoutePolicy::new()
+                .ttl(Duration::from_millis(50))
+                .stale_while_revalidate(Duration::from_secs(3600)),
+        )])
+        .unwrap();
+        let cache = SsrCache::with_ttl(100, 3600).with_routes(routes);
+        let key = cache.route_key("/news", "{}");
+
+        cache.insert_routed(&key, Arc::from("v1"));
+        assert!(matches!(cache.lookup(&key).await, RouteLookup::Hit(_)));
+
+        tokio::time::sleep(Duration::from_millis(80)).await;
+        let guard = match cache.lookup(&key).await {
+            RouteLookup::Stale(html, guard) => {
+                assert_eq!(&*html, "v1");
+                guard
+            }
+            other => panic!("expected stale page, got {:?}", other),
+        };
+        // Others get the stale page while it is re-rendered
+        match cache.lookup(&key).await {
+            RouteLookup::Hit(html) => assert_eq!(&*html, "v1"),
+            other => panic!("expected stale hit, got {:?}", other),
+        }
+
+        cache.insert_routed(&key, Arc::from("v2"));
+        drop(guard);
+        match cache.lookup(&key).await {
+            RouteLookup::Hit(html) => assert_eq!(&*html, "v2"),
+            other => panic!("expected fresh page, got {:?}", other),
+        }
+        assert_eq!(cache.metrics().routes[0].stale_hits, 2);
+    }
+
+    #[cfg(feature = "cache-compression")]
+    #[test]
+    fn test_compressed_variants() {
+        let html = "<p>compressible</p>".repeat(200);
+        let cache = SsrCache::new(100).with_compression(
+            CacheCompression::new([Encoding::Brotli, Encoding::Gzip]).without_raw(),
+        );
+        cache.insert("/page", Arc::from(html.as_str()));
+
+        match cache.try_get_encoded("/page", "gzip, br") {
+            Some(EncodedHtml::Encoded(Encoding::Brotli, data)) => {
+                assert_eq!(Encoding::Brotli.decompress(&data).unwrap(), html.as_bytes());
+            }
+            other => panic!("expected brotli variant, got {:?}", other),
+        }
+        // Clients without compression get the decompressed HTML
+        match cache.try_get_encoded("/page", "identity") {
+            Some(EncodedHtml::Raw(raw)) => assert_eq!(&*raw, html),
+            other => panic!("expected raw HTML, got {:?}", other),
+        }
+
+        let metrics = cache.metrics();
+        assert_eq!(metrics.encoded_hits, 1);
+        assert!(metrics.cold_bytes < html.len(), "raw HTML is not stored");
+        let info = cache.peek_entry("/page").unwrap().info;
+        assert_eq!(info.hits, 2);
+        assert_eq!(info.tags, ["encoding:br", "encoding:gzip"]);
+    }
+
+    #[test]
+    fn test_entry_inspection() {
+        let routes = CacheRoutes::new([("/blog/**", RoutePolicy::new())]).unwrap();
+        let cache = SsrCache::with_ttl(100, 3600).with_routes(routes);
+        for url in ["/blog/b", "/blog/a", "/blog/c", "/about"] {
+            cache.insert(url, Arc::from(format!("<p>{}</p>", url)));
+        }
+
+        // Hits in the hot and cold tiers are counted
+        cache.try_get("/blog/a");
+        cache.generation.fetch_add(1, Ordering::Relaxed); // drop hot copies
+        cache.try_get("/blog/a");
+        cache.try_get("/blog/a");
+
+        let page = cache.entries(&EntryQuery::new().prefix("/blog/").offset(1).limit(1));
+        assert_eq!(page.total, 3);
+        assert_eq!(page.entries.len(), 1);
+        assert_eq!(page.entries[0].url, "/blog/b");
+        assert_eq!(page.entries[0].tags, ["route:/blog/**"]);
+
+        let peeked = cache.peek_entry("/blog/a").unwrap();
+        assert_eq!(peeked.html, "<p>/blog/a</p>");
+        assert_eq!(peeked.info.hits, 3);
+        assert!(peeked.info.expires_in_secs.unwrap() > 3500);
+        assert!(cache.peek_entry("/missing").is_none());
+
+        // Peeking counts neither a hit nor a lookup
+        assert_eq!(cache.peek_entry("/blog/a").unwrap().info.hits, 3);
+        assert_eq!(cache.metrics().lookups, 3);
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let cache = SsrCache::with_ttl(100, 3600);
+        cache.insert("/a", Arc::from("<p>a</p>"));
+        cache.insert_with_ttl("/b", Arc::from("<p>b</p>"), Some(Duration::from_secs(60)));
+
+        let snapshot = cache.export_snapshot();
+        assert_eq!(snapshot.entries.len(), 2);
+        assert_eq!(snapshot.entries[0].url, "/a");
+        assert!(snapshot.entries[1].ttl_secs.unwrap() <= 60);
+
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let restored = SsrCache::with_ttl(100, 3600);
+        let snapshot: CacheSnapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.import_snapshot(&snapshot), 2);
+        assert_eq!(restored.try_get("/a").as_deref(), Some("<p>a</p>"));
+        let info = restored.peek_entry("/b").unwrap().info;
+        assert!(info.expires_in_secs.unwrap() <= 60);
+    }
 }
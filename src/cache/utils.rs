@@ -1,16 +1,61 @@
 //! Cache utility functions
 
-use std::collections::hash_map::DefaultHasher;
-use std::hash::{Hash, Hasher};
+use siphasher::sip::SipHasher13;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
 
-/// Compute a hash for a URL to use as cache key
+/// Secret key of the URL and render data hashes in cache keys
 ///
-/// Uses DefaultHasher for fast string hashing.
-#[inline(always)]
-pub fn hash_url(url: &str) -> u64 {
-    let mut hasher = DefaultHasher::new();
-    url.hash(&mut hasher);
-    hasher.finish()
+/// Hashes are keyed SipHash-1-3, so URLs and render data cannot be crafted
+/// to collide; with a fixed hash, two render data values with the same hash
+/// would share one `#data=` cache key. Every tier still compares the stored
+/// URL on lookup. The disk cache persists its key, so persisted keys stay
+/// valid across restarts.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) struct HashKey(pub(crate) [u8; 16]);
+
+impl HashKey {
+    /// A new key from the process's random source
+    pub(crate) fn random() -> Self {
+        let state = RandomState::new();
+        let mut key = [0u8; 16];
+        key[..8].copy_from_slice(&state.hash_one(0u8).to_le_bytes());
+        key[8..].copy_from_slice(&state.hash_one(1u8).to_le_bytes());
+        Self(key)
+    }
+
+    /// Hash bytes, e.g. render data
+    pub(crate) fn hash(&self, bytes: &[u8]) -> u64 {
+        let mut hasher = SipHasher13::new_with_key(&self.0);
+        hasher.write(bytes);
+        hasher.finish()
+    }
+
+    /// Compute a hash for a URL to use as cache key
+    #[inline(always)]
+    pub(crate) fn hash_url(&self, url: &str) -> u64 {
+        self.hash(url.as_bytes())
+    }
+}
+
+impl std::fmt::Debug for HashKey {
+    // Never print the key
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("HashKey(..)")
+    }
+}
+
+/// FNV-1a hash that is stable across processes and Rust versions
+///
+/// Used for names that are persisted, such as disk cache files. Not keyed;
+/// cache keys use [`HashKey`].
+pub fn stable_hash(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    bytes.iter().fold(OFFSET_BASIS, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(PRIME)
+    })
 }
 
 #[cfg(test)]
@@ -19,15 +64,38 @@ mod tests {
 
     #[test]
     fn test_hash_consistency() {
-        let hash1 = hash_url("/test");
-        let hash2 = hash_url("/test");
-        assert_eq!(hash1, hash2);
+        let key = HashKey::random();
+        assert_eq!(key.hash_url("/test"), key.hash_url("/test"));
     }
 
     #[test]
     fn test_hash_different() {
-        let hash1 = hash_url("/page1");
-        let hash2 = hash_url("/page2");
-        assert_ne!(hash1, hash2);
+        let key = HashKey::random();
+        assert_ne!(key.hash_url("/page1"), key.hash_url("/page2"));
+    }
+
+    #[test]
+    fn test_hash_depends_on_key() {
+        let (a, b) = (HashKey::random(), HashKey::random());
+        assert_ne!(a, b);
+        assert_ne!(a.hash_url("/"), b.hash_url("/"));
+        assert_eq!(HashKey(a.0).hash_url("/"), a.hash_url("/"));
+    }
+
+    #[test]
+    fn test_siphash13_known_value() {
+        // SipHash-1-3 of an empty input under the key 00 01 .. 0f
+        let key = HashKey(std::array::from_fn(|i| i as u8));
+        assert_eq!(
+            key.hash(b""),
+            SipHasher13::new_with_keys(0x0706050403020100, 0x0f0e0d0c0b0a0908).finish()
+        );
+    }
+
+    #[test]
+    fn test_stable_hash_known_values() {
+        // Reference values of 64-bit FNV-1a
+        assert_eq!(stable_hash(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(stable_hash(b"a"), 0xaf63_dc4c_8601_ec8c);
     }
 }
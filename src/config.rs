@@ -1,9 +1,170 @@
 //! Configuration for Rusty SSR engine
 
+use std::collections::HashMap;
 use std::path::PathBuf;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use crate::error::{SsrError, SsrResult};
+use crate::normalize::UrlNormalizer;
+
+#[cfg(feature = "v8-pool")]
+use crate::v8_pool::Polyfills;
+
+#[cfg(feature = "cache-compression")]
+use crate::cache::CacheCompression;
+#[cfg(feature = "cache")]
+use crate::cache::{CacheBackend, CacheRoutes, Eviction, InvalidationBus, RoutePolicy};
+#[cfg(feature = "cache")]
+use std::sync::Arc;
+
+/// Format of the JavaScript SSR bundle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BundleFormat {
+    /// Classic script (IIFE/UMD) that assigns the render function to `globalThis`
+    #[default]
+    Script,
+
+    /// ES module that exports the render function
+    ///
+    /// The bundle is loaded as the main module and its imports are resolved
+    /// relative to the bundle directory.
+    Module,
+}
+
+/// How much JavaScript state survives from one render to the next
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RenderIsolation {
+    /// Renders share the isolate's globals (fastest)
+    #[default]
+    Shared,
+
+    /// Restore `globalThis` and the polyfill objects after every render
+    ///
+    /// Globals added or overwritten by a render are removed or restored and
+    /// `localStorage`/`sessionStorage` are cleared. Module-level state inside
+    /// the bundle is not reset.
+    ResetGlobals,
+
+    /// Rebuild the V8 runtime from a snapshot of the bundle after every render
+    ///
+    /// Nothing survives between renders, including module-level singletons.
+    /// The bundle is evaluated once when the pool starts and each new
+    /// isolate is restored from a V8 snapshot of the result, after the
+    /// response is sent. Work the bundle does at the top level, such as
+    /// reading the clock, runs only once. If the bundle cannot be
+    /// snapshotted, each rebuild evaluates it instead.
+    FreshRuntime,
+}
+
+/// Class of a render request, from most to least important
+///
+/// Requests wait in one queue per priority; see [`Scheduling`] for how
+/// workers pick between them. When the queue is full, queued requests of a
+/// lower priority are shed to make room.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Priority {
+    /// Pages a user is waiting for
+    #[default]
+    Interactive,
+
+    /// Renders nobody is waiting for, such as cache pre-warming
+    Background,
+
+    /// Requests from crawlers
+    Bot,
+}
+
+impl Priority {
+    /// Every priority, from most to least important
+    pub const ALL: [Priority; 3] = [Priority::Interactive, Priority::Background, Priority::Bot];
+
+    /// Short name of the priority
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Priority::Interactive => "interactive",
+            Priority::Background => "background",
+            Priority::Bot => "bot",
+        }
+    }
+
+    /// Position in [`Priority::ALL`]
+    #[cfg(feature = "v8-pool")]
+    pub(crate) fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// How V8 workers pick the next request between priorities
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scheduling {
+    /// Always render the most important queued request
+    ///
+    /// Lower priorities only run while no higher one is queued, so they
+    /// can starve under sustained load.
+    Strict,
+
+    /// Share the workers between queued priorities in proportion to their weights
+    ///
+    /// With the default weights, 8 of every 11 renders go to interactive
+    /// requests while all three priorities have work queued.
+    Weighted {
+        /// Weight of [`Priority::Interactive`]
+        interactive: u32,
+        /// Weight of [`Priority::Background`]
+        background: u32,
+        /// Weight of [`Priority::Bot`]
+        bot: u32,
+    },
+}
+
+impl Default for Scheduling {
+    fn default() -> Self {
+        Scheduling::Weighted {
+            interactive: 8,
+            background: 2,
+            bot: 1,
+        }
+    }
+}
+
+/// Options of a single render
+///
+/// # Example
+/// ```rust
+/// use std::time::{Duration, Instant};
+/// use rusty_ssr::{Priority, RenderOptions};
+///
+/// let options = RenderOptions {
+///     priority: Priority::Bot,
+///     deadline: Some(Instant::now() + Duration::from_secs(2)),
+/// };
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RenderOptions {
+    /// Priority of the request in the V8 pool queue
+    pub priority: Priority,
+
+    /// Fail with a timeout if no V8 worker started the render by then
+    ///
+    /// Without a deadline, waiting for room in the queue is bounded by the
+    /// configured `request_timeout` and queued renders never expire.
+    pub deadline: Option<Instant>,
+}
+
+/// Sandbox for ES module imports made by the bundle
+///
+/// Modules are only served from the bundle directory, the directories listed
+/// in `allowed_dirs`, or the in-memory `modules` map. `http(s):` and `node:`
+/// specifiers are rejected unless the exact specifier is registered in
+/// `modules` (e.g. a `node:buffer` shim or a vendored CDN module).
+#[derive(Debug, Clone, Default)]
+pub struct ModuleSandbox {
+    /// Additional directories modules may be loaded from
+    pub allowed_dirs: Vec<PathBuf>,
+
+    /// In-memory modules keyed by import specifier
+    pub modules: HashMap<String, String>,
+}
 
 /// Configuration for the SSR engine
 #[derive(Debug, Clone)]
@@ -11,12 +172,34 @@ pub struct SsrConfig {
     /// Path to the JavaScript SSR bundle
     pub bundle_path: PathBuf,
 
+    /// Format of the bundle (classic script or ES module)
+    pub bundle_format: BundleFormat,
+
+    /// Sandbox for module imports made by the bundle
+    pub module_sandbox: ModuleSandbox,
+
+    /// Browser polyfills installed before the bundle runs
+    #[cfg(feature = "v8-pool")]
+    pub polyfills: Polyfills,
+
+    /// Pre-load the code-split chunks the bundle imports into each isolate
+    pub preload_chunks: bool,
+
+    /// Public URL prefix for `<link rel="modulepreload">` tags of used chunks
+    pub chunk_base_url: Option<String>,
+
+    /// State isolation between renders
+    pub render_isolation: RenderIsolation,
+
     /// Number of V8 worker threads (default: number of CPUs)
     pub pool_size: usize,
 
     /// Size of the task queue for V8 pool
     pub queue_capacity: usize,
 
+    /// How workers pick the next request between priorities
+    pub scheduling: Scheduling,
+
     /// Pin V8 workers to specific CPU cores
     pub pin_threads: bool,
 
@@ -26,24 +209,119 @@ pub struct SsrConfig {
     /// Cache TTL (None = no expiration)
     pub cache_ttl: Option<Duration>,
 
+    /// Byte budget of the SSR cache (None = bounded by `cache_size` only)
+    pub cache_max_bytes: Option<usize>,
+    /// Pages larger than this many bytes are not cached (None = `cache_max_bytes`)
+    /// Pages larger than this many bytes are not cached (None = no limit)
+    pub cache_max_entry_bytes: Option<usize>,
+
+    /// Eviction policy of the SSR cache
+    #[cfg(feature = "cache")]
+    pub cache_eviction: Eviction,
+
+    /// Per-route cache policies, in matching order
+    #[cfg(feature = "cache")]
+    pub cache_routes: Vec<(String, RoutePolicy)>,
+
+    /// Pre-compressed variants stored with cached pages (None = raw only)
+    #[cfg(feature = "cache-compression")]
+    pub cache_compression: Option<CacheCompression>,
+
+    /// Directory of the persistent disk cache tier (None = disabled)
+    pub disk_cache_dir: Option<PathBuf>,
+
+    /// Maximum bytes stored by the disk cache tier
+    pub disk_cache_max_bytes: u64,
+
+    /// Disk cache namespace
+    ///
+    /// Pages cached under another namespace are never served. `None`
+    /// derives it from the bundle contents, so a new bundle version starts
+    /// with an empty disk cache. Other namespaces unchanged for a day are
+    /// deleted on startup, see `DiskCache::prune_namespaces`.
+    pub disk_cache_namespace: Option<String>,
+
+    /// Cache shared between replicas, consulted after a local miss
+    #[cfg(feature = "cache")]
+    pub cache_backend: Option<Arc<dyn CacheBackend>>,
+
+    /// Secret key of the render data hashes in cache keys (None = the key
+    /// of the disk cache, or a random key per process)
+    #[cfg(feature = "cache")]
+    pub cache_hash_key: Option<[u8; 16]>,
+
+    /// Bus broadcasting cache invalidations between replicas
+    #[cfg(feature = "cache")]
+    pub invalidation_bus: Option<Arc<dyn InvalidationBus>>,
+
+    /// Canonicalizes request URLs before the cache lookup and render
+    pub url_normalizer: Option<UrlNormalizer>,
+
     /// Request timeout for enqueueing render jobs
     pub request_timeout: Option<Duration>,
 
-    /// Name of the global render function in JS bundle
+    /// Name of the render function in JS bundle
+    ///
+    /// A `globalThis` property for [`BundleFormat::Script`], an export name
+    /// for [`BundleFormat::Module`] (`"default"` for the default export).
     pub render_function: String,
+
+    /// Additional render functions callable via `SsrEngine::render_entry`
+    ///
+    /// Named like `render_function`. Each entry is checked to exist in the
+    /// bundle at startup and gets its own cache namespace.
+    pub render_entries: Vec<String>,
+
+    /// URLs rendered by every worker at startup
+    ///
+    /// Warm-up renders JIT-compile hot paths and pre-fill the cache. If any
+    /// of them throws, engine construction fails.
+    pub warmup_urls: Vec<String>,
+
+    /// Maximum concurrent renders of `SsrEngine::prewarm`
+    pub prewarm_concurrency: usize,
 }
 
 impl Default for SsrConfig {
     fn default() -> Self {
         Self {
             bundle_path: PathBuf::from("ssr-bundle.js"),
+            bundle_format: BundleFormat::Script,
+            module_sandbox: ModuleSandbox::default(),
+            #[cfg(feature = "v8-pool")]
+            polyfills: Polyfills::default(),
+            preload_chunks: false,
+            chunk_base_url: None,
+            render_isolation: RenderIsolation::Shared,
             pool_size: num_cpus::get(),
             queue_capacity: 512,
+            scheduling: Scheduling::default(),
             pin_threads: false,
             cache_size: 300,
             cache_ttl: Some(Duration::from_secs(300)), // 5 minutes
+            cache_max_bytes: None,
+            cache_max_entry_bytes: None,
+            #[cfg(feature = "cache")]
+            cache_eviction: Eviction::Lru,
+            #[cfg(feature = "cache")]
+            cache_routes: Vec::new(),
+            #[cfg(feature = "cache-compression")]
+            cache_compression: None,
+            disk_cache_dir: None,
+            disk_cache_max_bytes: 1024 * 1024 * 1024, // 1 GiB
+            disk_cache_namespace: None,
+            #[cfg(feature = "cache")]
+            cache_backend: None,
+            #[cfg(feature = "cache")]
+            cache_hash_key: None,
+            #[cfg(feature = "cache")]
+            invalidation_bus: None,
+            url_normalizer: None,
             request_timeout: Some(Duration::from_secs(30)),
             render_function: "renderPage".to_string(),
+            render_entries: Vec::new(),
+            warmup_urls: Vec::new(),
+            prewarm_concurrency: (num_cpus::get() / 2).max(1),
         }
     }
 }
@@ -59,13 +337,42 @@ impl SsrConfig {
 #[derive(Debug, Default)]
 pub struct SsrConfigBuilder {
     bundle_path: Option<PathBuf>,
+    bundle_format: Option<BundleFormat>,
+    module_sandbox: ModuleSandbox,
+    #[cfg(feature = "v8-pool")]
+    polyfills: Option<Polyfills>,
+    preload_chunks: Option<bool>,
+    chunk_base_url: Option<String>,
+    render_isolation: Option<RenderIsolation>,
     pool_size: Option<usize>,
     queue_capacity: Option<usize>,
+    scheduling: Option<Scheduling>,
     pin_threads: Option<bool>,
     cache_size: Option<usize>,
     cache_ttl: Option<Option<Duration>>,
+    cache_max_bytes: Option<usize>,
+    cache_max_entry_bytes: Option<usize>,
+    #[cfg(feature = "cache")]
+    cache_eviction: Option<Eviction>,
+    #[cfg(feature = "cache")]
+    cache_routes: Vec<(String, RoutePolicy)>,
+    #[cfg(feature = "cache-compression")]
+    cache_compression: Option<CacheCompression>,
+    disk_cache_dir: Option<PathBuf>,
+    disk_cache_max_bytes: Option<u64>,
+    disk_cache_namespace: Option<String>,
+    #[cfg(feature = "cache")]
+    cache_backend: Option<Arc<dyn CacheBackend>>,
+    #[cfg(feature = "cache")]
+    cache_hash_key: Option<[u8; 16]>,
+    #[cfg(feature = "cache")]
+    invalidation_bus: Option<Arc<dyn InvalidationBus>>,
+    url_normalizer: Option<UrlNormalizer>,
     request_timeout: Option<Option<Duration>>,
     render_function: Option<String>,
+    render_entries: Vec<String>,
+    warmup_urls: Vec<String>,
+    prewarm_concurrency: Option<usize>,
 }
 
 impl SsrConfigBuilder {
@@ -84,6 +391,112 @@ impl SsrConfigBuilder {
         self
     }
 
+    /// Set the bundle format
+    ///
+    /// Default: [`BundleFormat::Script`]
+    ///
+    /// # Example
+    /// ```rust
+    /// use rusty_ssr::{BundleFormat, SsrConfig};
+    ///
+    /// let config = SsrConfig::builder()
+    ///     .bundle_path("dist/server/entry.mjs")
+    ///     .bundle_format(BundleFormat::Module)
+    ///     .render_function("default")
+    ///     .build();
+    /// ```
+    pub fn bundle_format(mut self, format: BundleFormat) -> Self {
+        self.bundle_format = Some(format);
+        self
+    }
+
+    /// Allow the bundle to import modules from an additional directory
+    ///
+    /// The bundle directory is always allowed.
+    pub fn allow_module_dir<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.module_sandbox.allowed_dirs.push(dir.into());
+        self
+    }
+
+    /// Register an in-memory module under an import specifier
+    ///
+    /// # Example
+    /// ```rust
+    /// use rusty_ssr::SsrConfig;
+    ///
+    /// let config = SsrConfig::builder()
+    ///     .module("virtual:config", "export default { locale: 'en' };")
+    ///     .module("node:buffer", "export const Buffer = globalThis.Buffer;")
+    ///     .build();
+    /// ```
+    pub fn module<K: Into<String>, S: Into<String>>(mut self, specifier: K, source: S) -> Self {
+        self.module_sandbox
+            .modules
+            .insert(specifier.into(), source.into());
+        self
+    }
+
+    /// Set the browser polyfills installed before the bundle runs
+    ///
+    /// Default: all built-in polyfill modules
+    ///
+    /// # Example
+    /// ```rust
+    /// use rusty_ssr::{v8_pool::Polyfills, SsrConfig};
+    ///
+    /// let config = SsrConfig::builder()
+    ///     .polyfills(
+    ///         Polyfills::default()
+    ///             .storage(false)
+    ///             .shim("globalThis.__APP_ENV__ = 'production';"),
+    ///     )
+    ///     .build();
+    /// ```
+    #[cfg(feature = "v8-pool")]
+    pub fn polyfills(mut self, polyfills: Polyfills) -> Self {
+        self.polyfills = Some(polyfills);
+        self
+    }
+
+    /// Pre-load code-split chunks into each V8 isolate at startup
+    ///
+    /// The chunks the bundle entry can `import()`, found by following its
+    /// imports, are loaded and evaluated once per isolate, so lazy `import()`
+    /// calls during renders are served from the isolate's module cache.
+    /// Other files in the bundle directory are left alone. Only applies to
+    /// module bundles.
+    pub fn preload_chunks(mut self, preload: bool) -> Self {
+        self.preload_chunks = Some(preload);
+        self
+    }
+
+    /// Inject `<link rel="modulepreload">` tags for the chunks a page imported
+    ///
+    /// `base_url` is the public URL the bundle directory is served from,
+    /// e.g. `"/assets/"`.
+    pub fn chunk_base_url<S: Into<String>>(mut self, base_url: S) -> Self {
+        self.chunk_base_url = Some(base_url.into());
+        self
+    }
+
+    /// Set how much JavaScript state survives between renders
+    ///
+    /// Default: [`RenderIsolation::Shared`]
+    ///
+    /// # Example
+    /// ```rust
+    /// use rusty_ssr::{RenderIsolation, SsrConfig};
+    ///
+    /// let config = SsrConfig::builder()
+    ///     .render_isolation(RenderIsolation::ResetGlobals)
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn render_isolation(mut self, isolation: RenderIsolation) -> Self {
+        self.render_isolation = Some(isolation);
+        self
+    }
+
     /// Set the number of V8 worker threads
     ///
     /// Default: number of CPU cores
@@ -100,6 +513,25 @@ impl SsrConfigBuilder {
         self
     }
 
+    /// Set how workers pick the next request between priorities
+    ///
+    /// Default: [`Scheduling::Weighted`] with weights 8 (interactive),
+    /// 2 (background) and 1 (bot)
+    ///
+    /// # Example
+    /// ```rust
+    /// use rusty_ssr::{Scheduling, SsrConfig};
+    ///
+    /// let config = SsrConfig::builder()
+    ///     .scheduling(Scheduling::Strict)
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn scheduling(mut self, scheduling: Scheduling) -> Self {
+        self.scheduling = Some(scheduling);
+        self
+    }
+
     /// Enable CPU core pinning for V8 workers
     ///
     /// This can improve cache locality but may reduce flexibility
@@ -136,6 +568,230 @@ impl SsrConfigBuilder {
         self
     }
 
+    /// Bound the SSR cache by the total size of cached pages in bytes
+    ///
+    /// Applies on top of `cache_size`. When exceeded, the least recently
+    /// used pages are evicted until the new one fits.
+    ///
+    /// Default: no byte budget
+    ///
+    /// # Example
+    /// ```rust
+    /// use rusty_ssr::SsrConfig;
+    ///
+    /// let config = SsrConfig::builder()
+    ///     .cache_size(10_000)
+    ///     .cache_max_bytes(512 * 1024 * 1024)    // 512 MiB
+    ///     .cache_max_entry_bytes(4 * 1024 * 1024) // skip pages over 4 MiB
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn cache_max_bytes(mut self, max_bytes: usize) -> Self {
+        self.cache_max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Do not cache pages larger than this many bytes
+    ///
+    /// Default: no limit
+    pub fn cache_max_entry_bytes(mut self, max_bytes: usize) -> Self {
+        self.cache_max_entry_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Select the SSR cache eviction policy
+    ///
+    /// [`Eviction::TinyLfu`] and [`Eviction::S3Fifo`] keep popular pages
+    /// cached through scans of long-tail URLs (e.g. crawlers) that would
+    /// flush an LRU cache.
+    ///
+    /// Default: [`Eviction::Lru`]
+    ///
+    /// # Example
+    /// ```rust
+    /// use rusty_ssr::SsrConfig;
+    /// use rusty_ssr::cache::Eviction;
+    ///
+    /// let config = SsrConfig::builder()
+    ///     .cache_size(10_000)
+    ///     .cache_eviction(Eviction::TinyLfu)
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    #[cfg(feature = "cache")]
+    pub fn cache_eviction(mut self, eviction: Eviction) -> Self {
+        self.cache_eviction = Some(eviction);
+        self
+    }
+
+    /// Cache the pages of a route with their own policy
+    ///
+    /// Patterns match the URL path: `:name` or `*` match one segment, `**`
+    /// any number of segments. Routes are tried in the order they are
+    /// added and the first match wins; other URLs use the global settings.
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::time::Duration;
+    /// use rusty_ssr::SsrConfig;
+    /// use rusty_ssr::cache::RoutePolicy;
+    ///
+    /// let config = SsrConfig::builder()
+    ///     .cache_route("/checkout/**", RoutePolicy::no_store())
+    ///     .cache_route("/products/:id", RoutePolicy::new().ttl(Duration::from_secs(60)))
+    ///     .cache_route("/", RoutePolicy::new().ttl(Duration::from_secs(10)))
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    #[cfg(feature = "cache")]
+    pub fn cache_route<S: Into<String>>(mut self, pattern: S, policy: RoutePolicy) -> Self {
+        self.cache_routes.push((pattern.into(), policy));
+        self
+    }
+
+    /// Normalize request URLs before the cache lookup and render
+    ///
+    /// URL variants that normalize to the same URL share one cache entry and
+    /// one render. Cache route patterns match the normalized URL.
+    ///
+    /// Default: URLs are used as requested
+    ///
+    /// # Example
+    /// ```rust
+    /// use rusty_ssr::SsrConfig;
+    /// use rusty_ssr::normalize::{TrailingSlash, UrlNormalizer};
+    ///
+    /// let config = SsrConfig::builder()
+    ///     .url_normalizer(
+    ///         UrlNormalizer::new()
+    ///             .trailing_slash(TrailingSlash::Strip)
+    ///             .lowercase_path()
+    ///             .deny_tracking_params()
+    ///             .sort_query(),
+    ///     )
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn url_normalizer(mut self, normalizer: UrlNormalizer) -> Self {
+        self.url_normalizer = Some(normalizer);
+        self
+    }
+
+    /// Store pre-compressed variants of cached pages
+    ///
+    /// Pages are compressed once when cached, and `SsrEngine::render_encoded`
+    /// (or the `ssr_handler` axum handler) serves the variant matching the
+    /// request's `Accept-Encoding`.
+    ///
+    /// Default: raw HTML only
+    ///
+    /// # Example
+    /// ```rust
+    /// use rusty_ssr::SsrConfig;
+    /// use rusty_ssr::cache::{CacheCompression, Encoding};
+    ///
+    /// let config = SsrConfig::builder()
+    ///     .cache_compression(CacheCompression::new([Encoding::Brotli, Encoding::Gzip]))
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    #[cfg(feature = "cache-compression")]
+    pub fn cache_compression(mut self, compression: CacheCompression) -> Self {
+        self.cache_compression = Some(compression);
+        self
+    }
+
+    /// Enable the persistent disk cache tier in the given directory
+    ///
+    /// Cold cache misses fall back to disk, so rendered pages survive
+    /// restarts. Entries respect the cache TTL. With the `cache-compression`
+    /// feature, they are stored zstd-compressed.
+    ///
+    /// Default: disabled
+    ///
+    /// # Example
+    /// ```rust
+    /// use rusty_ssr::SsrConfig;
+    ///
+    /// let config = SsrConfig::builder()
+    ///     .disk_cache("/var/cache/my-app/ssr")
+    ///     .disk_cache_max_bytes(256 * 1024 * 1024)
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn disk_cache<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.disk_cache_dir = Some(dir.into());
+        self
+    }
+
+    /// Set the maximum bytes stored by the disk cache tier
+    ///
+    /// Default: 1 GiB
+    pub fn disk_cache_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.disk_cache_max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Set the disk cache namespace, e.g. a release version
+    ///
+    /// Default: derived from the bundle contents
+    pub fn disk_cache_namespace<S: Into<String>>(mut self, namespace: S) -> Self {
+        self.disk_cache_namespace = Some(namespace.into());
+        self
+    }
+
+    /// Share cached pages between replicas through a cache backend
+    ///
+    /// Local misses are looked up in the backend before rendering, and
+    /// rendered pages are written to it. Backend failures never fail a
+    /// render.
+    ///
+    /// Default: none
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::sync::Arc;
+    /// use rusty_ssr::SsrConfig;
+    /// use rusty_ssr::cache::MemoryBackend;
+    ///
+    /// let config = SsrConfig::builder()
+    ///     .cache_backend(Arc::new(MemoryBackend::new()))
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    #[cfg(feature = "cache")]
+    pub fn cache_backend(mut self, backend: Arc<dyn CacheBackend>) -> Self {
+        self.cache_backend = Some(backend);
+        self
+    }
+
+    /// Set the secret key of the render data hashes in cache keys
+    ///
+    /// Pages of routes varying on render data, and render entry pages, are
+    /// cached under a keyed hash of the data. Replicas sharing a cache
+    /// backend need the same key to share those pages.
+    ///
+    /// Default: the key persisted with the disk cache, or a random key per
+    /// process
+    #[cfg(feature = "cache")]
+    pub fn cache_hash_key(mut self, key: [u8; 16]) -> Self {
+        self.cache_hash_key = Some(key);
+        self
+    }
+
+    /// Broadcast cache invalidations to other replicas through a bus
+    ///
+    /// `invalidate`, `invalidate_prefix` and `clear_cache` are published to
+    /// the bus, and invalidations received from it are applied locally.
+    /// The engine must then be built within a Tokio runtime.
+    ///
+    /// Default: none (invalidations are local)
+    #[cfg(feature = "cache")]
+    pub fn invalidation_bus(mut self, bus: Arc<dyn InvalidationBus>) -> Self {
+        self.invalidation_bus = Some(bus);
+        self
+    }
+
     /// Set request timeout
     ///
     /// Default: 30 seconds. Use `None` for no timeout.
@@ -144,16 +800,70 @@ impl SsrConfigBuilder {
         self
     }
 
-    /// Set the name of the global render function
+    /// Set the name of the render function
     ///
     /// Default: "renderPage"
     ///
-    /// Your JS bundle should expose: `globalThis.{render_function}(url, data)`
+    /// Script bundles should expose: `globalThis.{render_function}(url, data)`.
+    /// Module bundles should export it: `export function {render_function}(url, data)`.
     pub fn render_function<S: Into<String>>(mut self, name: S) -> Self {
         self.render_function = Some(name.into());
         self
     }
 
+    /// Allow an additional render function to be called via `SsrEngine::render_entry`
+    ///
+    /// # Example
+    /// ```rust
+    /// use rusty_ssr::SsrConfig;
+    ///
+    /// let config = SsrConfig::builder()
+    ///     .render_entry("renderEmail")
+    ///     .render_entry("renderSitemap")
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn render_entry<S: Into<String>>(mut self, name: S) -> Self {
+        self.render_entries.push(name.into());
+        self
+    }
+
+    /// Render a URL in every worker at startup
+    ///
+    /// Default: no warm-up
+    pub fn warmup_url<S: Into<String>>(mut self, url: S) -> Self {
+        self.warmup_urls.push(url.into());
+        self
+    }
+
+    /// Render a list of URLs in every worker at startup
+    ///
+    /// # Example
+    /// ```rust
+    /// use rusty_ssr::SsrConfig;
+    ///
+    /// let config = SsrConfig::builder()
+    ///     .warmup_urls(["/", "/products", "/about"])
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn warmup_urls<I, S>(mut self, urls: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.warmup_urls.extend(urls.into_iter().map(Into::into));
+        self
+    }
+
+    /// Set the maximum number of concurrent `SsrEngine::prewarm` renders
+    ///
+    /// Default: half the pool size, so live traffic keeps free workers
+    pub fn prewarm_concurrency(mut self, concurrency: usize) -> Self {
+        self.prewarm_concurrency = Some(concurrency);
+        self
+    }
+
     /// Build the configuration
     ///
     /// # Errors
@@ -161,19 +871,61 @@ impl SsrConfigBuilder {
     /// - `pool_size` must be > 0
     /// - `cache_size` must be > 0
     /// - `queue_capacity` must be > 0
-    /// - `render_function` must be a valid JS identifier (alphanumeric, `_`, `.`)
+    /// - scheduling weights must be > 0
+    /// - `cache_max_bytes` and `cache_max_entry_bytes` must be > 0, and the
+    ///   entry limit must not exceed the budget
+    /// - `disk_cache_max_bytes` must be > 0
+    /// - cache route patterns must start with `/`, and route TTLs and
+    ///   entry limits must be > 0
+    /// - `prewarm_concurrency` must be > 0
+    /// - `render_function` and render entries must be valid JS identifiers
+    ///   (alphanumeric, `_`, `.`)
     pub fn build(self) -> SsrResult<SsrConfig> {
         let default = SsrConfig::default();
+        let pool_size = self.pool_size.unwrap_or(default.pool_size);
 
         let config = SsrConfig {
             bundle_path: self.bundle_path.unwrap_or(default.bundle_path),
-            pool_size: self.pool_size.unwrap_or(default.pool_size),
+            bundle_format: self.bundle_format.unwrap_or(default.bundle_format),
+            module_sandbox: self.module_sandbox,
+            #[cfg(feature = "v8-pool")]
+            polyfills: self.polyfills.unwrap_or(default.polyfills),
+            preload_chunks: self.preload_chunks.unwrap_or(default.preload_chunks),
+            chunk_base_url: self.chunk_base_url.or(default.chunk_base_url),
+            render_isolation: self.render_isolation.unwrap_or(default.render_isolation),
+            pool_size,
             queue_capacity: self.queue_capacity.unwrap_or(default.queue_capacity),
+            scheduling: self.scheduling.unwrap_or(default.scheduling),
             pin_threads: self.pin_threads.unwrap_or(default.pin_threads),
             cache_size: self.cache_size.unwrap_or(default.cache_size),
             cache_ttl: self.cache_ttl.unwrap_or(default.cache_ttl),
+            cache_max_bytes: self.cache_max_bytes.or(default.cache_max_bytes),
+            cache_max_entry_bytes: self.cache_max_entry_bytes.or(default.cache_max_entry_bytes),
+            #[cfg(feature = "cache")]
+            cache_eviction: self.cache_eviction.unwrap_or(default.cache_eviction),
+            #[cfg(feature = "cache")]
+            cache_routes: self.cache_routes,
+            #[cfg(feature = "cache-compression")]
+            cache_compression: self.cache_compression.or(default.cache_compression),
+            disk_cache_dir: self.disk_cache_dir.or(default.disk_cache_dir),
+            disk_cache_max_bytes: self
+                .disk_cache_max_bytes
+                .unwrap_or(default.disk_cache_max_bytes),
+            disk_cache_namespace: self.disk_cache_namespace.or(default.disk_cache_namespace),
+            #[cfg(feature = "cache")]
+            cache_backend: self.cache_backend.or(default.cache_backend),
+            #[cfg(feature = "cache")]
+            cache_hash_key: self.cache_hash_key.or(default.cache_hash_key),
+            #[cfg(feature = "cache")]
+            invalidation_bus: self.invalidation_bus.or(default.invalidation_bus),
+            url_normalizer: self.url_normalizer.or(default.url_normalizer),
             request_timeout: self.request_timeout.unwrap_or(default.request_timeout),
             render_function: self.render_function.unwrap_or(default.render_function),
+            render_entries: self.render_entries,
+            warmup_urls: self.warmup_urls,
+            prewarm_concurrency: self
+                .prewarm_concurrency
+                .unwrap_or((pool_size / 2).max(1)),
         };
 
         if config.pool_size == 0 {
@@ -185,22 +937,78 @@ impl SsrConfigBuilder {
         if config.queue_capacity == 0 {
             return Err(SsrError::Config("queue_capacity must be > 0".into()));
         }
-        if config.render_function.is_empty()
-            || !config
-                .render_function
-                .chars()
-                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+        if let Scheduling::Weighted {
+            interactive,
+            background,
+            bot,
+        } = config.scheduling
         {
+            if interactive == 0 || background == 0 || bot == 0 {
+                return Err(SsrError::Config("scheduling weights must be > 0".into()));
+            }
+        }
+        if config.cache_max_bytes == Some(0) {
+            return Err(SsrError::Config("cache_max_bytes must be > 0".into()));
+        }
+        if config.cache_max_entry_bytes == Some(0) {
+            return Err(SsrError::Config("cache_max_entry_bytes must be > 0".into()));
+        }
+        if let (Some(max_bytes), Some(max_entry_bytes)) =
+            (config.cache_max_bytes, config.cache_max_entry_bytes)
+        {
+            if max_entry_bytes > max_bytes {
+                return Err(SsrError::Config(
+                    "cache_max_entry_bytes must not exceed cache_max_bytes".into(),
+                ));
+            }
+        }
+        #[cfg(feature = "cache-compression")]
+        if config
+            .cache_compression
+            .as_ref()
+            .is_some_and(|c| c.encodings.is_empty())
+        {
+            return Err(SsrError::Config(
+                "cache_compression needs at least one encoding".into(),
+            ));
+        }
+        if config.disk_cache_max_bytes == 0 {
+            return Err(SsrError::Config("disk_cache_max_bytes must be > 0".into()));
+        }
+        #[cfg(feature = "cache")]
+        CacheRoutes::new(config.cache_routes.iter().cloned()).map_err(SsrError::Config)?;
+        if config.prewarm_concurrency == 0 {
+            return Err(SsrError::Config("prewarm_concurrency must be > 0".into()));
+        }
+        if !is_js_identifier(&config.render_function) {
             return Err(SsrError::Config(format!(
                 "render_function must be a valid JS identifier, got: {:?}",
                 config.render_function
             )));
         }
+        if let Some(entry) = config
+            .render_entries
+            .iter()
+            .find(|entry| !is_js_identifier(entry))
+        {
+            return Err(SsrError::Config(format!(
+                "render entry must be a valid JS identifier, got: {:?}",
+                entry
+            )));
+        }
 
         Ok(config)
     }
 }
 
+/// Whether `name` is safe to interpolate as a (dotted) JS property path
+fn is_js_identifier(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -211,6 +1019,8 @@ mod tests {
         assert_eq!(config.pool_size, num_cpus::get());
         assert_eq!(config.cache_size, 300);
         assert!(!config.pin_threads);
+        assert_eq!(config.bundle_format, BundleFormat::Script);
+        assert_eq!(config.render_isolation, RenderIsolation::Shared);
     }
 
     #[test]
@@ -263,4 +1073,180 @@ mod tests {
             .unwrap();
         assert_eq!(config.render_function, "module.renderPage");
     }
+
+    #[test]
+    fn test_module_bundle_format() {
+        let config = SsrConfig::builder()
+            .bundle_format(BundleFormat::Module)
+            .render_function("default")
+            .build()
+            .unwrap();
+        assert_eq!(config.bundle_format, BundleFormat::Module);
+        assert_eq!(config.render_function, "default");
+    }
+
+    #[test]
+    fn test_module_sandbox_builder() {
+        let config = SsrConfig::builder()
+            .allow_module_dir("vendor")
+            .module("virtual:config", "export default {};")
+            .build()
+            .unwrap();
+        assert_eq!(
+            config.module_sandbox.allowed_dirs,
+            vec![PathBuf::from("vendor")]
+        );
+        assert!(config.module_sandbox.modules.contains_key("virtual:config"));
+    }
+
+    #[test]
+    fn test_render_isolation() {
+        let config = SsrConfig::builder()
+            .render_isolation(RenderIsolation::FreshRuntime)
+            .build()
+            .unwrap();
+        assert_eq!(config.render_isolation, RenderIsolation::FreshRuntime);
+    }
+
+    #[test]
+    fn test_scheduling() {
+        assert_eq!(SsrConfig::default().scheduling, Scheduling::default());
+
+        let config = SsrConfig::builder()
+            .scheduling(Scheduling::Strict)
+            .build()
+            .unwrap();
+        assert_eq!(config.scheduling, Scheduling::Strict);
+
+        let result = SsrConfig::builder()
+            .scheduling(Scheduling::Weighted {
+                interactive: 4,
+                background: 0,
+                bot: 1,
+            })
+            .build();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_render_entries() {
+        let config = SsrConfig::builder()
+            .render_entry("renderEmail")
+            .render_entry("renderWidget")
+            .build()
+            .unwrap();
+        assert_eq!(config.render_entries, vec!["renderEmail", "renderWidget"]);
+
+        let result = SsrConfig::builder().render_entry("x; evil()").build();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_warmup_urls() {
+        let config = SsrConfig::builder()
+            .warmup_url("/")
+            .warmup_urls(["/products", "/about"])
+            .build()
+            .unwrap();
+        assert_eq!(config.warmup_urls, vec!["/", "/products", "/about"]);
+    }
+
+    #[test]
+    fn test_prewarm_concurrency_defaults_to_half_pool() {
+        let config = SsrConfig::builder().pool_size(8).build().unwrap();
+        assert_eq!(config.prewarm_concurrency, 4);
+
+        let config = SsrConfig::builder().pool_size(1).build().unwrap();
+        assert_eq!(config.prewarm_concurrency, 1);
+
+        let result = SsrConfig::builder().prewarm_concurrency(0).build();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_disk_cache() {
+        let config = SsrConfig::builder().build().unwrap();
+        assert!(config.disk_cache_dir.is_none());
+
+        let config = SsrConfig::builder()
+            .disk_cache("/tmp/ssr-cache")
+            .disk_cache_max_bytes(1024)
+            .disk_cache_namespace("v2")
+            .build()
+            .unwrap();
+        assert_eq!(config.disk_cache_dir, Some(PathBuf::from("/tmp/ssr-cache")));
+        assert_eq!(config.disk_cache_max_bytes, 1024);
+        assert_eq!(config.disk_cache_namespace.as_deref(), Some("v2"));
+
+        let result = SsrConfig::builder().disk_cache_max_bytes(0).build();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_cache_byte_limits() {
+        let config = SsrConfig::builder()
+            .cache_max_bytes(1024 * 1024)
+            .cache_max_entry_bytes(64 * 1024)
+            .build()
+            .unwrap();
+        assert_eq!(config.cache_max_bytes, Some(1024 * 1024));
+        assert_eq!(config.cache_max_entry_bytes, Some(64 * 1024));
+
+        assert!(SsrConfig::builder().cache_max_bytes(0).build().is_err());
+        assert!(SsrConfig::builder()
+            .cache_max_bytes(1024)
+            .cache_max_entry_bytes(2048)
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "cache")]
+    fn test_cache_eviction() {
+        let config = SsrConfig::builder().build().unwrap();
+        assert!(matches!(config.cache_eviction, Eviction::Lru));
+
+        let config = SsrConfig::builder()
+            .cache_eviction(Eviction::S3Fifo)
+            .build()
+            .unwrap();
+        assert!(matches!(config.cache_eviction, Eviction::S3Fifo));
+    }
+
+    #[test]
+    #[cfg(feature = "cache")]
+    fn test_cache_routes() {
+        let config = SsrConfig::builder()
+            .cache_route("/checkout/**", RoutePolicy::no_store())
+            .cache_route(
+                "/products/:id",
+                RoutePolicy::new().ttl(Duration::from_secs(60)),
+            )
+            .build()
+            .unwrap();
+        assert_eq!(config.cache_routes.len(), 2);
+        assert_eq!(config.cache_routes[0].0, "/checkout/**");
+
+        assert!(SsrConfig::builder()
+            .cache_route("products/:id", RoutePolicy::new())
+            .build()
+            .is_err());
+        assert!(SsrConfig::builder()
+            .cache_route("/", RoutePolicy::new().ttl(Duration::ZERO))
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn test_url_normalizer() {
+        let config = SsrConfig::builder().build().unwrap();
+        assert!(config.url_normalizer.is_none());
+
+        let config = SsrConfig::builder()
+            .url_normalizer(UrlNormalizer::new().lowercase_path())
+            .build()
+            .unwrap();
+        let normalizer = config.url_normalizer.unwrap();
+        assert_eq!(normalizer.normalize("/About").url, "/about");
+    }
 }
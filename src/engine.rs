@@ -1,15 +1,49 @@
 //! Main SSR Engine
 
+#[cfg(feature = "cache")]
+use std::borrow::Cow;
 use std::sync::Arc;
+#[cfg(feature = "v8-pool")]
+use std::time::Instant;
+#[cfg(feature = "v8-pool")]
+use tracing::Instrument;
 
 use crate::config::{SsrConfig, SsrConfigBuilder};
 use crate::error::{SsrError, SsrResult};
+use crate::metrics::{Family, Histogram, Registry, TextEncoder, DEFAULT_BUCKETS};
+use crate::normalize::NormalizedUrl;
 
+#[cfg(feature = "v8-pool")]
+use crate::config::RenderOptions;
 #[cfg(feature = "v8-pool")]
 use crate::v8_pool::{PoolError, V8Pool};
 
 #[cfg(feature = "cache")]
-use crate::cache::SsrCache;
+use crate::cache::{CacheRoutes, DiskCache, Invalidation, InvalidationBus, SsrCache};
+#[cfg(feature = "cache")]
+use futures::StreamExt;
+#[cfg(feature = "cache")]
+use std::time::Duration;
+#[cfg(feature = "cache")]
+use tokio::task::JoinHandle;
+
+#[cfg(all(feature = "v8-pool", feature = "cache-compression"))]
+use crate::cache::EncodedHtml;
+#[cfg(all(feature = "v8-pool", feature = "cache"))]
+use crate::cache::RouteLookup;
+
+#[cfg(all(feature = "v8-pool", feature = "cache"))]
+use crate::config::Priority;
+#[cfg(all(feature = "v8-pool", feature = "cache"))]
+use crate::prewarm::{PrewarmHandle, PrewarmProgress};
+
+/// `route` label of renders matching no cache route
+#[cfg(feature = "v8-pool")]
+const OTHER_ROUTE: &str = "other";
+
+/// Disk cache namespaces unchanged for this long are deleted on startup
+#[cfg(feature = "cache")]
+const DISK_NAMESPACE_IDLE: Duration = Duration::from_secs(24 * 60 * 60);
 
 /// The main SSR engine that coordinates V8 pool and caching
 pub struct SsrEngine {
@@ -19,7 +53,18 @@ pub struct SsrEngine {
     v8_pool: V8Pool,
 
     #[cfg(feature = "cache")]
-    cache: SsrCache,
+    cache: Arc<SsrCache>,
+
+    /// Task applying invalidations received from the bus
+    #[cfg(feature = "cache")]
+    invalidation_task: Option<JoinHandle<()>>,
+
+    /// Engine and application metrics
+    metrics: Registry,
+
+    /// Latency of V8 renders by cache route
+    #[cfg_attr(not(feature = "v8-pool"), allow(dead_code))]
+    render_duration: Arc<Family<Histogram>>,
 }
 
 impl SsrEngine {
@@ -50,29 +95,132 @@ impl SsrEngine {
         #[cfg(feature = "v8-pool")]
         let v8_pool = {
             // Initialize the V8 bundle
-            crate::v8_pool::init_bundle(&config.bundle_path)?;
+            match config.bundle_format {
+                crate::config::BundleFormat::Script => {
+                    crate::v8_pool::init_bundle_with_polyfills(
+                        &config.bundle_path,
+                        &config.polyfills,
+                    )?;
+                }
+                crate::config::BundleFormat::Module => {
+                    crate::v8_pool::init_module_bundle_with_polyfills(
+                        &config.bundle_path,
+                        &config.polyfills,
+                    )?;
+                }
+            }
 
-            V8Pool::new(crate::v8_pool::V8PoolConfig {
+            V8Pool::try_new(crate::v8_pool::V8PoolConfig {
                 num_threads: config.pool_size,
                 queue_capacity: config.queue_capacity,
+                scheduling: config.scheduling,
                 pin_threads: config.pin_threads,
                 request_timeout: config.request_timeout,
-                render_function: config.render_function.clone(),
+                render_function: Self::qualify_entry(&config, &config.render_function),
+                render_entries: config
+                    .render_entries
+                    .iter()
+                    .map(|entry| Self::qualify_entry(&config, entry))
+                    .collect(),
+                module_sandbox: config.module_sandbox.clone(),
+                preload_chunks: config.preload_chunks,
+                chunk_base_url: config.chunk_base_url.clone(),
+                render_isolation: config.render_isolation,
+                warmup_urls: config.warmup_urls.clone(),
             })
+            .map_err(|e| match e {
+                PoolError::Init(msg) => SsrError::BundleLoad(msg),
+                other => Self::map_pool_error(other),
+            })?
         };
 
         #[cfg(feature = "cache")]
         let cache = {
             let ttl_secs = config.cache_ttl.map(|d| d.as_secs()).unwrap_or(0);
-            SsrCache::with_ttl(config.cache_size, ttl_secs)
+            let mut cache = SsrCache::with_ttl(config.cache_size, ttl_secs)
+                .with_eviction(config.cache_eviction.clone());
+            if !config.cache_routes.is_empty() {
+                let routes = CacheRoutes::new(config.cache_routes.iter().cloned())
+                    .map_err(SsrError::Config)?;
+                tracing::info!("🧭 {} cache routes", config.cache_routes.len());
+                cache = cache.with_routes(routes);
+            }
+            if let Some(max_bytes) = config.cache_max_bytes {
+                cache = cache.with_max_bytes(max_bytes);
+            }
+            if let Some(max_entry_bytes) = config.cache_max_entry_bytes {
+                cache = cache.with_max_entry_bytes(max_entry_bytes);
+            }
+            #[cfg(feature = "cache-compression")]
+            if let Some(compression) = &config.cache_compression {
+                cache = cache.with_compression(compression.clone());
+            }
+            if let Some(key) = config.cache_hash_key {
+                cache = cache.with_hash_key(key);
+            }
+            if let Some(backend) = &config.cache_backend {
+                tracing::info!("🌐 Shared cache backend enabled");
+                cache = cache.with_backend(Arc::clone(backend));
+            }
+            match &config.disk_cache_dir {
+                Some(dir) => {
+                    let namespace = match &config.disk_cache_namespace {
+                        Some(namespace) => namespace.clone(),
+                        None => Self::bundle_namespace(&config)?,
+                    };
+                    let disk = DiskCache::open(
+                        dir,
+                        &namespace,
+                        config.disk_cache_max_bytes,
+                        ttl_secs,
+                    )?;
+                    tracing::info!(
+                        "💾 Disk cache: {} entries in {}/{}",
+                        disk.len(),
+                        dir.display(),
+                        namespace
+                    );
+                    Self::prune_disk_namespaces(dir.clone(), namespace);
+                    cache.with_disk_cache(disk)
+                }
+                None => cache,
+            }
         };
 
+        // Pre-fill the cache with the warm-up renders
+        #[cfg(all(feature = "v8-pool", feature = "cache"))]
+        for (url, html) in v8_pool.take_warmup_pages() {
+            let url = Self::canonical_url(&config, &url);
+            cache.insert_routed(&cache.route_key(&url, "{}"), Arc::from(html));
+        }
+
+        #[cfg(feature = "cache")]
+        let cache = Arc::new(cache);
+
+        #[cfg(feature = "cache")]
+        let invalidation_task = match &config.invalidation_bus {
+            Some(bus) => Some(Self::subscribe_invalidations(bus.as_ref(), Arc::clone(&cache))?),
+            None => None,
+        };
+
+        let metrics = Registry::new();
+        let render_duration = metrics.histogram_family(
+            "ssr_render_duration_seconds",
+            "Latency of uncached renders by cache route, queue wait included",
+            &["route"],
+            DEFAULT_BUCKETS,
+        );
+
         Ok(Self {
             config,
             #[cfg(feature = "v8-pool")]
             v8_pool,
             #[cfg(feature = "cache")]
             cache,
+            #[cfg(feature = "cache")]
+            invalidation_task,
+            metrics,
+            render_duration,
         })
     }
 
@@ -97,34 +245,315 @@ impl SsrEngine {
 
     /// Render a URL to HTML with custom data
     ///
+    /// The URL is first normalized with the configured `url_normalizer`.
+    /// The cache route matching it (see [`SsrConfigBuilder::cache_route`])
+    /// decides whether and how long the page is cached, and under which key.
+    ///
     /// # Arguments
     /// * `url` - The URL path to render
     /// * `data` - JSON string with data to pass to the render function
     #[cfg(all(feature = "v8-pool", feature = "cache"))]
     pub async fn render_with_data(&self, url: &str, data: &str) -> SsrResult<Arc<str>> {
+        self.render_with_options(url, data, RenderOptions::default())
+            .await
+    }
+
+    /// Render a URL to HTML with custom data and per-request options
+    ///
+    /// Like [`SsrEngine::render_with_data`]; on a cache miss the render is
+    /// queued at `options.priority`. Under overload, queued renders of a
+    /// lower priority are shed first and fail with [`SsrError::PoolFull`].
+    /// A render no worker started by `options.deadline` fails with
+    /// [`SsrError::Timeout`].
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// # use rusty_ssr::{Priority, RenderOptions, SsrEngine};
+    /// # async fn example(engine: SsrEngine) {
+    /// let options = RenderOptions {
+    ///     priority: Priority::Bot,
+    ///     ..Default::default()
+    /// };
+    /// let html = engine.render_with_options("/products", "{}", options).await.unwrap();
+    /// # }
+    /// ```
+    #[cfg(all(feature = "v8-pool", feature = "cache"))]
+    pub async fn render_with_options(
+        &self,
+        url: &str,
+        data: &str,
+        options: RenderOptions,
+    ) -> SsrResult<Arc<str>> {
+        let url = Self::canonical_url(&self.config, url);
+        self.render_cached(&url, data, None, options)
+            .instrument(Self::render_span(&url))
+            .await
+    }
+
+    /// Body of [`SsrEngine::render_with_options`] and
+    /// [`SsrEngine::render_entry`], run in their `ssr.render` span
+    ///
+    /// Renders with `entry` if given, else with the page render function.
+    #[cfg(all(feature = "v8-pool", feature = "cache"))]
+    async fn render_cached(
+        &self,
+        url: &str,
+        data: &str,
+        entry: Option<&str>,
+        options: RenderOptions,
+    ) -> SsrResult<Arc<str>> {
+        let key = match entry {
+            Some(entry) => self.cache.entry_key(entry, url, data),
+            None => self.cache.route_key(url, data),
+        };
+
         // Check cache first
-        if let Some(cached) = self.cache.try_get(url) {
-            tracing::debug!("Cache hit: {}", url);
-            return Ok(cached);
-        }
+        let stale = match self.cache.lookup(&key).await {
+            RouteLookup::Hit(cached) => {
+                Self::record_cache_outcome("hit");
+                tracing::debug!("Cache hit: {}", key.key());
+                return Ok(cached);
+            }
+            RouteLookup::Stale(cached, guard) => {
+                Self::record_cache_outcome("stale");
+                Some((cached, guard))
+            }
+            RouteLookup::Miss => {
+                Self::record_cache_outcome("miss");
+                None
+            }
+            RouteLookup::Bypass => {
+                Self::record_cache_outcome("bypass");
+                tracing::debug!("Not cached (no-store route): {}", url);
+                return self
+                    .render_v8(url, data, entry, options)
+                    .await
+                    .map(Arc::from)
+                    .map_err(Self::map_pool_error);
+            }
+        };
 
-        // Cache miss - render via V8
-        tracing::debug!("Cache miss, rendering: {}", url);
+        // Cache miss or stale page - render via V8
+        tracing::debug!("Cache miss, rendering: {}", key.key());
 
-        let html = self
-            .v8_pool
-            .render_with_data(url.to_string(), data.to_string())
-            .await
-            .map_err(Self::map_pool_error)?;
+        let html = match self.render_v8(url, data, entry, options).await {
+            Ok(html) => html,
+            Err(e) => {
+                return match stale {
+                    Some((cached, _guard)) => {
+                        tracing::warn!("⚠️ Re-render of {} failed, serving stale page: {}", url, e);
+                        Ok(cached)
+                    }
+                    None => Err(Self::map_pool_error(e)),
+                };
+            }
+        };
 
         let html: Arc<str> = Arc::from(html.as_str());
 
         // Store in cache
-        self.cache.insert(url, Arc::clone(&html));
+        tracing::info_span!("ssr.cache_insert")
+            .in_scope(|| self.cache.insert_routed(&key, Arc::clone(&html)));
 
         Ok(html)
     }
 
+    /// Render a URL in the best cached encoding for `accept_encoding`
+    ///
+    /// With `cache_compression` configured, cached pages are served as a
+    /// pre-compressed variant the client accepts, without recompressing.
+    /// Otherwise, or if the client accepts none, returns the raw HTML.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// # use rusty_ssr::SsrEngine;
+    /// # use rusty_ssr::cache::EncodedHtml;
+    /// # async fn example(engine: SsrEngine) {
+    /// match engine.render_encoded("/home", "gzip, br").await.unwrap() {
+    ///     EncodedHtml::Encoded(encoding, body) => println!("{}: {} bytes", encoding.as_str(), body.len()),
+    ///     EncodedHtml::Raw(html) => println!("raw: {} bytes", html.len()),
+    /// }
+    /// # }
+    /// ```
+    #[cfg(all(feature = "v8-pool", feature = "cache-compression"))]
+    pub async fn render_encoded(&self, url: &str, accept_encoding: &str) -> SsrResult<EncodedHtml> {
+        self.render_encoded_with_options(url, accept_encoding, RenderOptions::default())
+            .await
+    }
+
+    /// Like [`SsrEngine::render_encoded`], rendering cache misses at the
+    /// priority and deadline of `options`
+    #[cfg(all(feature = "v8-pool", feature = "cache-compression"))]
+    pub async fn render_encoded_with_options(
+        &self,
+        url: &str,
+        accept_encoding: &str,
+        options: RenderOptions,
+    ) -> SsrResult<EncodedHtml> {
+        let url = Self::canonical_url(&self.config, url);
+        let url = url.as_ref();
+        let key = self.cache.route_key(url, "{}");
+
+        // Routed pages go through `render` for their TTL and stale window
+        if !key.is_routed() {
+            if let Some(page) = self.cache.try_get_encoded(url, accept_encoding) {
+                return Ok(page);
+            }
+        }
+
+        // Rendering caches the page, compressed variants included
+        let html = self.render_with_options(url, "{}", options).await?;
+        Ok(match self.cache.peek_encoded(key.key(), accept_encoding) {
+            Some((encoding, data)) => EncodedHtml::Encoded(encoding, data),
+            None => EncodedHtml::Raw(html),
+        })
+    }
+
+    /// Render a URL with a named render entry
+    ///
+    /// `entry` must be the configured `render_function` or one of the
+    /// `render_entries`. Other entries are cached separately from the pages,
+    /// per URL and render data, under the cache route of the URL.
+    /// [`SsrEngine::invalidate`] of the URL removes them too.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// # use rusty_ssr::SsrEngine;
+    /// # async fn example(engine: SsrEngine) {
+    /// let email = engine
+    ///     .render_entry("renderEmail", "/welcome", r#"{"name":"Ann"}"#)
+    ///     .await
+    ///     .unwrap();
+    /// # }
+    /// ```
+    #[cfg(all(feature = "v8-pool", feature = "cache"))]
+    pub async fn render_entry(&self, entry: &str, url: &str, data: &str) -> SsrResult<Arc<str>> {
+        self.render_entry_with_options(entry, url, data, RenderOptions::default())
+            .await
+    }
+
+    /// Like [`SsrEngine::render_entry`], rendering cache misses at the
+    /// priority and deadline of `options`
+    #[cfg(all(feature = "v8-pool", feature = "cache"))]
+    pub async fn render_entry_with_options(
+        &self,
+        entry: &str,
+        url: &str,
+        data: &str,
+        options: RenderOptions,
+    ) -> SsrResult<Arc<str>> {
+        if entry == self.config.render_function {
+            return self.render_with_options(url, data, options).await;
+        }
+        if !self.config.render_entries.iter().any(|e| e == entry) {
+            return Err(SsrError::UnknownRenderEntry(entry.to_string()));
+        }
+
+        let url = Self::canonical_url(&self.config, url);
+        let span = Self::render_span(&url);
+        span.record("entry", entry);
+
+        self.render_cached(&url, data, Some(entry), options)
+            .instrument(span)
+            .await
+    }
+
+    /// Render URLs into the cache in the background
+    ///
+    /// At most `prewarm_concurrency` renders run at once, queued at
+    /// [`Priority::Background`] so live traffic goes first. URLs already
+    /// cached or on no-store routes are skipped, and pages whose render
+    /// throws are reported as failures instead of being cached as error
+    /// pages.
+    ///
+    /// Must be called from within a Tokio runtime.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// # use std::sync::Arc;
+    /// # use rusty_ssr::SsrEngine;
+    /// # async fn example(engine: Arc<SsrEngine>) -> rusty_ssr::SsrResult<()> {
+    /// let urls = rusty_ssr::prewarm::load_sitemap("public/sitemap.xml")?;
+    /// let handle = engine.prewarm(urls);
+    ///
+    /// let report = handle.wait().await;
+    /// println!("{} rendered, {} failed", report.rendered, report.failures.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(all(feature = "v8-pool", feature = "cache"))]
+    pub fn prewarm<I, S>(self: &Arc<Self>, urls: I) -> PrewarmHandle
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        use futures::StreamExt;
+
+        let urls: Vec<String> = urls.into_iter().map(Into::into).collect();
+        let progress = Arc::new(PrewarmProgress::new(urls.len()));
+        let concurrency = self.config.prewarm_concurrency;
+
+        tracing::info!(
+            "🔥 Pre-warming {} URLs (concurrency={})",
+            urls.len(),
+            concurrency
+        );
+
+        let engine = Arc::clone(self);
+        let task_progress = Arc::clone(&progress);
+        let task = tokio::spawn(async move {
+            futures::stream::iter(urls)
+                .for_each_concurrent(concurrency, |url| engine.prewarm_url(url, &task_progress))
+                .await;
+
+            tracing::info!(
+                "🔥 Pre-warm finished: {} rendered, {} skipped, {} failed",
+                task_progress.rendered(),
+                task_progress.skipped(),
+                task_progress.failed()
+            );
+        });
+
+        PrewarmHandle::new(progress, task)
+    }
+
+    /// Render one pre-warm URL into the cache
+    #[cfg(all(feature = "v8-pool", feature = "cache"))]
+    async fn prewarm_url(&self, url: String, progress: &PrewarmProgress) {
+        let url = Self::canonical_url(&self.config, &url).into_owned();
+        let key = self.cache.route_key(&url, "{}");
+        if !key.is_cacheable()
+            || self.cache.contains(key.key())
+            || self.cache.fetch_remote_routed(&key).await.is_some()
+        {
+            progress.record_skipped();
+            return;
+        }
+
+        let options = RenderOptions {
+            priority: Priority::Background,
+            ..Default::default()
+        };
+        let started = Instant::now();
+        let result = self
+            .v8_pool
+            .try_render_page_with_options(url.clone(), "{}".to_string(), options)
+            .await;
+        self.observe_render(&url, started);
+
+        match result {
+            Ok(page) => {
+                self.cache
+                    .insert_routed(&key, Arc::from(page.html.as_str()));
+                progress.record_rendered();
+            }
+            Err(e) => {
+                tracing::warn!("⚠️ Pre-warm of {} failed: {}", url, e);
+                progress.record_failure(url, e.to_string());
+            }
+        }
+    }
+
     /// Render a URL with JSON data (serde_json::Value)
     ///
     /// Convenience method that serializes the Value to a string.
@@ -155,12 +584,65 @@ impl SsrEngine {
     /// Render without caching (always hits V8)
     #[cfg(feature = "v8-pool")]
     pub async fn render_uncached(&self, url: &str, data: &str) -> SsrResult<String> {
-        self.v8_pool
-            .render_with_data(url.to_string(), data.to_string())
+        self.render_uncached_with_options(url, data, RenderOptions::default())
+            .await
+    }
+
+    /// Render without caching, at the priority and deadline of `options`
+    #[cfg(feature = "v8-pool")]
+    pub async fn render_uncached_with_options(
+        &self,
+        url: &str,
+        data: &str,
+        options: RenderOptions,
+    ) -> SsrResult<String> {
+        let span = Self::render_span(url);
+        span.record("cache", "bypass");
+
+        self.render_v8(url, data, None, options)
+            .instrument(span)
             .await
             .map_err(Self::map_pool_error)
     }
 
+    /// Render without caching, returning the code-split chunks the page imported
+    ///
+    /// Use the chunk list to emit `<link rel="modulepreload">` tags or
+    /// `Link` headers so the client fetches them early.
+    #[cfg(feature = "v8-pool")]
+    pub async fn render_page(
+        &self,
+        url: &str,
+        data: &str,
+    ) -> SsrResult<crate::v8_pool::RenderedPage> {
+        self.render_page_with_options(url, data, RenderOptions::default())
+            .await
+    }
+
+    /// Like [`SsrEngine::render_page`], at the priority and deadline of `options`
+    #[cfg(feature = "v8-pool")]
+    pub async fn render_page_with_options(
+        &self,
+        url: &str,
+        data: &str,
+        options: RenderOptions,
+    ) -> SsrResult<crate::v8_pool::RenderedPage> {
+        let span = Self::render_span(url);
+        span.record("cache", "bypass");
+
+        async {
+            let started = Instant::now();
+            let result = self
+                .v8_pool
+                .render_page_with_options(url.to_string(), data.to_string(), options)
+                .await;
+            self.observe_render(url, started);
+            result.map_err(Self::map_pool_error)
+        }
+        .instrument(span)
+        .await
+    }
+
     /// Render without caching with JSON data
     #[cfg(feature = "v8-pool")]
     pub async fn render_uncached_json(
@@ -173,21 +655,27 @@ impl SsrEngine {
 
     /// Invalidate a single cached URL
     ///
-    /// Use after content updates for a specific page.
+    /// Use after content updates for a specific page. With an invalidation
+    /// bus, other replicas are invalidated too.
     #[cfg(feature = "cache")]
     pub fn invalidate(&self, url: &str) {
+        let url = Self::canonical_url(&self.config, url);
+        let url = url.as_ref();
         self.cache.invalidate(url);
         tracing::debug!("Cache invalidated: {}", url);
+        self.publish_invalidation(Invalidation::Url(url.to_string()));
     }
 
     /// Invalidate all cached URLs matching a prefix
     ///
     /// Example: `engine.invalidate_prefix("/products")` clears all product pages.
+    /// The prefix is matched against normalized URLs as is.
     /// Returns the number of removed entries.
     #[cfg(feature = "cache")]
     pub fn invalidate_prefix(&self, prefix: &str) -> usize {
         let removed = self.cache.invalidate_prefix(prefix);
         tracing::info!("Cache invalidated {} entries with prefix: {}", removed, prefix);
+        self.publish_invalidation(Invalidation::Prefix(prefix.to_string()));
         removed
     }
 
@@ -196,6 +684,7 @@ impl SsrEngine {
     pub fn clear_cache(&self) {
         self.cache.clear();
         tracing::info!("SSR cache cleared");
+        self.publish_invalidation(Invalidation::All);
     }
 
     /// Get cache metrics
@@ -210,6 +699,71 @@ impl SsrEngine {
         self.v8_pool.worker_count()
     }
 
+    /// Get the V8 pool statistics (see [`V8Pool::stats`])
+    #[cfg(feature = "v8-pool")]
+    pub fn pool_stats(&self) -> crate::v8_pool::PoolStats {
+        self.v8_pool.stats()
+    }
+
+    /// Registry of the engine metrics
+    ///
+    /// Metrics registered here are included in [`SsrEngine::render_metrics`].
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// # use rusty_ssr::SsrEngine;
+    /// # fn example(engine: SsrEngine) {
+    /// let signups = engine
+    ///     .metrics_registry()
+    ///     .counter("app_signups_total", "Completed signups");
+    /// signups.inc();
+    /// # }
+    /// ```
+    pub fn metrics_registry(&self) -> &Registry {
+        &self.metrics
+    }
+
+    /// Render all metrics in the Prometheus text format
+    ///
+    /// Includes render latency per cache route, the V8 pool metrics (see
+    /// [`V8Pool::encode_metrics`]), cache hits and misses per tier,
+    /// evictions, cached bytes and the metrics registered in
+    /// [`SsrEngine::metrics_registry`]. Serve it with
+    /// [`metrics_handler`](crate::middleware::metrics_handler) or on your own.
+    pub fn render_metrics(&self) -> String {
+        let mut encoder = TextEncoder::new();
+        #[cfg(feature = "v8-pool")]
+        self.v8_pool.encode_metrics(&mut encoder);
+        #[cfg(feature = "cache")]
+        Self::encode_cache_metrics(&self.cache.metrics(), &mut encoder);
+        self.metrics.encode(&mut encoder);
+        encoder.finish()
+    }
+
+    /// Normalize a request URL with the configured `url_normalizer`
+    ///
+    /// Handlers use this to redirect clients to the canonical URL; rendering
+    /// normalizes on its own.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// # use rusty_ssr::SsrEngine;
+    /// # fn example(engine: SsrEngine) {
+    /// if let Some(location) = engine.normalize_url("/About/").redirect {
+    ///     println!("301 → {}", location);
+    /// }
+    /// # }
+    /// ```
+    pub fn normalize_url(&self, url: &str) -> NormalizedUrl {
+        match &self.config.url_normalizer {
+            Some(normalizer) => normalizer.normalize(url),
+            None => NormalizedUrl {
+                url: url.to_string(),
+                redirect: None,
+            },
+        }
+    }
+
     /// Get a reference to the configuration
     pub fn config(&self) -> &SsrConfig {
         &self.config
@@ -228,6 +782,15 @@ impl SsrEngine {
     }
 }
 
+#[cfg(feature = "cache")]
+impl Drop for SsrEngine {
+    fn drop(&mut self) {
+        if let Some(task) = &self.invalidation_task {
+            task.abort();
+        }
+    }
+}
+
 /// Builder extension to create SsrEngine directly
 impl SsrConfigBuilder {
     /// Build the configuration and create an SsrEngine
@@ -246,6 +809,257 @@ impl SsrEngine {
                 SsrError::JsExecution("V8 worker crashed".to_string())
             }
             PoolError::Render(msg) => SsrError::JsExecution(msg),
+            PoolError::Init(msg) => SsrError::V8Init(msg),
+            PoolError::UnknownEntry(name) => SsrError::UnknownRenderEntry(name),
+            PoolError::Shed => SsrError::PoolFull,
+        }
+    }
+
+    /// Render via V8 without caching, recording the render latency
+    ///
+    /// Renders with the render entry `entry` if given, else with the page
+    /// render function.
+    #[cfg(feature = "v8-pool")]
+    async fn render_v8(
+        &self,
+        url: &str,
+        data: &str,
+        entry: Option<&str>,
+        options: RenderOptions,
+    ) -> Result<String, PoolError> {
+        let started = Instant::now();
+        let result = match entry {
+            Some(entry) => {
+                self.v8_pool
+                    .render_entry_with_options(
+                        &Self::qualify_entry(&self.config, entry),
+                        url.to_string(),
+                        data.to_string(),
+                        options,
+                    )
+                    .await
+            }
+            None => {
+                self.v8_pool
+                    .render_page_with_options(url.to_string(), data.to_string(), options)
+                    .await
+            }
+        };
+        self.observe_render(url, started);
+        result.map(|page| page.html)
+    }
+
+    /// `ssr.render` span of a request
+    ///
+    /// Its `cache` field (`hit`, `stale`, `miss` or `bypass`) is recorded
+    /// once the cache lookup is done.
+    #[cfg(feature = "v8-pool")]
+    fn render_span(url: &str) -> tracing::Span {
+        tracing::info_span!(
+            "ssr.render",
+            url = %url,
+            cache = tracing::field::Empty,
+            entry = tracing::field::Empty
+        )
+    }
+
+    /// Record the cache outcome on the current `ssr.render` span
+    #[cfg(all(feature = "v8-pool", feature = "cache"))]
+    fn record_cache_outcome(outcome: &str) {
+        tracing::Span::current().record("cache", outcome);
+    }
+
+    /// Record the latency of a V8 render of `url` under its cache route
+    #[cfg(feature = "v8-pool")]
+    fn observe_render(&self, url: &str, started: Instant) {
+        #[cfg(feature = "cache")]
+        let route = self.cache.routes().pattern(url).unwrap_or(OTHER_ROUTE);
+        #[cfg(not(feature = "cache"))]
+        let route = {
+            let _ = url;
+            OTHER_ROUTE
+        };
+
+        self.render_duration
+            .with_labels(&[route])
+            .observe_duration(started.elapsed());
+    }
+
+    /// Write cache metrics in the Prometheus text format
+    #[cfg(feature = "cache")]
+    fn encode_cache_metrics(metrics: &crate::cache::CacheMetrics, encoder: &mut TextEncoder) {
+        use crate::metrics::MetricKind::{Counter, Gauge};
+
+        encoder.header("ssr_cache_lookups_total", "Cache lookups", Counter);
+        encoder.sample("ssr_cache_lookups_total", &[], metrics.lookups as f64);
+
+        encoder.header("ssr_cache_hits_total", "Cache hits by tier", Counter);
+        for (tier, hits) in [
+            ("hot", metrics.hot_hits),
+            ("cold", metrics.cold_hits),
+            ("disk", metrics.disk_hits),
+            ("remote", metrics.remote_hits),
+        ] {
+            encoder.sample("ssr_cache_hits_total", &[("tier", tier)], hits as f64);
+        }
+
+        encoder.header(
+            "ssr_cache_misses_total",
+            "Cache misses of the local tiers and the shared backend",
+            Counter,
+        );
+        for (tier, misses) in [("local", metrics.misses), ("remote", metrics.remote_misses)] {
+            encoder.sample("ssr_cache_misses_total", &[("tier", tier)], misses as f64);
+        }
+
+        encoder.header(
+            "ssr_cache_evictions_total",
+            "Cache evictions by tier",
+            Counter,
+        );
+        for (tier, evictions) in [
+            ("cold", metrics.evictions),
+            ("disk", metrics.disk_evictions),
+        ] {
+            encoder.sample(
+                "ssr_cache_evictions_total",
+                &[("tier", tier)],
+                evictions as f64,
+            );
+        }
+
+        encoder.header("ssr_cache_entries", "Cached pages by tier", Gauge);
+        for (tier, entries) in [("cold", metrics.cold_size), ("disk", metrics.disk_size)] {
+            encoder.sample("ssr_cache_entries", &[("tier", tier)], entries as f64);
+        }
+
+        encoder.header("ssr_cache_bytes", "Bytes cached by tier", Gauge);
+        for (tier, bytes) in [
+            ("cold", metrics.cold_bytes as u64),
+            ("disk", metrics.disk_bytes),
+        ] {
+            encoder.sample("ssr_cache_bytes", &[("tier", tier)], bytes as f64);
+        }
+
+        encoder.header(
+            "ssr_cache_oversized_total",
+            "Pages not cached because they exceeded the maximum entry size",
+            Counter,
+        );
+        encoder.sample("ssr_cache_oversized_total", &[], metrics.oversized as f64);
+
+        encoder.header(
+            "ssr_cache_remote_errors_total",
+            "Failed shared backend operations",
+            Counter,
+        );
+        encoder.sample(
+            "ssr_cache_remote_errors_total",
+            &[],
+            metrics.remote_errors as f64,
+        );
+
+        encoder.header(
+            "ssr_cache_route_lookups_total",
+            "Lookups of each cache route by outcome",
+            Counter,
+        );
+        for route in &metrics.routes {
+            for (outcome, count) in [
+                ("hit", route.hits),
+                ("stale", route.stale_hits),
+                ("miss", route.misses),
+                ("bypass", route.bypasses),
+            ] {
+                encoder.sample(
+                    "ssr_cache_route_lookups_total",
+                    &[("route", &route.pattern), ("outcome", outcome)],
+                    count as f64,
+                );
+            }
+        }
+    }
+
+    /// Send an invalidation to other replicas in the background
+    #[cfg(feature = "cache")]
+    fn publish_invalidation(&self, invalidation: Invalidation) {
+        let Some(bus) = &self.config.invalidation_bus else {
+            return;
+        };
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            tracing::warn!("⚠️ No Tokio runtime, not publishing {:?}", invalidation);
+            return;
+        };
+
+        let bus = Arc::clone(bus);
+        runtime.spawn(async move {
+            if let Err(e) = bus.publish(&invalidation).await {
+                tracing::warn!("⚠️ Failed to publish {:?}: {}", invalidation, e);
+            }
+        });
+    }
+
+    /// Apply invalidations received from the bus to the local cache
+    #[cfg(feature = "cache")]
+    fn subscribe_invalidations(
+        bus: &dyn InvalidationBus,
+        cache: Arc<SsrCache>,
+    ) -> SsrResult<JoinHandle<()>> {
+        let runtime = tokio::runtime::Handle::try_current().map_err(|_| {
+            SsrError::Config("invalidation_bus requires building the engine within a Tokio runtime".into())
+        })?;
+
+        let mut invalidations = bus.subscribe();
+        Ok(runtime.spawn(async move {
+            while let Some(invalidation) = invalidations.next().await {
+                tracing::debug!("📡 Received invalidation: {:?}", invalidation);
+                match invalidation {
+                    Invalidation::Url(url) => cache.invalidate(&url),
+                    Invalidation::Prefix(prefix) => {
+                        cache.invalidate_prefix(&prefix);
+                    }
+                    Invalidation::All => cache.clear(),
+                }
+            }
+        }))
+    }
+
+    /// Disk cache namespace derived from the bundle contents
+    #[cfg(feature = "cache")]
+    fn bundle_namespace(config: &SsrConfig) -> SsrResult<String> {
+        let bundle = std::fs::read(&config.bundle_path)?;
+        Ok(format!("bundle-{:016x}", crate::cache::stable_hash(&bundle)))
+    }
+
+    /// Delete the disk cache namespaces of old bundles, in the background
+    #[cfg(feature = "cache")]
+    fn prune_disk_namespaces(dir: std::path::PathBuf, keep: String) {
+        std::thread::spawn(move || {
+            match DiskCache::prune_namespaces(&dir, &keep, DISK_NAMESPACE_IDLE) {
+                Ok(0) => {}
+                Ok(pruned) => tracing::info!("🧹 Deleted {} old disk cache namespaces", pruned),
+                Err(e) => tracing::warn!("⚠️ Failed to prune disk cache namespaces: {}", e),
+            }
+        });
+    }
+
+    /// URL used for caching and rendering
+    #[cfg(feature = "cache")]
+    fn canonical_url<'a>(config: &SsrConfig, url: &'a str) -> Cow<'a, str> {
+        match &config.url_normalizer {
+            Some(normalizer) => Cow::Owned(normalizer.normalize(url).url),
+            None => Cow::Borrowed(url),
+        }
+    }
+
+    /// JS property path of a render function for the configured bundle format
+    #[cfg(feature = "v8-pool")]
+    fn qualify_entry(config: &SsrConfig, name: &str) -> String {
+        match config.bundle_format {
+            crate::config::BundleFormat::Script => name.to_string(),
+            crate::config::BundleFormat::Module => {
+                format!("{}.{}", crate::v8_pool::MODULE_EXPORTS_GLOBAL, name)
+            }
         }
     }
 }
@@ -29,6 +29,21 @@ pub enum SsrError {
     /// Configuration error
     Config(String),
 
+    /// Module import outside the sandboxed directories
+    ModuleOutsideSandbox(String),
+
+    /// Remote (`http:`/`https:`) module import rejected by the sandbox
+    RemoteModuleDenied(String),
+
+    /// Node.js builtin (`node:`) module import rejected by the sandbox
+    BuiltinModuleDenied(String),
+
+    /// Module could not be resolved or read
+    ModuleNotFound(String),
+
+    /// Render entry is not the render function or a configured render entry
+    UnknownRenderEntry(String),
+
     /// IO error
     Io(std::io::Error),
 }
@@ -43,6 +58,17 @@ impl fmt::Display for SsrError {
             SsrError::Cache(msg) => write!(f, "Cache error: {}", msg),
             SsrError::PoolFull => write!(f, "V8 pool is full, request rejected"),
             SsrError::Config(msg) => write!(f, "Configuration error: {}", msg),
+            SsrError::ModuleOutsideSandbox(spec) => {
+                write!(f, "Module {} is outside the module sandbox", spec)
+            }
+            SsrError::RemoteModuleDenied(spec) => {
+                write!(f, "Remote module {} is not allowed in SSR", spec)
+            }
+            SsrError::BuiltinModuleDenied(spec) => {
+                write!(f, "Node.js builtin {} is not available in SSR", spec)
+            }
+            SsrError::ModuleNotFound(msg) => write!(f, "Module not found: {}", msg),
+            SsrError::UnknownRenderEntry(name) => write!(f, "Unknown render entry: {}", name),
             SsrError::Io(err) => write!(f, "IO error: {}", err),
         }
     }
@@ -49,7 +49,10 @@
 #![warn(rustdoc::missing_crate_level_docs)]
 
 // Re-export commonly used types
-pub use config::{SsrConfig, SsrConfigBuilder};
+pub use config::{
+    BundleFormat, ModuleSandbox, Priority, RenderIsolation, RenderOptions, Scheduling, SsrConfig,
+    SsrConfigBuilder,
+};
 pub use engine::SsrEngine;
 pub use error::{SsrError, SsrResult};
 
@@ -70,6 +73,15 @@ pub mod v8_pool;
 #[cfg(feature = "cache")]
 pub mod cache;
 
+/// Background cache pre-warming and sitemap loading
+pub mod prewarm;
+
+/// URL normalization before cache lookup
+pub mod normalize;
+
+/// Prometheus metrics
+pub mod metrics;
+
 /// Axum middleware (brotli, etc.)
 #[cfg(feature = "axum-integration")]
 pub mod middleware;
@@ -82,13 +94,16 @@ pub mod prelude {
     //! use rusty_ssr::prelude::*;
     //! ```
 
-    pub use crate::config::{SsrConfig, SsrConfigBuilder};
+    pub use crate::config::{
+        BundleFormat, Priority, RenderIsolation, RenderOptions, SsrConfig, SsrConfigBuilder,
+    };
     pub use crate::engine::SsrEngine;
     pub use crate::error::{SsrError, SsrResult};
+    pub use crate::normalize::{TrailingSlash, UrlNormalizer};
 
     #[cfg(feature = "cache")]
     pub use crate::cache::{SsrCache, CacheMetrics};
 
     #[cfg(feature = "v8-pool")]
-    pub use crate::v8_pool::{V8Pool, V8PoolConfig};
+    pub use crate::v8_pool::{Polyfills, V8Pool, V8PoolConfig};
 }
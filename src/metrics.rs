@@ -0,0 +1,510 @@
+//! Prometheus metrics
+//!
+//! Counters, gauges and histograms, optionally split by labels, rendered in
+//! the Prometheus text exposition format. The engine registers its own
+//! metrics (see [`SsrEngine::render_metrics`](crate::SsrEngine::render_metrics))
+//! and applications can add theirs to the same [`Registry`](crate::metrics::Registry).
+//!
+//! # Example
+//! ```rust
+//! use rusty_ssr::metrics::{Registry, DEFAULT_BUCKETS};
+//!
+//! let registry = Registry::new();
+//! let logins = registry.counter("app_logins_total", "Successful logins");
+//! let latency = registry.histogram_family(
+//!     "app_api_duration_seconds",
+//!     "API call latency",
+//!     &["endpoint"],
+//!     DEFAULT_BUCKETS,
+//! );
+//!
+//! logins.inc();
+//! latency.with_labels(&["/cart"]).observe(0.012);
+//!
+//! let text = registry.render();
+//! assert!(text.contains("app_logins_total 1"));
+//! assert!(text.contains(r#"app_api_duration_seconds_count{endpoint="/cart"} 1"#));
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+/// Default histogram buckets in seconds, from 0.5ms to 10s
+pub const DEFAULT_BUCKETS: &[f64] = &[
+    0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Content type of [`Registry::render`] output
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Monotonically increasing count
+#[derive(Debug, Default)]
+pub struct Counter {
+    value: AtomicU64,
+}
+
+impl Counter {
+    /// Create a counter at zero
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add one
+    #[inline]
+    pub fn inc(&self) {
+        self.inc_by(1);
+    }
+
+    /// Add `n`
+    #[inline]
+    pub fn inc_by(&self, n: u64) {
+        self.value.fetch_add(n, Ordering::Relaxed);
+    }
+
+    /// Current count
+    pub fn get(&self) -> u64 {
+        self.value.load(Ordering::Relaxed)
+    }
+}
+
+/// Value that can go up and down
+#[derive(Debug, Default)]
+pub struct Gauge {
+    bits: AtomicU64,
+}
+
+impl Gauge {
+    /// Create a gauge at zero
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the value
+    #[inline]
+    pub fn set(&self, value: f64) {
+        self.bits.store(value.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Add `delta` (negative to subtract)
+    pub fn add(&self, delta: f64) {
+        atomic_add_f64(&self.bits, delta);
+    }
+
+    /// Current value
+    pub fn get(&self) -> f64 {
+        f64::from_bits(self.bits.load(Ordering::Relaxed))
+    }
+}
+
+/// Distribution of observed values over fixed buckets
+#[derive(Debug)]
+pub struct Histogram {
+    /// Upper bounds, ascending
+    bounds: Box<[f64]>,
+    /// Observations per bucket (not cumulative), plus one for `+Inf`
+    counts: Box<[AtomicU64]>,
+    sum_bits: AtomicU64,
+}
+
+impl Histogram {
+    /// Create a histogram with the given upper bounds
+    ///
+    /// Bounds are sorted; an implicit `+Inf` bucket catches larger values.
+    pub fn new(buckets: &[f64]) -> Self {
+        let mut bounds: Vec<f64> = buckets.iter().copied().filter(|b| b.is_finite()).collect();
+        bounds.sort_by(f64::total_cmp);
+        bounds.dedup();
+
+        Self {
+            counts: (0..=bounds.len()).map(|_| AtomicU64::new(0)).collect(),
+            bounds: bounds.into_boxed_slice(),
+            sum_bits: AtomicU64::new(0f64.to_bits()),
+        }
+    }
+
+    /// Record a value
+    #[inline]
+    pub fn observe(&self, value: f64) {
+        let bucket = self.bounds.partition_point(|&bound| bound < value);
+        self.counts[bucket].fetch_add(1, Ordering::Relaxed);
+        atomic_add_f64(&self.sum_bits, value);
+    }
+
+    /// Record a duration in seconds
+    #[inline]
+    pub fn observe_duration(&self, duration: Duration) {
+        self.observe(duration.as_secs_f64());
+    }
+
+    /// Number of observations
+    pub fn count(&self) -> u64 {
+        self.counts.iter().map(|c| c.load(Ordering::Relaxed)).sum()
+    }
+
+    /// Sum of observed values
+    pub fn sum(&self) -> f64 {
+        f64::from_bits(self.sum_bits.load(Ordering::Relaxed))
+    }
+
+    /// Cumulative count per upper bound, ending with `+Inf`
+    pub fn buckets(&self) -> Vec<(f64, u64)> {
+        let mut cumulative = 0;
+        self.bounds
+            .iter()
+            .copied()
+            .chain(std::iter::once(f64::INFINITY))
+            .zip(self.counts.iter())
+            .map(|(bound, count)| {
+                cumulative += count.load(Ordering::Relaxed);
+                (bound, cumulative)
+            })
+            .collect()
+    }
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self::new(DEFAULT_BUCKETS)
+    }
+}
+
+fn atomic_add_f64(bits: &AtomicU64, delta: f64) {
+    let _ = bits.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |current| {
+        Some((f64::from_bits(current) + delta).to_bits())
+    });
+}
+
+/// Metrics of one name, one per combination of label values
+pub struct Family<M> {
+    label_names: Vec<String>,
+    children: RwLock<BTreeMap<Vec<String>, Arc<M>>>,
+    new_metric: Box<dyn Fn() -> M + Send + Sync>,
+}
+
+impl<M> Family<M> {
+    fn new<F>(label_names: &[&str], new_metric: F) -> Self
+    where
+        F: Fn() -> M + Send + Sync + 'static,
+    {
+        Self {
+            label_names: label_names.iter().map(|name| name.to_string()).collect(),
+            children: RwLock::new(BTreeMap::new()),
+            new_metric: Box::new(new_metric),
+        }
+    }
+
+    /// The metric for these label values, created on first use
+    ///
+    /// Values are matched to the label names by position; missing values
+    /// are empty. Keep the number of distinct values small, e.g. route
+    /// patterns rather than URLs.
+    pub fn with_labels(&self, values: &[&str]) -> Arc<M> {
+        let key: Vec<String> = (0..self.label_names.len())
+            .map(|i| values.get(i).copied().unwrap_or("").to_string())
+            .collect();
+        if let Some(metric) = self.children.read().unwrap().get(&key) {
+            return Arc::clone(metric);
+        }
+
+        Arc::clone(
+            self.children
+                .write()
+                .unwrap()
+                .entry(key)
+                .or_insert_with(|| Arc::new((self.new_metric)())),
+        )
+    }
+
+    fn for_each(&self, mut f: impl FnMut(&[(&str, &str)], &M)) {
+        for (values, metric) in self.children.read().unwrap().iter() {
+            let labels: Vec<(&str, &str)> = self
+                .label_names
+                .iter()
+                .map(String::as_str)
+                .zip(values.iter().map(String::as_str))
+                .collect();
+            f(&labels, metric);
+        }
+    }
+}
+
+impl<M> std::fmt::Debug for Family<M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Family")
+            .field("label_names", &self.label_names)
+            .field("children", &self.children.read().unwrap().len())
+            .finish()
+    }
+}
+
+/// Prometheus metric type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricKind {
+    /// Monotonic count, named `*_total`
+    Counter,
+    /// Value that can go up and down
+    Gauge,
+    /// Bucketed distribution
+    Histogram,
+}
+
+impl MetricKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            MetricKind::Counter => "counter",
+            MetricKind::Gauge => "gauge",
+            MetricKind::Histogram => "histogram",
+        }
+    }
+}
+
+enum Registered {
+    Counter(Arc<Family<Counter>>),
+    Gauge(Arc<Family<Gauge>>),
+    Histogram(Arc<Family<Histogram>>),
+}
+
+/// Collection of named metrics
+#[derive(Default)]
+pub struct Registry {
+    metrics: RwLock<Vec<(String, String, Registered)>>,
+}
+
+impl Registry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a counter without labels
+    pub fn counter(&self, name: &str, help: &str) -> Arc<Counter> {
+        self.counter_family(name, help, &[]).with_labels(&[])
+    }
+
+    /// Register a counter split by labels
+    pub fn counter_family(&self, name: &str, help: &str, labels: &[&str]) -> Arc<Family<Counter>> {
+        let family = Arc::new(Family::new(labels, Counter::new));
+        self.register(name, help, Registered::Counter(Arc::clone(&family)));
+        family
+    }
+
+    /// Register a gauge without labels
+    pub fn gauge(&self, name: &str, help: &str) -> Arc<Gauge> {
+        self.gauge_family(name, help, &[]).with_labels(&[])
+    }
+
+    /// Register a gauge split by labels
+    pub fn gauge_family(&self, name: &str, help: &str, labels: &[&str]) -> Arc<Family<Gauge>> {
+        let family = Arc::new(Family::new(labels, Gauge::new));
+        self.register(name, help, Registered::Gauge(Arc::clone(&family)));
+        family
+    }
+
+    /// Register a histogram without labels
+    pub fn histogram(&self, name: &str, help: &str, buckets: &[f64]) -> Arc<Histogram> {
+        self.histogram_family(name, help, &[], buckets)
+            .with_labels(&[])
+    }
+
+    /// Register a histogram split by labels
+    pub fn histogram_family(
+        &self,
+        name: &str,
+        help: &str,
+        labels: &[&str],
+        buckets: &[f64],
+    ) -> Arc<Family<Histogram>> {
+        let buckets = buckets.to_vec();
+        let family = Arc::new(Family::new(labels, move || Histogram::new(&buckets)));
+        self.register(name, help, Registered::Histogram(Arc::clone(&family)));
+        family
+    }
+
+    fn register(&self, name: &str, help: &str, metric: Registered) {
+        let mut metrics = self.metrics.write().unwrap();
+        // Re-registering a name replaces the earlier metric
+        metrics.retain(|(existing, _, _)| existing != name);
+        metrics.push((name.to_string(), help.to_string(), metric));
+    }
+
+    /// Write every registered metric to `encoder`
+    pub fn encode(&self, encoder: &mut TextEncoder) {
+        for (name, help, metric) in self.metrics.read().unwrap().iter() {
+            match metric {
+                Registered::Counter(family) => {
+                    encoder.header(name, help, MetricKind::Counter);
+                    family.for_each(|labels, counter| {
+                        encoder.sample(name, labels, counter.get() as f64)
+                    });
+                }
+                Registered::Gauge(family) => {
+                    encoder.header(name, help, MetricKind::Gauge);
+                    family.for_each(|labels, gauge| encoder.sample(name, labels, gauge.get()));
+                }
+                Registered::Histogram(family) => {
+                    encoder.header(name, help, MetricKind::Histogram);
+                    family.for_each(|labels, histogram| encoder.histogram(name, labels, histogram));
+                }
+            }
+        }
+    }
+
+    /// Render every registered metric in the Prometheus text format
+    pub fn render(&self) -> String {
+        let mut encoder = TextEncoder::new();
+        self.encode(&mut encoder);
+        encoder.finish()
+    }
+}
+
+impl std::fmt::Debug for Registry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.metrics.read().unwrap().iter().map(|(name, _, _)| name))
+            .finish()
+    }
+}
+
+/// Writer of the Prometheus text exposition format
+///
+/// Used to add samples computed at scrape time next to registered metrics.
+#[derive(Debug, Default)]
+pub struct TextEncoder {
+    out: String,
+}
+
+impl TextEncoder {
+    /// Create an empty encoder
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Write the `# HELP` and `# TYPE` lines of a metric
+    pub fn header(&mut self, name: &str, help: &str, kind: MetricKind) {
+        let help = help.replace('\\', "\\\\").replace('\n', "\\n");
+        let _ = writeln!(self.out, "# HELP {} {}", name, help);
+        let _ = writeln!(self.out, "# TYPE {} {}", name, kind.as_str());
+    }
+
+    /// Write one sample
+    pub fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: f64) {
+        self.out.push_str(name);
+        self.write_labels(labels, None);
+        let _ = writeln!(self.out, " {}", format_value(value));
+    }
+
+    /// Write the bucket, sum and count samples of a histogram
+    pub fn histogram(&mut self, name: &str, labels: &[(&str, &str)], histogram: &Histogram) {
+        let mut count = 0;
+        for (bound, cumulative) in histogram.buckets() {
+            let _ = write!(self.out, "{}_bucket", name);
+            self.write_labels(labels, Some(&format_value(bound)));
+            let _ = writeln!(self.out, " {}", cumulative);
+            count = cumulative;
+        }
+        self.sample(&format!("{}_sum", name), labels, histogram.sum());
+        self.sample(&format!("{}_count", name), labels, count as f64);
+    }
+
+    fn write_labels(&mut self, labels: &[(&str, &str)], le: Option<&str>) {
+        if labels.is_empty() && le.is_none() {
+            return;
+        }
+
+        self.out.push('{');
+        let le = le.map(|le| ("le", le));
+        for (i, (name, value)) in labels.iter().copied().chain(le).enumerate() {
+            if i > 0 {
+                self.out.push(',');
+            }
+            let value = value
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n");
+            let _ = write!(self.out, "{}=\"{}\"", name, value);
+        }
+        self.out.push('}');
+    }
+
+    /// The encoded text
+    pub fn finish(self) -> String {
+        self.out
+    }
+}
+
+fn format_value(value: f64) -> String {
+    if value == f64::INFINITY {
+        "+Inf".to_string()
+    } else if value == f64::NEG_INFINITY {
+        "-Inf".to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram_buckets() {
+        let histogram = Histogram::new(&[0.1, 1.0, 0.5]);
+        for value in [0.05, 0.1, 0.3, 2.0] {
+            histogram.observe(value);
+        }
+
+        assert_eq!(
+            histogram.buckets(),
+            [(0.1, 2), (0.5, 3), (1.0, 3), (f64::INFINITY, 4)]
+        );
+        assert_eq!(histogram.count(), 4);
+        assert!((histogram.sum() - 2.45).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_render_text_format() {
+        let registry = Registry::new();
+        let requests = registry.counter_family("requests_total", "Requests", &["path"]);
+        requests.with_labels(&["/a\"b"]).inc_by(3);
+        registry.gauge("queue_depth", "Queued\njobs").set(2.5);
+        registry
+            .histogram("latency_seconds", "Latency", &[0.1])
+            .observe(0.05);
+
+        assert_eq!(
+            registry.render(),
+            "# HELP requests_total Requests\n\
+             # TYPE requests_total counter\n\
+             requests_total{path=\"/a\\\"b\"} 3\n\
+             # HELP queue_depth Queued\\njobs\n\
+             # TYPE queue_depth gauge\n\
+             queue_depth 2.5\n\
+             # HELP latency_seconds Latency\n\
+             # TYPE latency_seconds histogram\n\
+             latency_seconds_bucket{le=\"0.1\"} 1\n\
+             latency_seconds_bucket{le=\"+Inf\"} 1\n\
+             latency_seconds_sum 0.05\n\
+             latency_seconds_count 1\n"
+        );
+    }
+
+    #[test]
+    fn test_family_reuses_children() {
+        let registry = Registry::new();
+        let family = registry.counter_family("hits_total", "Hits", &["tier", "route"]);
+        family.with_labels(&["hot", "/"]).inc();
+        family.with_labels(&["hot", "/"]).inc();
+        family.with_labels(&["cold"]).inc();
+        assert!(Arc::ptr_eq(
+            &family.with_labels(&["cold"]),
+            &family.with_labels(&["cold", ""])
+        ));
+
+        let text = registry.render();
+        assert!(text.contains("hits_total{tier=\"hot\",route=\"/\"} 2\n"));
+        assert!(text.contains("hits_total{tier=\"cold\",route=\"\"} 1\n"));
+    }
+}
@@ -0,0 +1,163 @@
+//! Authenticated admin endpoints for inspecting and managing the SSR cache
+
+use axum::{
+    extract::{DefaultBodyLimit, Query, Request, State},
+    http::{header, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+
+use crate::cache::{CacheSnapshot, EntryQuery};
+use crate::SsrEngine;
+
+/// Router exposing the cache admin API
+///
+/// Every request must carry `Authorization: Bearer <token>`; others get
+/// `401 Unauthorized`. An empty token rejects every request.
+///
+/// | Method | Path          | Description                                              |
+/// |--------|---------------|----------------------------------------------------------|
+/// | GET    | `/entries`    | List entries (`?prefix=/blog&offset=0&limit=100`)        |
+/// | GET    | `/entry`      | Peek at one entry and its HTML (`?url=/about`)           |
+/// | GET    | `/snapshot`   | Export a [`CacheSnapshot`]                               |
+/// | POST   | `/snapshot`   | Import a [`CacheSnapshot`]                               |
+/// | POST   | `/invalidate` | Invalidate `{"url": "/about"}` or `{"prefix": "/blog"}`  |
+/// | POST   | `/clear`      | Clear the cache                                          |
+///
+/// Invalidations go through the engine, so they reach other replicas when
+/// an invalidation bus is configured.
+///
+/// # Example
+/// ```rust,no_run
+/// use std::sync::Arc;
+/// use axum::Router;
+/// use rusty_ssr::middleware::admin_router;
+/// use rusty_ssr::SsrEngine;
+///
+/// # fn example(engine: SsrEngine) {
+/// let engine = Arc::new(engine);
+/// let token = std::env::var("SSR_ADMIN_TOKEN").unwrap();
+///
+/// let app: Router = Router::new().nest("/_admin/cache", admin_router(engine, token));
+/// # }
+/// ```
+pub fn admin_router<S: Into<String>>(engine: Arc<SsrEngine>, token: S) -> Router {
+    let token: Arc<str> = Arc::from(token.into());
+
+    Router::new()
+        .route("/entries", get(list_entries))
+        .route("/entry", get(peek_entry))
+        .route(
+            "/snapshot",
+            get(export_snapshot)
+                .post(import_snapshot)
+                // Snapshots hold whole pages, well over the 2 MB default
+                .layer(DefaultBodyLimit::disable()),
+        )
+        .route("/invalidate", post(invalidate))
+        .route("/clear", post(clear))
+        .layer(middleware::from_fn_with_state(token, require_token))
+        .with_state(engine)
+}
+
+/// Reject requests without the admin bearer token
+async fn require_token(State(token): State<Arc<str>>, request: Request, next: Next) -> Response {
+    let provided = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+
+    match provided {
+        Some(provided) if !token.is_empty() && constant_time_eq(provided, &token) => {
+            next.run(request).await
+        }
+        _ => {
+            let mut response = StatusCode::UNAUTHORIZED.into_response();
+            response
+                .headers_mut()
+                .insert(header::WWW_AUTHENTICATE, "Bearer".parse().unwrap());
+            response
+        }
+    }
+}
+
+/// Compare without leaking the position of the first difference
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0u8, |diff, (x, y)| diff | (x ^ y))
+            == 0
+}
+
+async fn list_entries(
+    State(engine): State<Arc<SsrEngine>>,
+    Query(query): Query<EntryQuery>,
+) -> Response {
+    Json(engine.cache().entries(&query)).into_response()
+}
+
+#[derive(Deserialize)]
+struct EntryParams {
+    url: String,
+}
+
+async fn peek_entry(
+    State(engine): State<Arc<SsrEngine>>,
+    Query(params): Query<EntryParams>,
+) -> Response {
+    match engine.cache().peek_entry(&params.url) {
+        Some(page) => Json(page).into_response(),
+        None => (StatusCode::NOT_FOUND, "Not cached").into_response(),
+    }
+}
+
+async fn export_snapshot(State(engine): State<Arc<SsrEngine>>) -> Response {
+    Json(engine.cache().export_snapshot()).into_response()
+}
+
+async fn import_snapshot(
+    State(engine): State<Arc<SsrEngine>>,
+    Json(snapshot): Json<CacheSnapshot>,
+) -> Response {
+    let imported = engine.cache().import_snapshot(&snapshot);
+    Json(json!({ "imported": imported })).into_response()
+}
+
+#[derive(Deserialize)]
+struct InvalidateRequest {
+    url: Option<String>,
+    prefix: Option<String>,
+}
+
+async fn invalidate(
+    State(engine): State<Arc<SsrEngine>>,
+    Json(request): Json<InvalidateRequest>,
+) -> Response {
+    match (request.url, request.prefix) {
+        (Some(url), None) => {
+            engine.invalidate(&url);
+            StatusCode::NO_CONTENT.into_response()
+        }
+        (None, Some(prefix)) => {
+            let removed = engine.invalidate_prefix(&prefix);
+            Json(json!({ "removed": removed })).into_response()
+        }
+        _ => (
+            StatusCode::BAD_REQUEST,
+            "Expected exactly one of \"url\" or \"prefix\"",
+        )
+            .into_response(),
+    }
+}
+
+async fn clear(State(engine): State<Arc<SsrEngine>>) -> Response {
+    engine.clear_cache();
+    StatusCode::NO_CONTENT.into_response()
+}
@@ -117,6 +117,11 @@ pub async fn brotli_compress(request: Request, next: Next) -> Result<Response, S
         return Ok(response);
     }
 
+    // Already encoded, e.g. served pre-compressed from the SSR cache
+    if response.headers().contains_key(header::CONTENT_ENCODING) {
+        return Ok(response);
+    }
+
     // Extract body
     let (parts, body) = response.into_parts();
     let body_bytes = match axum::body::to_bytes(body, usize::MAX).await {
@@ -0,0 +1,29 @@
+//! Prometheus scrape endpoint
+
+use axum::{extract::State, http::header, response::IntoResponse};
+use std::sync::Arc;
+
+use crate::metrics::CONTENT_TYPE;
+use crate::SsrEngine;
+
+/// Serve [`SsrEngine::render_metrics`] to Prometheus
+///
+/// # Example
+/// ```rust,no_run
+/// use std::sync::Arc;
+/// use axum::{routing::get, Router};
+/// use rusty_ssr::middleware::metrics_handler;
+/// use rusty_ssr::SsrEngine;
+///
+/// # fn example(engine: SsrEngine) {
+/// let app: Router = Router::new()
+///     .route("/metrics", get(metrics_handler))
+///     .with_state(Arc::new(engine));
+/// # }
+/// ```
+pub async fn metrics_handler(State(engine): State<Arc<SsrEngine>>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, CONTENT_TYPE)],
+        engine.render_metrics(),
+    )
+}
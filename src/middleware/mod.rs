@@ -1,9 +1,26 @@
 //! Axum middleware for SSR applications
 //!
-//! Provides compression and caching middleware for optimal performance.
+//! Provides compression and caching middleware for optimal performance,
+//! an admin API for the cache and a Prometheus metrics endpoint.
 
 #[cfg(feature = "brotli-compression")]
 mod brotli;
 
 #[cfg(feature = "brotli-compression")]
 pub use brotli::{brotli_compress, brotli_static};
+
+#[cfg(feature = "cache-compression")]
+mod ssr;
+
+#[cfg(all(feature = "cache-compression", feature = "v8-pool"))]
+pub use ssr::ssr_handler;
+
+#[cfg(feature = "cache")]
+mod admin;
+
+#[cfg(feature = "cache")]
+pub use admin::admin_router;
+
+mod metrics;
+
+pub use metrics::metrics_handler;
@@ -0,0 +1,166 @@
+//! Axum handler serving SSR pages from pre-compressed cache variants
+
+use axum::{
+    body::{Body, Bytes},
+    http::{header, HeaderValue},
+    response::{IntoResponse, Response},
+};
+
+use crate::cache::EncodedHtml;
+#[cfg(feature = "v8-pool")]
+use crate::{RenderOptions, SsrEngine};
+#[cfg(feature = "v8-pool")]
+use axum::{
+    extract::{Request, State},
+    http::StatusCode,
+    response::Redirect,
+};
+#[cfg(feature = "v8-pool")]
+use std::sync::Arc;
+
+/// Cached HTML, shared with the response body instead of copied
+struct SharedHtml(std::sync::Arc<str>);
+
+impl AsRef<[u8]> for SharedHtml {
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_bytes()
+    }
+}
+
+impl IntoResponse for EncodedHtml {
+    fn into_response(self) -> Response {
+        let mut response = match self {
+            EncodedHtml::Raw(html) => {
+                Response::new(Body::from(Bytes::from_owner(SharedHtml(html))))
+            }
+            EncodedHtml::Encoded(encoding, data) => {
+                let mut response = Response::new(Body::from(Bytes::from_owner(data)));
+                response.headers_mut().insert(
+                    header::CONTENT_ENCODING,
+                    HeaderValue::from_static(encoding.as_str()),
+                );
+                response
+            }
+        };
+
+        let headers = response.headers_mut();
+        headers.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("text/html; charset=utf-8"),
+        );
+        headers.insert(header::VARY, HeaderValue::from_static("Accept-Encoding"));
+        response
+    }
+}
+
+/// Axum handler rendering the request path with the engine
+///
+/// Serves the pre-compressed variant matching `Accept-Encoding` straight
+/// from the cache (see `SsrConfigBuilder::cache_compression`), so no
+/// compression layer is needed for SSR pages.
+///
+/// Requests for a non-canonical path (see `SsrConfigBuilder::url_normalizer`)
+/// get a `308 Permanent Redirect` to the canonical URL.
+///
+/// Cache misses are rendered with the [`RenderOptions`] request extension,
+/// if a middleware set one (e.g. a lower priority for crawlers), else with
+/// the defaults.
+///
+/// # Example
+/// ```rust,no_run
+/// use std::sync::Arc;
+/// use axum::{extract::Request, middleware::{self, Next}, routing::get, Router};
+/// use rusty_ssr::middleware::ssr_handler;
+/// use rusty_ssr::{Priority, RenderOptions, SsrEngine};
+///
+/// async fn classify(mut request: Request, next: Next) -> axum::response::Response {
+///     let ua = request.headers().get("user-agent").and_then(|v| v.to_str().ok());
+///     if ua.is_some_and(|ua| ua.contains("bot")) {
+///         request.extensions_mut().insert(RenderOptions {
+///             priority: Priority::Bot,
+///             ..Default::default()
+///         });
+///     }
+///     next.run(request).await
+/// }
+///
+/// # fn example(engine: SsrEngine) {
+/// let app: Router = Router::new()
+///     .fallback(get(ssr_handler))
+///     .layer(middleware::from_fn(classify))
+///     .with_state(Arc::new(engine));
+/// # }
+/// ```
+#[cfg(feature = "v8-pool")]
+pub async fn ssr_handler(State(engine): State<Arc<SsrEngine>>, request: Request) -> Response {
+    let accept_encoding = request
+        .headers()
+        .get(header::ACCEPT_ENCODING)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
+    let url = request
+        .uri()
+        .path_and_query()
+        .map(|pq| pq.as_str())
+        .unwrap_or("/");
+
+    if let Some(location) = engine.normalize_url(url).redirect {
+        return Redirect::permanent(&location).into_response();
+    }
+
+    let options = request
+        .extensions()
+        .get::<RenderOptions>()
+        .copied()
+        .unwrap_or_default();
+
+    match engine
+        .render_encoded_with_options(url, accept_encoding, options)
+        .await
+    {
+        Ok(page) => page.into_response(),
+        Err(e) => {
+            tracing::error!("SSR error for {}: {}", url, e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error").into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::Encoding;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_encoded_response_headers() {
+        let response =
+            EncodedHtml::Encoded(Encoding::Brotli, Arc::from(&b"data"[..])).into_response();
+        assert_eq!(response.headers()[header::CONTENT_ENCODING], "br");
+        assert_eq!(response.headers()[header::VARY], "Accept-Encoding");
+
+        let response = EncodedHtml::Raw(Arc::from("<p>hi</p>")).into_response();
+        assert!(response.headers().get(header::CONTENT_ENCODING).is_none());
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "text/html; charset=utf-8"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_response_body_shares_cached_bytes() {
+        let data: Arc<[u8]> = Arc::from(&b"compressed"[..]);
+        let response = EncodedHtml::Encoded(Encoding::Gzip, Arc::clone(&data)).into_response();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(body.as_ptr(), data.as_ptr());
+
+        let html: Arc<str> = Arc::from("<p>hi</p>");
+        let response = EncodedHtml::Raw(Arc::clone(&html)).into_response();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(body.as_ptr(), html.as_ptr());
+    }
+}
@@ -0,0 +1,437 @@
+//! Canonical request URLs
+//!
+//! `/products/1`, `/products/1/`, `/PRODUCTS/1` and `/products/1?utm_source=x`
+//! usually render the same page. A [`UrlNormalizer`](crate::normalize::UrlNormalizer) maps them to one URL
+//! before the cache lookup, so they share a cache entry and a render, and
+//! reports when the client should be redirected to the canonical path.
+
+/// What to do with a trailing slash on the path
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TrailingSlash {
+    /// Leave the path as is
+    #[default]
+    Keep,
+    /// `/about/` → `/about`
+    Strip,
+    /// `/about` → `/about/`, except for file-like paths (`/robots.txt`)
+    Always,
+}
+
+/// Which query parameters are kept
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum QueryParams {
+    /// Keep every parameter
+    #[default]
+    All,
+    /// Keep only these parameters
+    Allow(Vec<String>),
+    /// Drop these parameters
+    Deny(Vec<String>),
+}
+
+/// Common analytics and ad click parameters, for [`UrlNormalizer::deny_tracking_params`]
+///
+/// A trailing `*` matches any parameter with that prefix.
+pub const TRACKING_PARAMS: &[&str] = &[
+    "utm_*", "gclid", "gbraid", "wbraid", "dclid", "fbclid", "msclkid", "yclid", "twclid",
+    "igshid", "mc_cid", "mc_eid", "_ga", "_gl", "_hsenc", "_hsmi",
+];
+
+/// Rewrites request URLs to a canonical form
+///
+/// Every option is off by default, so `UrlNormalizer::new()` leaves URLs
+/// unchanged apart from dropping the `#fragment`.
+///
+/// # Example
+/// ```rust
+/// use rusty_ssr::normalize::{TrailingSlash, UrlNormalizer};
+///
+/// let normalizer = UrlNormalizer::new()
+///     .trailing_slash(TrailingSlash::Strip)
+///     .lowercase_path()
+///     .deny_tracking_params()
+///     .sort_query()
+///     .decode_percent();
+///
+/// let normalized = normalizer.normalize("/Products/1/?utm_source=x&b=2&a=1");
+/// assert_eq!(normalized.url, "/products/1?a=1&b=2");
+/// // The path was not canonical: redirect, keeping the original query
+/// assert_eq!(
+///     normalized.redirect.as_deref(),
+///     Some("/products/1?utm_source=x&b=2&a=1")
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UrlNormalizer {
+    /// Trailing slash policy
+    pub trailing_slash: TrailingSlash,
+
+    /// Lowercase the path (the query is left as is)
+    pub lowercase_path: bool,
+
+    /// Query parameters to keep
+    pub query_params: QueryParams,
+
+    /// Sort query parameters by name (values of a repeated name keep their order)
+    pub sort_query: bool,
+
+    /// Decode percent-encoded unreserved characters (`%7E` → `~`)
+    ///
+    /// Other escapes, such as `%2F`, change the meaning of a URL and are only
+    /// normalized to uppercase hex.
+    pub decode_percent: bool,
+}
+
+/// Result of [`UrlNormalizer::normalize`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NormalizedUrl {
+    /// Canonical URL, used as the cache key and passed to the renderer
+    pub url: String,
+
+    /// Where to redirect the client, if the requested path is not canonical
+    ///
+    /// Only path changes (trailing slash, case, percent-decoding) warrant a
+    /// redirect; the query is passed on unchanged, so dropped tracking
+    /// parameters still reach client-side analytics. Paths starting with
+    /// `//` or `/\` are never redirected to, since browsers would read them
+    /// as another host.
+    pub redirect: Option<String>,
+}
+
+impl UrlNormalizer {
+    /// Create a normalizer that leaves URLs unchanged
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the trailing slash policy
+    pub fn trailing_slash(mut self, policy: TrailingSlash) -> Self {
+        self.trailing_slash = policy;
+        self
+    }
+
+    /// Lowercase the path
+    pub fn lowercase_path(mut self) -> Self {
+        self.lowercase_path = true;
+        self
+    }
+
+    /// Keep only the given query parameters
+    ///
+    /// A trailing `*` matches any parameter with that prefix.
+    pub fn allow_query_params<I, S>(mut self, params: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.query_params = QueryParams::Allow(params.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Drop the given query parameters
+    ///
+    /// A trailing `*` matches any parameter with that prefix. Adds to
+    /// parameters denied earlier, e.g. by [`UrlNormalizer::deny_tracking_params`].
+    pub fn deny_query_params<I, S>(mut self, params: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut denied = match self.query_params {
+            QueryParams::Deny(denied) => denied,
+            _ => Vec::new(),
+        };
+        denied.extend(params.into_iter().map(Into::into));
+        self.query_params = QueryParams::Deny(denied);
+        self
+    }
+
+    /// Drop the [`TRACKING_PARAMS`]
+    pub fn deny_tracking_params(self) -> Self {
+        self.deny_query_params(TRACKING_PARAMS.iter().copied())
+    }
+
+    /// Sort query parameters by name
+    pub fn sort_query(mut self) -> Self {
+        self.sort_query = true;
+        self
+    }
+
+    /// Decode percent-encoded unreserved characters
+    pub fn decode_percent(mut self) -> Self {
+        self.decode_percent = true;
+        self
+    }
+
+    /// Normalize a request URL (path and query)
+    pub fn normalize(&self, url: &str) -> NormalizedUrl {
+        let url = url.split('#').next().unwrap_or(url);
+        let (requested_path, query) = match url.split_once('?') {
+            Some((path, query)) => (path, Some(query)),
+            None => (url, None),
+        };
+
+        let path = self.normalize_path(requested_path);
+
+        let mut params: Vec<String> = query
+            .into_iter()
+            .flat_map(|query| query.split('&'))
+            .filter(|param| !param.is_empty())
+            .map(|param| {
+                if self.decode_percent {
+                    decode_unreserved(param)
+                } else {
+                    param.to_string()
+                }
+            })
+            .filter(|param| self.keeps(param_name(param)))
+            .collect();
+        if self.sort_query {
+            // Stable, so repeated names keep their order
+            params.sort_by(|a, b| param_name(a).cmp(param_name(b)));
+        }
+
+        let mut canonical = path.clone();
+        if !params.is_empty() {
+            canonical.push('?');
+            canonical.push_str(&params.join("&"));
+        }
+
+        let redirect = (path != requested_path && !is_host_relative(&path)).then(|| match query {
+            Some(query) => format!("{}?{}", path, query),
+            None => path,
+        });
+
+        NormalizedUrl {
+            url: canonical,
+            redirect,
+        }
+    }
+
+    fn normalize_path(&self, path: &str) -> String {
+        let mut path = if self.decode_percent {
+            decode_unreserved(path)
+        } else {
+            path.to_string()
+        };
+
+        if self.lowercase_path {
+            path = uppercase_escapes(&path.to_lowercase());
+        }
+
+        match self.trailing_slash {
+            TrailingSlash::Keep => {}
+            TrailingSlash::Strip => {
+                let trimmed = path.trim_end_matches('/').len();
+                path.truncate(trimmed.max(1));
+            }
+            TrailingSlash::Always => {
+                let last = path.rsplit('/').next().unwrap_or("");
+                if !path.ends_with('/') && !last.contains('.') {
+                    path.push('/');
+                }
+            }
+        }
+
+        path
+    }
+
+    fn keeps(&self, name: &str) -> bool {
+        match &self.query_params {
+            QueryParams::All => true,
+            QueryParams::Allow(allowed) => allowed.iter().any(|p| param_matches(p, name)),
+            QueryParams::Deny(denied) => !denied.iter().any(|p| param_matches(p, name)),
+        }
+    }
+}
+
+/// Whether a path would be read as a URL of another host (`//evil.com`)
+fn is_host_relative(path: &str) -> bool {
+    path.starts_with("//") || path.starts_with("/\\")
+}
+
+fn param_name(param: &str) -> &str {
+    param.split('=').next().unwrap_or(param)
+}
+
+fn param_matches(pattern: &str, name: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => name.starts_with(prefix),
+        None => pattern == name,
+    }
+}
+
+fn hex_value(byte: u8) -> Option<u8> {
+    (byte as char).to_digit(16).map(|d| d as u8)
+}
+
+/// Decode escapes of unreserved characters, uppercasing the hex of others
+fn decode_unreserved(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = String::with_capacity(s.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            if let (Some(hi), Some(lo)) = (hex_value(bytes[i + 1]), hex_value(bytes[i + 2])) {
+                let decoded = (hi << 4 | lo) as char;
+                if decoded.is_ascii_alphanumeric() || "-._~".contains(decoded) {
+                    out.push(decoded);
+                } else {
+                    out.push_str(&format!("%{:02X}", hi << 4 | lo));
+                }
+                i += 3;
+                continue;
+            }
+        }
+        // Copy the whole (possibly multi-byte) character
+        let ch = s[i..].chars().next().unwrap_or('\u{FFFD}');
+        out.push(ch);
+        i += ch.len_utf8();
+    }
+
+    out
+}
+
+/// Uppercase the hex digits of percent escapes (`%c3%a9` → `%C3%A9`)
+fn uppercase_escapes(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        out.push(ch);
+        if ch == '%' {
+            for _ in 0..2 {
+                match chars.peek() {
+                    Some(c) if c.is_ascii_hexdigit() => {
+                        out.push(c.to_ascii_uppercase());
+                        chars.next();
+                    }
+                    _ => break,
+                }
+            }
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_leaves_urls_unchanged() {
+        let normalizer = UrlNormalizer::new();
+        for url in ["/", "/Products/1/", "/a?b=2&a=1", "/%7euser"] {
+            let normalized = normalizer.normalize(url);
+            assert_eq!(normalized.url, url);
+            assert_eq!(normalized.redirect, None);
+        }
+        assert_eq!(normalizer.normalize("/page#top").url, "/page");
+    }
+
+    #[test]
+    fn test_trailing_slash() {
+        let strip = UrlNormalizer::new().trailing_slash(TrailingSlash::Strip);
+        assert_eq!(strip.normalize("/about/").url, "/about");
+        assert_eq!(strip.normalize("/about//?x=1").url, "/about?x=1");
+        assert_eq!(strip.normalize("/").url, "/");
+        assert_eq!(strip.normalize("/").redirect, None);
+
+        let always = UrlNormalizer::new().trailing_slash(TrailingSlash::Always);
+        assert_eq!(always.normalize("/about").url, "/about/");
+        assert_eq!(always.normalize("/robots.txt").url, "/robots.txt");
+        assert_eq!(
+            always.normalize("/about").redirect.as_deref(),
+            Some("/about/")
+        );
+    }
+
+    #[test]
+    fn test_no_redirect_to_other_hosts() {
+        let normalizer = UrlNormalizer::new()
+            .trailing_slash(TrailingSlash::Strip)
+            .lowercase_path();
+
+        for url in [
+            "//evil.com/",
+            "//evil.com",
+            "/\\evil.com",
+            "//EVIL.com/?x=1",
+        ] {
+            assert_eq!(normalizer.normalize(url).redirect, None, "{}", url);
+        }
+        assert_eq!(
+            UrlNormalizer::new()
+                .trailing_slash(TrailingSlash::Always)
+                .normalize("/\\evil.com")
+                .redirect,
+            None
+        );
+    }
+
+    #[test]
+    fn test_lowercase_and_percent_decoding() {
+        let normalizer = UrlNormalizer::new().lowercase_path().decode_percent();
+
+        assert_eq!(normalizer.normalize("/PRODUCTS/1").url, "/products/1");
+        assert_eq!(normalizer.normalize("/%7Euser/%41").url, "/~user/a");
+        // Reserved and non-ASCII escapes stay encoded, with uppercase hex
+        assert_eq!(
+            normalizer.normalize("/a%2fb/caf%c3%a9").url,
+            "/a%2Fb/caf%C3%A9"
+        );
+        // The query is not lowercased
+        assert_eq!(normalizer.normalize("/s?q=Shoes%7e").url, "/s?q=Shoes~");
+        assert_eq!(normalizer.normalize("/s?q=Shoes%7e").redirect, None);
+        assert_eq!(normalizer.normalize("/bad%zz%").url, "/bad%zz%");
+    }
+
+    #[test]
+    fn test_query_filtering_and_sorting() {
+        let deny = UrlNormalizer::new().deny_tracking_params().sort_query();
+        assert_eq!(
+            deny.normalize("/p?utm_source=x&b=2&gclid=1&a=1&b=1").url,
+            "/p?a=1&b=2&b=1"
+        );
+        assert_eq!(deny.normalize("/p?utm_medium=email").url, "/p");
+        // Dropped parameters alone do not warrant a redirect
+        assert_eq!(deny.normalize("/p?utm_medium=email").redirect, None);
+
+        let allow = UrlNormalizer::new().allow_query_params(["page", "filter_*"]);
+        assert_eq!(
+            allow.normalize("/list?ref=x&page=2&filter_color=red").url,
+            "/list?page=2&filter_color=red"
+        );
+
+        let both = UrlNormalizer::new()
+            .deny_tracking_params()
+            .deny_query_params(["session"]);
+        assert_eq!(both.normalize("/?session=1&utm_id=2&q=x").url, "/?q=x");
+    }
+
+    #[test]
+    fn test_variants_share_one_url() {
+        let normalizer = UrlNormalizer::new()
+            .trailing_slash(TrailingSlash::Strip)
+            .lowercase_path()
+            .deny_tracking_params();
+
+        for url in [
+            "/products/1",
+            "/products/1/",
+            "/products/1?utm_source=x",
+            "/PRODUCTS/1",
+        ] {
+            assert_eq!(normalizer.normalize(url).url, "/products/1", "{}", url);
+        }
+        assert_eq!(
+            normalizer
+                .normalize("/PRODUCTS/1/?utm_source=x")
+                .redirect
+                .as_deref(),
+            Some("/products/1?utm_source=x")
+        );
+    }
+}
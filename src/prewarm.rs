@@ -0,0 +1,247 @@
+//! Background cache pre-warming
+//!
+//! [`SsrEngine::prewarm`](crate::SsrEngine::prewarm) renders a list of URLs
+//! into the cache at bounded concurrency. URL lists can be loaded from a
+//! `sitemap.xml` with [`load_sitemap`](crate::prewarm::load_sitemap) or
+//! [`parse_sitemap`](crate::prewarm::parse_sitemap).
+
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use tokio::task::JoinHandle;
+
+use crate::error::{SsrError, SsrResult};
+
+/// Live counters of a running pre-warm
+#[derive(Debug, Default)]
+pub struct PrewarmProgress {
+    total: AtomicUsize,
+    rendered: AtomicUsize,
+    skipped: AtomicUsize,
+    failures: Mutex<Vec<(String, String)>>,
+}
+
+impl PrewarmProgress {
+    #[cfg(all(feature = "v8-pool", feature = "cache"))]
+    pub(crate) fn new(total: usize) -> Self {
+        Self {
+            total: AtomicUsize::new(total),
+            ..Default::default()
+        }
+    }
+
+    #[cfg(all(feature = "v8-pool", feature = "cache"))]
+    pub(crate) fn record_rendered(&self) {
+        self.rendered.fetch_add(1, Ordering::Relaxed);
+    }
+
+    #[cfg(all(feature = "v8-pool", feature = "cache"))]
+    pub(crate) fn record_skipped(&self) {
+        self.skipped.fetch_add(1, Ordering::Relaxed);
+    }
+
+    #[cfg(all(feature = "v8-pool", feature = "cache"))]
+    pub(crate) fn record_failure(&self, url: String, error: String) {
+        self.failures.lock().unwrap().push((url, error));
+    }
+
+    /// Number of URLs to pre-warm
+    pub fn total(&self) -> usize {
+        self.total.load(Ordering::Relaxed)
+    }
+
+    /// URLs rendered and cached so far
+    pub fn rendered(&self) -> usize {
+        self.rendered.load(Ordering::Relaxed)
+    }
+
+    /// URLs skipped because they were already cached or are never cached
+    pub fn skipped(&self) -> usize {
+        self.skipped.load(Ordering::Relaxed)
+    }
+
+    /// URLs whose render failed so far
+    pub fn failed(&self) -> usize {
+        self.failures.lock().unwrap().len()
+    }
+
+    /// URLs processed so far, whatever the outcome
+    pub fn completed(&self) -> usize {
+        self.rendered() + self.skipped() + self.failed()
+    }
+
+    /// Whether every URL has been processed
+    pub fn is_done(&self) -> bool {
+        self.completed() >= self.total()
+    }
+
+    pub(crate) fn report(&self) -> PrewarmReport {
+        PrewarmReport {
+            total: self.total(),
+            rendered: self.rendered(),
+            skipped: self.skipped(),
+            failures: self.failures.lock().unwrap().clone(),
+        }
+    }
+}
+
+/// Outcome of a finished pre-warm
+#[derive(Debug, Clone, Default)]
+pub struct PrewarmReport {
+    /// Number of URLs to pre-warm
+    pub total: usize,
+
+    /// URLs rendered and cached
+    pub rendered: usize,
+
+    /// URLs skipped because they were already cached or are never cached
+    pub skipped: usize,
+
+    /// URLs whose render failed, with the error message
+    pub failures: Vec<(String, String)>,
+}
+
+/// Handle to a pre-warm running in the background
+///
+/// Dropping the handle does not stop the pre-warm.
+#[derive(Debug)]
+pub struct PrewarmHandle {
+    progress: Arc<PrewarmProgress>,
+    task: JoinHandle<()>,
+}
+
+impl PrewarmHandle {
+    #[cfg(all(feature = "v8-pool", feature = "cache"))]
+    pub(crate) fn new(progress: Arc<PrewarmProgress>, task: JoinHandle<()>) -> Self {
+        Self { progress, task }
+    }
+
+    /// Live progress counters
+    pub fn progress(&self) -> &PrewarmProgress {
+        &self.progress
+    }
+
+    /// Stop pre-warming; pages already cached stay cached
+    pub fn abort(&self) {
+        self.task.abort();
+    }
+
+    /// Wait for the pre-warm to finish and return its report
+    pub async fn wait(self) -> PrewarmReport {
+        let _ = self.task.await;
+        self.progress.report()
+    }
+}
+
+/// Extract page paths from a `sitemap.xml` document
+///
+/// Returns the path and query of every `<loc>` entry (e.g.
+/// `https://example.com/products?page=2` becomes `/products?page=2`), in
+/// document order and without duplicates. A sitemap index lists other
+/// sitemaps rather than pages; load each of those instead.
+pub fn parse_sitemap(xml: &str) -> Vec<String> {
+    let mut urls = Vec::new();
+    let mut seen = HashSet::new();
+    let mut rest = xml;
+
+    while let Some(start) = rest.find("<loc>") {
+        rest = &rest[start + "<loc>".len()..];
+        let Some(end) = rest.find("</loc>") else {
+            break;
+        };
+
+        let path = to_path(&unescape_xml(rest[..end].trim()));
+        if !path.is_empty() && seen.insert(path.clone()) {
+            urls.push(path);
+        }
+        rest = &rest[end + "</loc>".len()..];
+    }
+
+    urls
+}
+
+/// Read a `sitemap.xml` file and extract its page paths
+///
+/// See [`parse_sitemap`].
+pub fn load_sitemap<P: AsRef<Path>>(path: P) -> SsrResult<Vec<String>> {
+    let xml = std::fs::read_to_string(path.as_ref()).map_err(SsrError::Io)?;
+    Ok(parse_sitemap(&xml))
+}
+
+/// Strip scheme and host from an absolute URL
+fn to_path(loc: &str) -> String {
+    let Some(scheme_end) = loc.find("://") else {
+        return loc.to_string();
+    };
+
+    let after_host = &loc[scheme_end + 3..];
+    match after_host.find(['/', '?']) {
+        Some(i) if after_host[i..].starts_with('/') => after_host[i..].to_string(),
+        Some(i) => format!("/{}", &after_host[i..]),
+        None => "/".to_string(),
+    }
+}
+
+/// Decode the XML entities allowed in sitemap URLs
+fn unescape_xml(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sitemap() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+              <url><loc>https://example.com/</loc></url>
+              <url>
+                <loc> https://example.com/products?page=2&amp;sort=asc </loc>
+                <lastmod>2024-01-01</lastmod>
+              </url>
+              <url><loc>https://example.com?ref=home</loc></url>
+              <url><loc>https://example.com/</loc></url>
+            </urlset>"#;
+
+        assert_eq!(
+            parse_sitemap(xml),
+            vec!["/", "/products?page=2&sort=asc", "/?ref=home"]
+        );
+    }
+
+    #[test]
+    fn test_parse_sitemap_relative_and_empty() {
+        assert_eq!(
+            parse_sitemap("<loc>/about</loc><loc></loc>"),
+            vec!["/about"]
+        );
+        assert!(parse_sitemap("<urlset></urlset>").is_empty());
+    }
+
+    #[test]
+    #[cfg(all(feature = "v8-pool", feature = "cache"))]
+    fn test_progress_report() {
+        let progress = PrewarmProgress::new(3);
+        progress.record_rendered();
+        progress.record_skipped();
+        assert!(!progress.is_done());
+
+        progress.record_failure("/broken".into(), "boom".into());
+        assert!(progress.is_done());
+
+        let report = progress.report();
+        assert_eq!(report.rendered, 1);
+        assert_eq!(report.skipped, 1);
+        assert_eq!(
+            report.failures,
+            vec![("/broken".to_string(), "boom".to_string())]
+        );
+    }
+}
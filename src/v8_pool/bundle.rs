@@ -1,16 +1,33 @@
 //! SSR Bundle loader
 
-use std::path::Path;
+use deno_core::ModuleSpecifier;
+use std::path::{Path, PathBuf};
 use std::sync::OnceLock;
 
 use crate::error::{SsrError, SsrResult};
 
 /// Cached SSR bundle (loaded once at startup)
+///
+/// For ES module bundles this only holds the polyfills, which run as a
+/// classic script before the module is evaluated.
 static SSR_BUNDLE: OnceLock<String> = OnceLock::new();
 
-/// Browser polyfills for V8 compatibility
-/// These mock browser APIs that don't exist in V8 isolates
-const BROWSER_POLYFILLS: &str = r#"
+/// Cached ES module bundle (only set for module bundles)
+static SSR_MODULE: OnceLock<ModuleBundle> = OnceLock::new();
+
+/// Directory that bundle imports are resolved against
+static BUNDLE_ROOT: OnceLock<PathBuf> = OnceLock::new();
+
+/// ES module bundle loaded as the main module of each isolate
+pub(crate) struct ModuleBundle {
+    /// `file://` specifier of the bundle
+    pub specifier: ModuleSpecifier,
+    /// Module source code
+    pub code: String,
+}
+
+/// Base globals, always installed unless the bundle is loaded raw
+const POLYFILL_GLOBALS: &str = r#"
 // =========================================
 // Rusty-SSR Browser Polyfills
 // =========================================
@@ -19,6 +36,18 @@ const BROWSER_POLYFILLS: &str = r#"
 globalThis.window = globalThis;
 globalThis.self = globalThis;
 
+// Console (ensure it exists)
+globalThis.console = globalThis.console || {
+    log: () => {},
+    warn: () => {},
+    error: () => {},
+    info: () => {},
+    debug: () => {}
+};
+"#;
+
+/// Timers and animation frames
+const POLYFILL_TIMERS: &str = r#"
 // Minimal timers (no real scheduling; executes immediately)
 let __rustyTimerId = 0;
 globalThis.setTimeout = (cb, _ms, ...args) => {
@@ -34,6 +63,13 @@ globalThis.setInterval = (cb, _ms, ...args) => {
 };
 globalThis.clearInterval = () => {};
 
+// Animation frame mocks
+globalThis.requestAnimationFrame = (cb) => setTimeout(cb, 16);
+globalThis.cancelAnimationFrame = (id) => clearTimeout(id);
+"#;
+
+/// `document`, `navigator`, `location`, `performance` and `Image`
+const POLYFILL_DOM: &str = r#"
 // Document mock
 globalThis.document = {
     createElement: (tag) => ({
@@ -85,10 +121,6 @@ globalThis.location = {
     hash: ''
 };
 
-// Animation frame mocks
-globalThis.requestAnimationFrame = (cb) => setTimeout(cb, 16);
-globalThis.cancelAnimationFrame = (id) => clearTimeout(id);
-
 // Performance mock
 globalThis.performance = {
     now: () => Date.now(),
@@ -98,6 +130,18 @@ globalThis.performance = {
     getEntriesByType: () => []
 };
 
+// Image mock
+globalThis.Image = class Image {
+    constructor() {
+        this.src = '';
+        this.onload = null;
+        this.onerror = null;
+    }
+};
+"#;
+
+/// `localStorage` and `sessionStorage`
+const POLYFILL_STORAGE: &str = r#"
 // Storage mock
 const createStorage = () => {
     const data = {};
@@ -112,12 +156,18 @@ const createStorage = () => {
 };
 globalThis.localStorage = createStorage();
 globalThis.sessionStorage = createStorage();
+"#;
 
+/// `fetch` stub that points bundle code at the render data instead
+const POLYFILL_FETCH: &str = r#"
 // Fetch mock (minimal - throws if actually used)
 globalThis.fetch = async () => {
     throw new Error('fetch() is not available in SSR. Use data prop instead.');
 };
+"#;
 
+/// `MutationObserver`, `ResizeObserver` and `IntersectionObserver`
+const POLYFILL_OBSERVERS: &str = r#"
 // MutationObserver mock
 globalThis.MutationObserver = class MutationObserver {
     constructor() {}
@@ -141,7 +191,10 @@ globalThis.IntersectionObserver = class IntersectionObserver {
     unobserve() {}
     disconnect() {}
 };
+"#;
 
+/// `matchMedia`
+const POLYFILL_MATCH_MEDIA: &str = r#"
 // matchMedia mock
 globalThis.matchMedia = (query) => ({
     matches: false,
@@ -153,26 +206,148 @@ globalThis.matchMedia = (query) => ({
     removeEventListener: () => {},
     dispatchEvent: () => false
 });
+"#;
 
-// Image mock
-globalThis.Image = class Image {
-    constructor() {
-        this.src = '';
-        this.onload = null;
-        this.onerror = null;
+/// Web platform APIs backed by `Deno.core` and the ops in [`super::web`]
+const POLYFILL_WEB_APIS: &str = include_str!("js/web_apis.js");
+
+/// Set of browser polyfills installed before the bundle runs
+///
+/// Every module is enabled by default. The `window`/`self`/`console` aliases
+/// are always installed; use [`init_bundle_raw`] to skip polyfills entirely.
+///
+/// # Example
+/// ```rust
+/// use rusty_ssr::v8_pool::Polyfills;
+///
+/// let polyfills = Polyfills::default()
+///     .timers(false)
+///     .shim("globalThis.__APP_VERSION__ = '1.2.3';");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Polyfills {
+    dom: bool,
+    storage: bool,
+    observers: bool,
+    timers: bool,
+    match_media: bool,
+    fetch: bool,
+    web_apis: bool,
+    shims: Vec<String>,
+}
+
+impl Default for Polyfills {
+    fn default() -> Self {
+        Self::all()
     }
-};
+}
 
-// Console (ensure it exists)
-globalThis.console = globalThis.console || {
-    log: () => {},
-    warn: () => {},
-    error: () => {},
-    info: () => {},
-    debug: () => {}
-};
+impl Polyfills {
+    /// All built-in polyfill modules
+    pub fn all() -> Self {
+        Self {
+            dom: true,
+            storage: true,
+            observers: true,
+            timers: true,
+            match_media: true,
+            fetch: true,
+            web_apis: true,
+            shims: Vec::new(),
+        }
+    }
 
-"#;
+    /// Only the `window`/`self`/`console` aliases
+    pub fn none() -> Self {
+        Self {
+            dom: false,
+            storage: false,
+            observers: false,
+            timers: false,
+            match_media: false,
+            fetch: false,
+            web_apis: false,
+            shims: Vec::new(),
+        }
+    }
+
+    /// `document`, `navigator`, `location`, `performance` and `Image` mocks
+    pub fn dom(mut self, enabled: bool) -> Self {
+        self.dom = enabled;
+        self
+    }
+
+    /// In-memory `localStorage` and `sessionStorage`
+    pub fn storage(mut self, enabled: bool) -> Self {
+        self.storage = enabled;
+        self
+    }
+
+    /// `MutationObserver`, `ResizeObserver` and `IntersectionObserver` no-ops
+    pub fn observers(mut self, enabled: bool) -> Self {
+        self.observers = enabled;
+        self
+    }
+
+    /// `setTimeout`/`setInterval` (run immediately) and `requestAnimationFrame`
+    pub fn timers(mut self, enabled: bool) -> Self {
+        self.timers = enabled;
+        self
+    }
+
+    /// `matchMedia` that never matches
+    pub fn match_media(mut self, enabled: bool) -> Self {
+        self.match_media = enabled;
+        self
+    }
+
+    /// `fetch` stub that rejects and points to the render data instead
+    pub fn fetch(mut self, enabled: bool) -> Self {
+        self.fetch = enabled;
+        self
+    }
+
+    /// `URL`/`URLSearchParams`, `TextEncoder`/`TextDecoder`, `atob`/`btoa`,
+    /// `crypto.getRandomValues`/`randomUUID`, `structuredClone`,
+    /// `queueMicrotask`, `EventTarget`, `AbortController` and `DOMException`
+    pub fn web_apis(mut self, enabled: bool) -> Self {
+        self.web_apis = enabled;
+        self
+    }
+
+    /// Append a user-supplied JS shim
+    ///
+    /// Shims run after the built-in modules, in the order they were added.
+    pub fn shim<S: Into<String>>(mut self, code: S) -> Self {
+        self.shims.push(code.into());
+        self
+    }
+
+    /// Concatenate the enabled modules into a single script
+    pub fn to_script(&self) -> String {
+        let modules = [
+            (true, POLYFILL_GLOBALS),
+            (self.timers, POLYFILL_TIMERS),
+            (self.dom, POLYFILL_DOM),
+            (self.storage, POLYFILL_STORAGE),
+            (self.fetch, POLYFILL_FETCH),
+            (self.observers, POLYFILL_OBSERVERS),
+            (self.match_media, POLYFILL_MATCH_MEDIA),
+            (self.web_apis, POLYFILL_WEB_APIS),
+        ];
+
+        let mut script = String::new();
+        for (_, code) in modules.iter().filter(|(enabled, _)| *enabled) {
+            script.push_str(code);
+        }
+        for shim in &self.shims {
+            script.push('\n');
+            script.push_str(shim);
+            script.push('\n');
+        }
+        script
+    }
+}
 
 /// Initialize the SSR bundle from a file
 ///
@@ -180,6 +355,14 @@ globalThis.console = globalThis.console || {
 /// The bundle is cached and reused for all V8 workers.
 /// Browser polyfills are automatically prepended.
 pub fn init_bundle<P: AsRef<Path>>(path: P) -> SsrResult<()> {
+    init_bundle_with_polyfills(path, &Polyfills::default())
+}
+
+/// Initialize the SSR bundle from a file with a custom polyfill set
+pub fn init_bundle_with_polyfills<P: AsRef<Path>>(
+    path: P,
+    polyfills: &Polyfills,
+) -> SsrResult<()> {
     let path = path.as_ref();
 
     if SSR_BUNDLE.get().is_some() {
@@ -192,12 +375,61 @@ pub fn init_bundle<P: AsRef<Path>>(path: P) -> SsrResult<()> {
         SsrError::BundleLoad(format!("Failed to read SSR bundle from {:?}: {}", path, e))
     })?;
 
-    let full_bundle = format!("{}\n{}", BROWSER_POLYFILLS, user_bundle);
+    let full_bundle = format!("{}\n{}", polyfills.to_script(), user_bundle);
 
     SSR_BUNDLE
         .set(full_bundle)
         .map_err(|_| SsrError::BundleLoad("Bundle already initialized".to_string()))?;
 
+    if let Ok(root) = bundle_dir(path) {
+        let _ = BUNDLE_ROOT.set(root);
+    }
+
+    Ok(())
+}
+
+/// Initialize an ES module SSR bundle from a file
+///
+/// The module is loaded as the main module of every V8 isolate, and its
+/// static and dynamic imports are resolved relative to the bundle directory.
+/// Browser polyfills run as a classic script before the module is evaluated.
+pub fn init_module_bundle<P: AsRef<Path>>(path: P) -> SsrResult<()> {
+    init_module_bundle_with_polyfills(path, &Polyfills::default())
+}
+
+/// Initialize an ES module SSR bundle from a file with a custom polyfill set
+pub fn init_module_bundle_with_polyfills<P: AsRef<Path>>(
+    path: P,
+    polyfills: &Polyfills,
+) -> SsrResult<()> {
+    let path = path.as_ref();
+
+    if SSR_BUNDLE.get().is_some() {
+        return Ok(());
+    }
+
+    tracing::info!("📦 Loading SSR module bundle from {:?}", path);
+
+    let code = std::fs::read_to_string(path).map_err(|e| {
+        SsrError::BundleLoad(format!("Failed to read SSR bundle from {:?}: {}", path, e))
+    })?;
+
+    let absolute = path.canonicalize().map_err(|e| {
+        SsrError::BundleLoad(format!(
+            "Failed to resolve SSR bundle path {:?}: {}",
+            path, e
+        ))
+    })?;
+    let specifier = ModuleSpecifier::from_file_path(&absolute)
+        .map_err(|_| SsrError::BundleLoad(format!("Invalid SSR bundle path: {:?}", absolute)))?;
+    let root = bundle_dir(&absolute)?;
+
+    SSR_BUNDLE
+        .set(polyfills.to_script())
+        .map_err(|_| SsrError::BundleLoad("Bundle already initialized".to_string()))?;
+    let _ = SSR_MODULE.set(ModuleBundle { specifier, code });
+    let _ = BUNDLE_ROOT.set(root);
+
     Ok(())
 }
 
@@ -206,7 +438,7 @@ pub fn init_bundle<P: AsRef<Path>>(path: P) -> SsrResult<()> {
 /// Use this if you want to embed the bundle or load it from elsewhere.
 /// Browser polyfills are automatically prepended.
 pub fn init_bundle_from_string(bundle: String) -> SsrResult<()> {
-    let full_bundle = format!("{}\n{}", BROWSER_POLYFILLS, bundle);
+    let full_bundle = format!("{}\n{}", Polyfills::default().to_script(), bundle);
     SSR_BUNDLE
         .set(full_bundle)
         .map_err(|_| SsrError::BundleLoad("Bundle already initialized".to_string()))?;
@@ -233,7 +465,72 @@ pub fn get_bundle() -> &'static str {
         .expect("SSR bundle not initialized. Call init_bundle() first.")
 }
 
+/// Get the ES module bundle, if the bundle was loaded as a module
+pub(crate) fn get_module() -> Option<&'static ModuleBundle> {
+    SSR_MODULE.get()
+}
+
+/// Get the directory that bundle imports are resolved against
+///
+/// `None` for bundles initialized from a string.
+pub(crate) fn bundle_root() -> Option<&'static Path> {
+    BUNDLE_ROOT.get().map(PathBuf::as_path)
+}
+
 /// Check if the bundle is initialized
 pub fn is_initialized() -> bool {
     SSR_BUNDLE.get().is_some()
 }
+
+/// Canonical directory containing the bundle file
+fn bundle_dir(path: &Path) -> SsrResult<PathBuf> {
+    path.canonicalize()
+        .ok()
+        .and_then(|p| p.parent().map(Path::to_path_buf))
+        .ok_or_else(|| {
+            SsrError::BundleLoad(format!("Failed to resolve bundle directory of {:?}", path))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_polyfills_include_all_modules() {
+        let script = Polyfills::default().to_script();
+        assert!(script.contains("globalThis.window = globalThis"));
+        assert!(script.contains("globalThis.document"));
+        assert!(script.contains("globalThis.localStorage"));
+        assert!(script.contains("globalThis.setTimeout"));
+        assert!(script.contains("globalThis.matchMedia"));
+        assert!(script.contains("globalThis.fetch"));
+        assert!(script.contains("globalThis.MutationObserver"));
+        assert!(script.contains("globalThis.TextEncoder"));
+    }
+
+    #[test]
+    fn test_disabled_modules_are_omitted() {
+        let script = Polyfills::default()
+            .storage(false)
+            .timers(false)
+            .to_script();
+        assert!(!script.contains("globalThis.localStorage"));
+        assert!(!script.contains("globalThis.setTimeout"));
+        assert!(script.contains("globalThis.document"));
+    }
+
+    #[test]
+    fn test_none_keeps_base_globals_and_shims() {
+        let script = Polyfills::none()
+            .shim("globalThis.first = 1;")
+            .shim("globalThis.second = 2;")
+            .to_script();
+        assert!(script.contains("globalThis.window = globalThis"));
+        assert!(!script.contains("globalThis.document"));
+
+        let first = script.find("globalThis.first").unwrap();
+        let second = script.find("globalThis.second").unwrap();
+        assert!(first < second, "shims run in insertion order");
+    }
+}
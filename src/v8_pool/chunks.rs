@@ -0,0 +1,364 @@
+//! Code-split chunk graph of an ES module bundle
+//!
+//! Built once per process by scanning the `import` declarations and
+//! `import()` calls of the modules reachable from the bundle entry. Workers
+//! pre-load only the chunks the entry can actually import, and report the
+//! chunks a lazy chunk imports statically together with it.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use super::bundle;
+
+/// Chunk graph of the module bundle, built on first use
+static CHUNK_GRAPH: OnceLock<ChunkGraph> = OnceLock::new();
+
+/// Get the chunk graph, if the bundle was loaded as a module
+pub(crate) fn chunk_graph() -> Option<&'static ChunkGraph> {
+    let module = bundle::get_module()?;
+    let root = bundle::bundle_root()?;
+    let entry = module.specifier.to_file_path().ok()?;
+
+    Some(CHUNK_GRAPH.get_or_init(|| ChunkGraph::build(root, &entry, &module.code)))
+}
+
+/// Modules reachable from the bundle entry, by path relative to the bundle directory
+#[derive(Debug, Default)]
+pub(crate) struct ChunkGraph {
+    /// Chunks loaded with `import()`, in discovery order
+    lazy: Vec<String>,
+    /// Static imports of each module
+    imports: HashMap<String, Vec<String>>,
+    /// Entry and the modules it imports statically, always loaded
+    eager: HashSet<String>,
+}
+
+impl ChunkGraph {
+    /// Scan the modules reachable from `entry`, whose source is `code`
+    ///
+    /// Only relative specifiers of files inside `root` are followed; bare and
+    /// remote imports are left to the module loader.
+    pub fn build(root: &Path, entry: &Path, code: &str) -> Self {
+        let mut graph = Self::default();
+        let Some(entry_name) = relative_name(root, entry) else {
+            return graph;
+        };
+
+        let mut seen = HashSet::from([entry_name.clone()]);
+        let mut queue =
+            VecDeque::from([(entry_name.clone(), entry.to_path_buf(), code.to_string())]);
+        while let Some((name, path, code)) = queue.pop_front() {
+            let (static_imports, dynamic_imports) = scan_imports(&code);
+
+            for (specifier, lazy) in static_imports
+                .iter()
+                .map(|s| (s, false))
+                .chain(dynamic_imports.iter().map(|s| (s, true)))
+            {
+                let Some((import, import_path)) = resolve(root, &path, specifier) else {
+                    continue;
+                };
+
+                if lazy {
+                    if !graph.lazy.contains(&import) {
+                        graph.lazy.push(import.clone());
+                    }
+                } else {
+                    let imports = graph.imports.entry(name.clone()).or_default();
+                    if !imports.contains(&import) {
+                        imports.push(import.clone());
+                    }
+                }
+
+                if seen.insert(import.clone()) {
+                    match std::fs::read_to_string(&import_path) {
+                        Ok(code) => queue.push_back((import, import_path, code)),
+                        Err(e) => tracing::debug!("Failed to scan chunk {}: {}", import, e),
+                    }
+                }
+            }
+        }
+
+        graph.eager = graph.static_closure(&entry_name);
+        graph
+    }
+
+    /// Chunks loaded with `import()`, relative to the bundle directory
+    pub fn lazy_chunks(&self) -> &[String] {
+        &self.lazy
+    }
+
+    /// `chunks` followed by the chunks they import statically, transitively
+    ///
+    /// Modules the entry imports statically are left out, as they are part
+    /// of every page.
+    pub fn with_static_imports(&self, chunks: Vec<String>) -> Vec<String> {
+        let mut all = chunks;
+        let mut next = 0;
+        while next < all.len() {
+            for import in self.imports.get(&all[next]).into_iter().flatten() {
+                if !self.eager.contains(import) && !all.contains(import) {
+                    all.push(import.clone());
+                }
+            }
+            next += 1;
+        }
+        all
+    }
+
+    /// `module` and the modules it imports statically, transitively
+    fn static_closure(&self, module: &str) -> HashSet<String> {
+        let mut closure = HashSet::from([module.to_string()]);
+        let mut stack = vec![module];
+        while let Some(module) = stack.pop() {
+            for import in self.imports.get(module).into_iter().flatten() {
+                if closure.insert(import.clone()) {
+                    stack.push(import);
+                }
+            }
+        }
+        closure
+    }
+}
+
+/// Resolve a relative import of `referrer` to a file inside `root`
+fn resolve(root: &Path, referrer: &Path, specifier: &str) -> Option<(String, PathBuf)> {
+    if !(specifier.starts_with("./") || specifier.starts_with("../") || specifier.starts_with('/'))
+    {
+        return None;
+    }
+
+    let path = referrer.parent()?.join(specifier).canonicalize().ok()?;
+    let name = relative_name(root, &path)?;
+    Some((name, path))
+}
+
+/// Path of `path` relative to `root`, with `/` separators
+fn relative_name(root: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(root).ok()?;
+    Some(relative.to_string_lossy().replace('\\', "/"))
+}
+
+/// Specifiers of the static imports and of the `import()` calls in `source`
+///
+/// A lexical scan rather than a parse: comments, strings and regular
+/// expression literals are skipped, and only string literal specifiers are
+/// found. `export ... from` counts as a static import.
+fn scan_imports(source: &str) -> (Vec<String>, Vec<String>) {
+    let bytes = source.as_bytes();
+    let mut static_imports = Vec::new();
+    let mut dynamic_imports = Vec::new();
+    // Whether a `/` at this point starts a regular expression
+    let mut expect_operand = true;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                i = find(bytes, i + 2, b"\n").unwrap_or(bytes.len());
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i = find(bytes, i + 2, b"*/").map_or(bytes.len(), |end| end + 2);
+            }
+            b'/' if expect_operand => {
+                i = skip_regex(bytes, i);
+                expect_operand = false;
+            }
+            b'\'' | b'"' | b'`' => {
+                i = skip_string(bytes, i).1;
+                expect_operand = false;
+            }
+            c if is_identifier(c) => {
+                let start = i;
+                while i < bytes.len() && is_identifier(bytes[i]) {
+                    i += 1;
+                }
+                let word = &source[start..i];
+                let member = start > 0 && bytes[start - 1] == b'.';
+
+                if !member {
+                    let next = skip_whitespace(bytes, i);
+                    match word {
+                        "import" if bytes.get(next) == Some(&b'(') => {
+                            let arg = skip_whitespace(bytes, next + 1);
+                            if let Some(specifier) = string_at(bytes, arg) {
+                                dynamic_imports.push(specifier);
+                            }
+                        }
+                        "import" | "from" => {
+                            if let Some(specifier) = string_at(bytes, next) {
+                                static_imports.push(specifier);
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                expect_operand = !member && KEYWORDS_BEFORE_OPERAND.contains(&word);
+            }
+            c if c.is_ascii_whitespace() => i += 1,
+            c => {
+                expect_operand = !matches!(c, b')' | b']' | b'}');
+                i += 1;
+            }
+        }
+    }
+
+    (static_imports, dynamic_imports)
+}
+
+/// Keywords after which a `/` starts a regular expression, not a division
+const KEYWORDS_BEFORE_OPERAND: &[&str] = &[
+    "return",
+    "typeof",
+    "instanceof",
+    "in",
+    "of",
+    "new",
+    "delete",
+    "void",
+    "throw",
+    "case",
+    "do",
+    "else",
+    "yield",
+    "await",
+];
+
+fn is_identifier(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || c == b'_' || c == b'$' || c >= 0x80
+}
+
+fn skip_whitespace(bytes: &[u8], mut i: usize) -> usize {
+    while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+        i += 1;
+    }
+    i
+}
+
+/// Index of the first `needle` at or after `from`
+fn find(bytes: &[u8], from: usize, needle: &[u8]) -> Option<usize> {
+    bytes
+        .get(from..)?
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|position| from + position)
+}
+
+/// Contents of the string literal starting at `i`, if any
+fn string_at(bytes: &[u8], i: usize) -> Option<String> {
+    if !matches!(bytes.get(i), Some(b'\'' | b'"' | b'`')) {
+        return None;
+    }
+    let (contents, _) = skip_string(bytes, i);
+    // Template literals with substitutions are not static specifiers
+    let contents = std::str::from_utf8(contents?).ok()?;
+    (!contents.contains("${")).then(|| contents.to_string())
+}
+
+/// Skip the string literal starting at `start`, returning its contents
+/// (`None` if unterminated) and the index after it
+fn skip_string(bytes: &[u8], start: usize) -> (Option<&[u8]>, usize) {
+    let quote = bytes[start];
+    let mut i = start + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'\n' if quote != b'`' => return (None, i),
+            c if c == quote => return (Some(&bytes[start + 1..i]), i + 1),
+            _ => i += 1,
+        }
+    }
+    (None, bytes.len())
+}
+
+/// Skip the regular expression literal starting at `start`
+fn skip_regex(bytes: &[u8], start: usize) -> usize {
+    let mut in_class = false;
+    let mut i = start + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'\n' => return i,
+            b'[' => in_class = true,
+            b']' => in_class = false,
+            b'/' if !in_class => return i + 1,
+            _ => {}
+        }
+        i += 1;
+    }
+    bytes.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_imports() {
+        let source = r#"
+            import { a } from './a.js';
+            import"./side-effect.js";
+            export * from "./b.js";
+            // import('./commented.js')
+            /* import('./also-commented.js') */
+            const text = "import('./in-string.js')";
+            const re = /['"]/g, ratio = 1 / 2;
+            const Lazy = () => import( './lazy.js' );
+            const dynamic = (name) => import(`./pages/${name}.js`);
+            router.import('./not-an-import.js');
+        "#;
+
+        let (static_imports, dynamic_imports) = scan_imports(source);
+        assert_eq!(static_imports, ["./a.js", "./side-effect.js", "./b.js"]);
+        assert_eq!(dynamic_imports, ["./lazy.js"]);
+    }
+
+    #[test]
+    fn test_scan_minified_imports() {
+        let source = r#"import{h as e}from"./preact.js";const t=()=>import("./Lazy-3f2a.js");"#;
+
+        let (static_imports, dynamic_imports) = scan_imports(source);
+        assert_eq!(static_imports, ["./preact.js"]);
+        assert_eq!(dynamic_imports, ["./Lazy-3f2a.js"]);
+    }
+
+    #[test]
+    fn test_graph_follows_reachable_modules_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        std::fs::create_dir_all(root.join("chunks")).unwrap();
+        let entry_code = "import './lib.js'; export const load = () => import('./chunks/Lazy.js');";
+        std::fs::write(root.join("entry.mjs"), entry_code).unwrap();
+        std::fs::write(root.join("lib.js"), "export const lib = 1;").unwrap();
+        std::fs::write(
+            root.join("chunks/Lazy.js"),
+            "import './shared.js'; import '../lib.js'; export const Lazy = 1;",
+        )
+        .unwrap();
+        std::fs::write(root.join("chunks/shared.js"), "export const shared = 1;").unwrap();
+        std::fs::write(root.join("unrelated.js"), "globalThis.sideEffect = true;").unwrap();
+
+        let graph = ChunkGraph::build(&root, &root.join("entry.mjs"), entry_code);
+
+        assert_eq!(graph.lazy_chunks(), ["chunks/Lazy.js"]);
+        assert_eq!(
+            graph.with_static_imports(vec!["chunks/Lazy.js".to_string()]),
+            ["chunks/Lazy.js", "chunks/shared.js"]
+        );
+        assert!(graph.with_static_imports(Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn test_graph_ignores_imports_outside_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap().join("bundle");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.parent().unwrap().join("outside.js"), "").unwrap();
+        let entry_code = "import('../outside.js'); import('preact'); import('./missing.js');";
+
+        let graph = ChunkGraph::build(&root, &root.join("entry.mjs"), entry_code);
+
+        assert!(graph.lazy_chunks().is_empty());
+    }
+}
@@ -0,0 +1,60 @@
+
+// Per-render global state reset (RenderIsolation::ResetGlobals)
+(() => {
+    const POLYFILL_OBJECTS = ['document', 'navigator', 'location', 'performance'];
+    const STORAGES = ['localStorage', 'sessionStorage'];
+
+    const snapshot = (target) => {
+        const props = new Map();
+        for (const key of Reflect.ownKeys(target)) {
+            props.set(key, Object.getOwnPropertyDescriptor(target, key));
+        }
+        return props;
+    };
+
+    const sameDescriptor = (a, b) =>
+        Object.is(a.value, b.value) && a.get === b.get && a.set === b.set &&
+        a.writable === b.writable && a.enumerable === b.enumerable;
+
+    // Remove added properties and restore changed ones, collecting their names
+    const restore = (target, baseline, prefix, mutated) => {
+        for (const key of Reflect.ownKeys(target)) {
+            if (!baseline.has(key)) {
+                mutated.push(prefix + String(key));
+                try { delete target[key]; } catch (_) {}
+            }
+        }
+        for (const [key, descriptor] of baseline) {
+            const current = Object.getOwnPropertyDescriptor(target, key);
+            if (!current || !sameDescriptor(current, descriptor)) {
+                mutated.push(prefix + String(key));
+                try { Object.defineProperty(target, key, descriptor); } catch (_) {}
+            }
+        }
+    };
+
+    let globals;
+    const objects = [];
+
+    Object.defineProperty(globalThis, '__rustySsrResetGlobals', {
+        value: () => {
+            const mutated = [];
+            restore(globalThis, globals, '', mutated);
+            for (const [name, target, baseline] of objects) {
+                restore(target, baseline, name + '.', mutated);
+            }
+            for (const name of STORAGES) {
+                globalThis[name]?.clear?.();
+            }
+            return mutated;
+        },
+    });
+
+    globals = snapshot(globalThis);
+    for (const name of POLYFILL_OBJECTS) {
+        const target = globalThis[name];
+        if (target && typeof target === 'object') {
+            objects.push([name, target, snapshot(target)]);
+        }
+    }
+})();
@@ -0,0 +1,36 @@
+// Tracing API for bundle code (backed by the rusty_ssr_trace ops)
+(() => {
+    const ops = globalThis.Deno?.core?.ops ?? {};
+    const encode = (attributes) => JSON.stringify(attributes ?? {});
+
+    globalThis.__rustySsrTrace = {
+        // Record an event in the innermost open span
+        event(name, attributes) {
+            ops.op_ssr_trace_event?.(String(name), encode(attributes));
+        },
+
+        // Run `fn` in a child span of the innermost open span, closed when
+        // its result settles
+        span(name, attributes, fn) {
+            if (typeof attributes === 'function') {
+                fn = attributes;
+                attributes = undefined;
+            }
+            const handle = ops.op_ssr_trace_enter?.(String(name), encode(attributes));
+            const exit = () => ops.op_ssr_trace_exit?.(handle);
+
+            let result;
+            try {
+                result = fn();
+            } catch (error) {
+                exit();
+                throw error;
+            }
+            if (result && typeof result.then === 'function') {
+                return result.finally(exit);
+            }
+            exit();
+            return result;
+        },
+    };
+})();
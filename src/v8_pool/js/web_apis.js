@@ -0,0 +1,346 @@
+
+// Web platform APIs (backed by deno_core and Rust ops)
+(() => {
+    const core = globalThis.Deno?.core;
+    const ops = core?.ops ?? {};
+
+    if (typeof globalThis.queueMicrotask !== 'function') {
+        globalThis.queueMicrotask = (cb) => {
+            if (core?.queueMicrotask) { core.queueMicrotask(cb); }
+            else { Promise.resolve().then(cb); }
+        };
+    }
+
+    // ---- TextEncoder / TextDecoder ----
+
+    if (typeof globalThis.TextEncoder !== 'function') {
+        globalThis.TextEncoder = class TextEncoder {
+            get encoding() { return 'utf-8'; }
+            encode(input = '') { return core.encode(String(input)); }
+            encodeInto(input, dest) {
+                const str = String(input);
+                // Only whole code points that fit in `dest` are encoded
+                let read = 0;
+                let written = 0;
+                for (const ch of str) {
+                    const cp = ch.codePointAt(0);
+                    const size = cp < 0x80 ? 1 : cp < 0x800 ? 2 : cp < 0x10000 ? 3 : 4;
+                    if (written + size > dest.length) break;
+                    read += ch.length;
+                    written += size;
+                }
+                dest.set(core.encode(str.slice(0, read)));
+                return { read, written };
+            }
+        };
+    }
+
+    if (typeof globalThis.TextDecoder !== 'function') {
+        globalThis.TextDecoder = class TextDecoder {
+            constructor(label = 'utf-8') {
+                if (!/^utf-?8$/i.test(label)) {
+                    throw new RangeError(`TextDecoder: unsupported encoding "${label}"`);
+                }
+            }
+            get encoding() { return 'utf-8'; }
+            decode(input) {
+                if (input === undefined) return '';
+                const bytes = input instanceof Uint8Array
+                    ? input
+                    : ArrayBuffer.isView(input)
+                        ? new Uint8Array(input.buffer, input.byteOffset, input.byteLength)
+                        : new Uint8Array(input);
+                return core.decode(bytes);
+            }
+        };
+    }
+
+    // ---- DOMException ----
+
+    if (typeof globalThis.DOMException !== 'function') {
+        globalThis.DOMException = class DOMException extends Error {
+            #name;
+            constructor(message = '', name = 'Error') {
+                super(message);
+                this.#name = String(name);
+            }
+            get name() { return this.#name; }
+        };
+    }
+
+    // ---- atob / btoa ----
+
+    const B64 = 'ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/';
+
+    const invalidCharacter = (msg) => new DOMException(msg, 'InvalidCharacterError');
+
+    if (typeof globalThis.btoa !== 'function') {
+        globalThis.btoa = (input) => {
+            const str = String(input);
+            let out = '';
+            for (let i = 0; i < str.length; i += 3) {
+                const a = str.charCodeAt(i);
+                const b = str.charCodeAt(i + 1);
+                const c = str.charCodeAt(i + 2);
+                if (a > 0xff || b > 0xff || c > 0xff) {
+                    throw invalidCharacter('btoa: string contains characters outside of the Latin1 range');
+                }
+                const triple = (a << 16) | ((b || 0) << 8) | (c || 0);
+                out += B64[(triple >> 18) & 63] + B64[(triple >> 12) & 63];
+                out += i + 1 < str.length ? B64[(triple >> 6) & 63] : '=';
+                out += i + 2 < str.length ? B64[triple & 63] : '=';
+            }
+            return out;
+        };
+    }
+
+    if (typeof globalThis.atob !== 'function') {
+        globalThis.atob = (input) => {
+            let str = String(input).replace(/[\t\n\f\r ]/g, '');
+            if (str.length % 4 === 0) str = str.replace(/==?$/, '');
+            if (str.length % 4 === 1 || /[^A-Za-z0-9+/]/.test(str)) {
+                throw invalidCharacter('atob: the string to be decoded is not correctly encoded');
+            }
+            let out = '';
+            let buffer = 0;
+            let bits = 0;
+            for (const ch of str) {
+                buffer = (buffer << 6) | B64.indexOf(ch);
+                bits += 6;
+                if (bits >= 8) {
+                    bits -= 8;
+                    out += String.fromCharCode((buffer >> bits) & 0xff);
+                }
+            }
+            return out;
+        };
+    }
+
+    // ---- crypto ----
+
+    if (typeof globalThis.crypto?.getRandomValues !== 'function') {
+        const getRandomValues = (array) => {
+            if (!ArrayBuffer.isView(array) || array instanceof Float32Array || array instanceof Float64Array) {
+                throw new TypeError('getRandomValues: argument must be an integer TypedArray');
+            }
+            ops.op_ssr_random_fill(new Uint8Array(array.buffer, array.byteOffset, array.byteLength));
+            return array;
+        };
+
+        const randomUUID = () => {
+            const bytes = getRandomValues(new Uint8Array(16));
+            bytes[6] = (bytes[6] & 0x0f) | 0x40;
+            bytes[8] = (bytes[8] & 0x3f) | 0x80;
+            const hex = Array.from(bytes, (b) => b.toString(16).padStart(2, '0')).join('');
+            return `${hex.slice(0, 8)}-${hex.slice(8, 12)}-${hex.slice(12, 16)}-${hex.slice(16, 20)}-${hex.slice(20)}`;
+        };
+
+        globalThis.crypto = { ...(globalThis.crypto ?? {}), getRandomValues, randomUUID };
+    }
+
+    // ---- URLSearchParams / URL ----
+
+    const formDecode = (s) => decodeURIComponent(s.replace(/\+/g, ' '));
+    const formEncode = (s) => encodeURIComponent(s)
+        .replace(/%20/g, '+')
+        .replace(/[!'()~]/g, (c) => '%' + c.charCodeAt(0).toString(16).toUpperCase());
+
+    // Links a URL to its `searchParams`, kept out of the public API
+    let attachParams = () => {};
+    let reparseParams = () => {};
+
+    if (typeof globalThis.URLSearchParams !== 'function') {
+        class URLSearchParams {
+            #list = [];
+            #onUpdate = null;
+
+            static {
+                attachParams = (params, onUpdate) => { params.#onUpdate = onUpdate; };
+                reparseParams = (params, query) => { params.#parse(query); };
+            }
+
+            constructor(init = '') {
+                if (typeof init === 'string') {
+                    this.#parse(init);
+                } else if (init instanceof URLSearchParams) {
+                    this.#list = [...init];
+                } else if (init && typeof init[Symbol.iterator] === 'function') {
+                    for (const pair of init) {
+                        if (pair.length !== 2) throw new TypeError('URLSearchParams: each pair must have two items');
+                        this.#list.push([String(pair[0]), String(pair[1])]);
+                    }
+                } else if (init && typeof init === 'object') {
+                    for (const key of Object.keys(init)) this.#list.push([key, String(init[key])]);
+                }
+            }
+
+            #parse(query) {
+                this.#list = [];
+                for (const part of query.replace(/^\?/, '').split('&')) {
+                    if (!part) continue;
+                    const eq = part.indexOf('=');
+                    const name = eq === -1 ? part : part.slice(0, eq);
+                    const value = eq === -1 ? '' : part.slice(eq + 1);
+                    this.#list.push([formDecode(name), formDecode(value)]);
+                }
+            }
+
+            #update() {
+                if (this.#onUpdate) this.#onUpdate(this.toString());
+            }
+
+            get size() { return this.#list.length; }
+            append(name, value) { this.#list.push([String(name), String(value)]); this.#update(); }
+            delete(name) { this.#list = this.#list.filter(([k]) => k !== String(name)); this.#update(); }
+            get(name) { return this.#list.find(([k]) => k === String(name))?.[1] ?? null; }
+            getAll(name) { return this.#list.filter(([k]) => k === String(name)).map(([, v]) => v); }
+            has(name) { return this.#list.some(([k]) => k === String(name)); }
+            set(name, value) {
+                name = String(name);
+                const index = this.#list.findIndex(([k]) => k === name);
+                if (index === -1) {
+                    this.#list.push([name, String(value)]);
+                } else {
+                    this.#list[index][1] = String(value);
+                    this.#list = this.#list.filter(([k], i) => k !== name || i === index);
+                }
+                this.#update();
+            }
+            sort() {
+                this.#list.sort(([a], [b]) => (a < b ? -1 : a > b ? 1 : 0));
+                this.#update();
+            }
+            forEach(cb, thisArg) { for (const [k, v] of this.#list) cb.call(thisArg, v, k, this); }
+            keys() { return this.#list.map(([k]) => k)[Symbol.iterator](); }
+            values() { return this.#list.map(([, v]) => v)[Symbol.iterator](); }
+            entries() { return this.#list.map(([k, v]) => [k, v])[Symbol.iterator](); }
+            [Symbol.iterator]() { return this.entries(); }
+            toString() { return this.#list.map(([k, v]) => `${formEncode(k)}=${formEncode(v)}`).join('&'); }
+        }
+        globalThis.URLSearchParams = URLSearchParams;
+    }
+
+    if (typeof globalThis.URL !== 'function') {
+        const parse = (href, base) => {
+            try {
+                return ops.op_ssr_url_parse(String(href), base === undefined ? null : String(base));
+            } catch (error) {
+                throw new TypeError(error.message);
+            }
+        };
+
+        class URL {
+            #parts;
+            #searchParams;
+
+            constructor(url, base) {
+                this.#parts = parse(url, base);
+                this.#searchParams = new URLSearchParams(this.#parts.search);
+                attachParams(this.#searchParams, (query) => {
+                    this.#parts = ops.op_ssr_url_set(this.#parts.href, 'search', query);
+                });
+            }
+
+            static canParse(url, base) {
+                try { parse(url, base); return true; } catch { return false; }
+            }
+
+            static parse(url, base) {
+                try { return new URL(url, base); } catch { return null; }
+            }
+
+            #set(component, value) {
+                this.#parts = ops.op_ssr_url_set(this.#parts.href, component, String(value));
+                if (component !== 'hash') {
+                    reparseParams(this.#searchParams, this.#parts.search);
+                }
+            }
+
+            get href() { return this.#parts.href; }
+            set href(v) {
+                try { this.#set('href', v); } catch (error) { throw new TypeError(error.message); }
+            }
+            get origin() { return this.#parts.origin; }
+            get protocol() { return this.#parts.protocol; }
+            set protocol(v) { this.#set('protocol', v); }
+            get username() { return this.#parts.username; }
+            set username(v) { this.#set('username', v); }
+            get password() { return this.#parts.password; }
+            set password(v) { this.#set('password', v); }
+            get host() { return this.#parts.host; }
+            set host(v) { this.#set('host', v); }
+            get hostname() { return this.#parts.hostname; }
+            set hostname(v) { this.#set('hostname', v); }
+            get port() { return this.#parts.port; }
+            set port(v) { this.#set('port', v); }
+            get pathname() { return this.#parts.pathname; }
+            set pathname(v) { this.#set('pathname', v); }
+            get search() { return this.#parts.search; }
+            set search(v) { this.#set('search', v); }
+            get hash() { return this.#parts.hash; }
+            set hash(v) { this.#set('hash', v); }
+            get searchParams() { return this.#searchParams; }
+            toString() { return this.#parts.href; }
+            toJSON() { return this.#parts.href; }
+        }
+        globalThis.URL = URL;
+    }
+
+    // ---- structuredClone ----
+
+    if (typeof globalThis.structuredClone !== 'function') {
+        globalThis.structuredClone = (value) => core.deserialize(core.serialize(value));
+    }
+
+    // ---- EventTarget / AbortController ----
+
+    if (typeof globalThis.EventTarget !== 'function') {
+        globalThis.EventTarget = class EventTarget {
+            #listeners = new Map();
+            addEventListener(type, listener) {
+                if (!listener) return;
+                if (!this.#listeners.has(type)) this.#listeners.set(type, new Set());
+                this.#listeners.get(type).add(listener);
+            }
+            removeEventListener(type, listener) {
+                this.#listeners.get(type)?.delete(listener);
+            }
+            dispatchEvent(event) {
+                for (const listener of this.#listeners.get(event.type) ?? []) {
+                    if (typeof listener === 'function') listener.call(this, event);
+                    else listener.handleEvent(event);
+                }
+                return true;
+            }
+        };
+    }
+
+    if (typeof globalThis.AbortController !== 'function') {
+        class AbortSignal extends EventTarget {
+            aborted = false;
+            reason = undefined;
+            onabort = null;
+            throwIfAborted() { if (this.aborted) throw this.reason; }
+            static abort(reason) {
+                const controller = new AbortController();
+                controller.abort(reason);
+                return controller.signal;
+            }
+        }
+        globalThis.AbortSignal = AbortSignal;
+        globalThis.AbortController = class AbortController {
+            signal = new AbortSignal();
+            abort(reason) {
+                const signal = this.signal;
+                if (signal.aborted) return;
+                signal.aborted = true;
+                signal.reason = reason === undefined
+                    ? new DOMException('This operation was aborted', 'AbortError')
+                    : reason;
+                const event = { type: 'abort', target: signal };
+                if (typeof signal.onabort === 'function') signal.onabort(event);
+                signal.dispatchEvent(event);
+            }
+        };
+    }
+})();
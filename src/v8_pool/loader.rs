@@ -0,0 +1,339 @@
+//! Sandboxed module loader for SSR isolates
+//!
+//! Only serves modules from the bundle directory, explicitly allowed
+//! directories, or the in-memory module map. Everything else (files outside
+//! the sandbox, `http(s):` and `node:` specifiers) is rejected with an
+//! [`SsrError`], so bundle code cannot `import()` arbitrary files from the server.
+//!
+//! Dynamic imports of code-split chunks from the bundle directory are recorded
+//! per thread, so the worker can report which chunks a page used.
+
+use deno_core::error::AnyError;
+use deno_core::{
+    resolve_import, ModuleLoadResponse, ModuleLoader, ModuleSource, ModuleSourceCode,
+    ModuleSpecifier, ModuleType, RequestedModuleType, ResolutionKind,
+};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use super::chunks;
+use crate::config::ModuleSandbox;
+use crate::error::SsrError;
+
+/// URL prefix for in-memory modules
+const MEMORY_PREFIX: &str = "ssr-memory:///";
+
+thread_local! {
+    /// Chunks dynamically imported on this thread since the last `take_used_chunks`
+    static USED_CHUNKS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Take the chunks dynamically imported on this thread, relative to the bundle directory
+///
+/// Each chunk is followed by the chunks it imports statically, which the
+/// client needs as well.
+pub fn take_used_chunks() -> Vec<String> {
+    let chunks = USED_CHUNKS.with(|chunks| std::mem::take(&mut *chunks.borrow_mut()));
+    match chunks::chunk_graph() {
+        Some(graph) if !chunks.is_empty() => graph.with_static_imports(chunks),
+        _ => chunks,
+    }
+}
+
+/// Module loader restricted to the module sandbox
+pub struct SandboxedModuleLoader {
+    /// Canonical bundle directory (chunks are reported relative to it)
+    bundle_root: Option<PathBuf>,
+    /// Canonical directories modules may be loaded from
+    allowed_dirs: Vec<PathBuf>,
+    /// In-memory module sources keyed by their `ssr-memory:` URL
+    memory_modules: HashMap<String, String>,
+    /// Import specifier → `ssr-memory:` URL
+    memory_specifiers: HashMap<String, ModuleSpecifier>,
+}
+
+impl SandboxedModuleLoader {
+    /// Create a loader serving the bundle directory plus the sandbox
+    pub fn new(bundle_root: Option<&Path>, sandbox: &ModuleSandbox) -> Self {
+        let allowed_dirs = bundle_root
+            .map(Path::to_path_buf)
+            .into_iter()
+            .chain(sandbox.allowed_dirs.iter().filter_map(|dir| {
+                dir.canonicalize()
+                    .map_err(|e| tracing::warn!("⚠️ Ignoring module dir {:?}: {}", dir, e))
+                    .ok()
+            }))
+            .collect();
+
+        let mut memory_modules = HashMap::with_capacity(sandbox.modules.len());
+        let mut memory_specifiers = HashMap::with_capacity(sandbox.modules.len());
+        for (specifier, source) in &sandbox.modules {
+            match memory_url(specifier) {
+                Some(url) => {
+                    memory_modules.insert(url.as_str().to_string(), source.clone());
+                    memory_specifiers.insert(specifier.clone(), url);
+                }
+                None => tracing::warn!("⚠️ Ignoring in-memory module {:?}", specifier),
+            }
+        }
+
+        Self {
+            bundle_root: bundle_root.map(Path::to_path_buf),
+            allowed_dirs,
+            memory_modules,
+            memory_specifiers,
+        }
+    }
+
+    /// Resolve an import and check it against the sandbox
+    fn resolve_specifier(
+        &self,
+        specifier: &str,
+        referrer: &str,
+    ) -> Result<ModuleSpecifier, SsrError> {
+        if let Some(url) = self.memory_specifiers.get(specifier) {
+            return Ok(url.clone());
+        }
+
+        let resolved = resolve_import(specifier, referrer)
+            .map_err(|e| SsrError::ModuleNotFound(format!("{} ({})", specifier, e)))?;
+        self.check(&resolved)?;
+        Ok(resolved)
+    }
+
+    /// Check that a resolved specifier is inside the sandbox
+    fn check(&self, specifier: &ModuleSpecifier) -> Result<(), SsrError> {
+        match specifier.scheme() {
+            "file" => {
+                let path = specifier
+                    .to_file_path()
+                    .map_err(|_| SsrError::ModuleNotFound(specifier.to_string()))?;
+                self.check_path(&path, specifier)
+            }
+            "ssr-memory" if self.memory_modules.contains_key(specifier.as_str()) => Ok(()),
+            "ssr-memory" => Err(SsrError::ModuleNotFound(specifier.to_string())),
+            "http" | "https" => Err(SsrError::RemoteModuleDenied(specifier.to_string())),
+            "node" => Err(SsrError::BuiltinModuleDenied(specifier.to_string())),
+            _ => Err(SsrError::ModuleOutsideSandbox(specifier.to_string())),
+        }
+    }
+
+    /// Record a dynamically imported chunk from the bundle directory
+    fn record_chunk(&self, specifier: &ModuleSpecifier) {
+        let (Some(root), Ok(path)) = (&self.bundle_root, specifier.to_file_path()) else {
+            return;
+        };
+        let Ok(relative) = path.strip_prefix(root) else {
+            return;
+        };
+
+        let chunk = relative.to_string_lossy().replace('\\', "/");
+        USED_CHUNKS.with(|chunks| {
+            let mut chunks = chunks.borrow_mut();
+            if !chunks.contains(&chunk) {
+                chunks.push(chunk);
+            }
+        });
+    }
+
+    fn check_path(&self, path: &Path, specifier: &ModuleSpecifier) -> Result<(), SsrError> {
+        if self.allowed_dirs.iter().any(|dir| path.starts_with(dir)) {
+            Ok(())
+        } else {
+            Err(SsrError::ModuleOutsideSandbox(specifier.to_string()))
+        }
+    }
+
+    fn load_source(&self, specifier: &ModuleSpecifier) -> Result<ModuleSource, SsrError> {
+        self.check(specifier)?;
+
+        let code = if let Some(source) = self.memory_modules.get(specifier.as_str()) {
+            source.clone()
+        } else {
+            let path = specifier
+                .to_file_path()
+                .map_err(|_| SsrError::ModuleNotFound(specifier.to_string()))?;
+
+            // Re-check after resolving symlinks
+            let canonical = path
+                .canonicalize()
+                .map_err(|e| SsrError::ModuleNotFound(format!("{} ({})", specifier, e)))?;
+            self.check_path(&canonical, specifier)?;
+
+            std::fs::read_to_string(&canonical)
+                .map_err(|e| SsrError::ModuleNotFound(format!("{} ({})", specifier, e)))?
+        };
+
+        let module_type = if specifier.path().ends_with(".json") {
+            ModuleType::Json
+        } else {
+            ModuleType::JavaScript
+        };
+
+        Ok(ModuleSource::new(
+            module_type,
+            ModuleSourceCode::String(code.into()),
+            specifier,
+            None,
+        ))
+    }
+}
+
+impl ModuleLoader for SandboxedModuleLoader {
+    fn resolve(
+        &self,
+        specifier: &str,
+        referrer: &str,
+        kind: ResolutionKind,
+    ) -> Result<ModuleSpecifier, AnyError> {
+        let resolved = self.resolve_specifier(specifier, referrer)?;
+        // Resolution runs for every `import()`, even when the module is already cached
+        if matches!(kind, ResolutionKind::DynamicImport) {
+            self.record_chunk(&resolved);
+        }
+        Ok(resolved)
+    }
+
+    fn load(
+        &self,
+        module_specifier: &ModuleSpecifier,
+        _maybe_referrer: Option<&ModuleSpecifier>,
+        _is_dyn_import: bool,
+        _requested_module_type: RequestedModuleType,
+    ) -> ModuleLoadResponse {
+        ModuleLoadResponse::Sync(self.load_source(module_specifier).map_err(AnyError::from))
+    }
+}
+
+/// `ssr-memory:` URL for an in-memory module specifier
+fn memory_url(specifier: &str) -> Option<ModuleSpecifier> {
+    ModuleSpecifier::parse(&format!("{}{}", MEMORY_PREFIX, specifier)).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn loader(dir: &Path, sandbox: ModuleSandbox) -> SandboxedModuleLoader {
+        SandboxedModuleLoader::new(Some(dir), &sandbox)
+    }
+
+    fn referrer(dir: &Path) -> String {
+        ModuleSpecifier::from_file_path(dir.join("entry.mjs"))
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn test_bundle_relative_import_allowed() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let loader = loader(&root, ModuleSandbox::default());
+
+        let resolved = loader
+            .resolve_specifier("./lib/a.js", &referrer(&root))
+            .unwrap();
+        assert_eq!(resolved.to_file_path().unwrap(), root.join("lib/a.js"));
+    }
+
+    #[test]
+    fn test_file_outside_sandbox_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let loader = loader(&root, ModuleSandbox::default());
+
+        let result = loader.resolve_specifier("../secret.js", &referrer(&root));
+        assert!(matches!(result, Err(SsrError::ModuleOutsideSandbox(_))));
+
+        let result = loader.resolve_specifier("file:///etc/passwd", &referrer(&root));
+        assert!(matches!(result, Err(SsrError::ModuleOutsideSandbox(_))));
+    }
+
+    #[test]
+    fn test_remote_and_builtin_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let loader = loader(&root, ModuleSandbox::default());
+
+        let result = loader.resolve_specifier("https://esm.sh/preact", &referrer(&root));
+        assert!(matches!(result, Err(SsrError::RemoteModuleDenied(_))));
+
+        let result = loader.resolve_specifier("node:fs", &referrer(&root));
+        assert!(matches!(result, Err(SsrError::BuiltinModuleDenied(_))));
+    }
+
+    #[test]
+    fn test_memory_modules_served() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let mut sandbox = ModuleSandbox::default();
+        sandbox
+            .modules
+            .insert("node:buffer".into(), "export const Buffer = null;".into());
+        sandbox
+            .modules
+            .insert("virtual/a.js".into(), "export * from './b.js';".into());
+        sandbox
+            .modules
+            .insert("virtual/b.js".into(), "export const b = 1;".into());
+        let loader = loader(&root, sandbox);
+
+        let buffer = loader
+            .resolve_specifier("node:buffer", &referrer(&root))
+            .unwrap();
+        assert!(loader.load_source(&buffer).is_ok());
+
+        let a = loader
+            .resolve_specifier("virtual/a.js", &referrer(&root))
+            .unwrap();
+        let b = loader.resolve_specifier("./b.js", a.as_str()).unwrap();
+        assert!(loader.load_source(&b).is_ok());
+
+        let result = loader.resolve_specifier("./missing.js", a.as_str());
+        assert!(matches!(result, Err(SsrError::ModuleNotFound(_))));
+    }
+
+    #[test]
+    fn test_dynamic_imports_recorded_as_chunks() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let loader = loader(&root, ModuleSandbox::default());
+        take_used_chunks();
+
+        let entry = referrer(&root);
+        loader
+            .resolve("./assets/Lazy.js", &entry, ResolutionKind::DynamicImport)
+            .unwrap();
+        loader
+            .resolve("./assets/Lazy.js", &entry, ResolutionKind::DynamicImport)
+            .unwrap();
+        loader
+            .resolve("./lib/static.js", &entry, ResolutionKind::Import)
+            .unwrap();
+
+        assert_eq!(take_used_chunks(), vec!["assets/Lazy.js".to_string()]);
+        assert!(take_used_chunks().is_empty());
+    }
+
+    #[test]
+    fn test_allowed_dir() {
+        let bundle = tempfile::tempdir().unwrap();
+        let vendor = tempfile::tempdir().unwrap();
+        std::fs::write(vendor.path().join("lib.js"), "export const x = 1;").unwrap();
+        let root = bundle.path().canonicalize().unwrap();
+        let vendor_root = vendor.path().canonicalize().unwrap();
+
+        let sandbox = ModuleSandbox {
+            allowed_dirs: vec![vendor_root.clone()],
+            ..Default::default()
+        };
+        let loader = loader(&root, sandbox);
+
+        let specifier = ModuleSpecifier::from_file_path(vendor_root.join("lib.js")).unwrap();
+        let resolved = loader
+            .resolve_specifier(specifier.as_str(), &referrer(&root))
+            .unwrap();
+        assert!(loader.load_source(&resolved).is_ok());
+    }
+}
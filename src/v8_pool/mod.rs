@@ -9,7 +9,7 @@
 //! ┌─────────────────────────────────────────────────┐
 //! │                   V8Pool                         │
 //! │  ┌──────────┐                                   │
-//! │  │ Channel  │◄──── render requests              │
+//! │  │  Queues  │◄──── render requests (by priority)│
 //! │  └────┬─────┘                                   │
 //! │       │                                         │
 //! │  ┌────▼────┐  ┌─────────┐  ┌─────────┐        │
@@ -20,9 +20,23 @@
 //! ```
 
 mod bundle;
+mod chunks;
+mod loader;
 mod pool;
+mod queue;
 mod renderer;
 mod runtime;
+mod stats;
+mod trace;
+mod web;
 
-pub use bundle::{init_bundle, init_bundle_from_string, is_initialized};
-pub use pool::{PoolError, V8Pool, V8PoolConfig};
+pub use bundle::{
+    init_bundle, init_bundle_from_string, init_bundle_raw, init_bundle_with_polyfills,
+    init_module_bundle, init_module_bundle_with_polyfills, is_initialized, Polyfills,
+};
+pub use pool::{PoolError, RenderedPage, V8Pool, V8PoolConfig};
+#[cfg(feature = "bench")]
+#[doc(hidden)]
+pub use queue::{PushError, RenderQueue};
+pub use runtime::MODULE_EXPORTS_GLOBAL;
+pub use stats::{PoolStats, WorkerStats};
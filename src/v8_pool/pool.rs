@@ -8,7 +8,11 @@ use std::time::Duration;
 use std::time::Instant;
 use tokio::sync::oneshot;
 
-use super::{renderer, runtime};
+use super::queue::{PushError, RenderQueue};
+use super::stats::{PoolMetrics, PoolStats};
+use super::{loader, renderer, runtime, trace};
+use crate::config::{ModuleSandbox, RenderIsolation, RenderOptions, Scheduling};
+use crate::metrics::TextEncoder;
 
 /// Configuration for the V8 thread pool
 #[derive(Debug, Clone)]
@@ -19,6 +23,9 @@ pub struct V8PoolConfig {
     /// Size of the task queue
     pub queue_capacity: usize,
 
+    /// How workers pick the next request between priorities
+    pub scheduling: Scheduling,
+
     /// Pin workers to specific CPU cores
     pub pin_threads: bool,
 
@@ -27,6 +34,32 @@ pub struct V8PoolConfig {
 
     /// Name of the render function in JS
     pub render_function: String,
+
+    /// Additional render functions callable via [`V8Pool::render_entry`]
+    ///
+    /// Like `render_function`, these are property paths on `globalThis`.
+    pub render_entries: Vec<String>,
+
+    /// Sandbox for module imports made by the bundle
+    pub module_sandbox: ModuleSandbox,
+
+    /// Pre-load the code-split chunks the bundle imports into each isolate
+    pub preload_chunks: bool,
+
+    /// Public URL prefix of the bundle chunks
+    ///
+    /// When set, `<link rel="modulepreload">` tags for the chunks a page
+    /// imported are injected into its HTML.
+    pub chunk_base_url: Option<String>,
+
+    /// State isolation between renders
+    pub render_isolation: RenderIsolation,
+
+    /// URLs every worker renders before accepting requests
+    ///
+    /// Warm-up renders JIT-compile the bundle's hot paths; an error thrown by
+    /// the bundle fails the worker's initialization.
+    pub warmup_urls: Vec<String>,
 }
 
 impl Default for V8PoolConfig {
@@ -34,19 +67,84 @@ impl Default for V8PoolConfig {
         Self {
             num_threads: num_cpus::get(),
             queue_capacity: 512,
+            scheduling: Scheduling::default(),
             pin_threads: false,
             request_timeout: Some(Duration::from_secs(30)),
             render_function: "renderPage".to_string(),
+            render_entries: Vec::new(),
+            module_sandbox: ModuleSandbox::default(),
+            preload_chunks: false,
+            chunk_base_url: None,
+            render_isolation: RenderIsolation::Shared,
+            warmup_urls: Vec::new(),
         }
     }
 }
 
+/// Rendered page together with the code-split chunks it imported
+#[derive(Debug, Clone, Default)]
+pub struct RenderedPage {
+    /// Rendered HTML
+    pub html: String,
+
+    /// Chunks dynamically imported during the render and the chunks they
+    /// import statically, relative to the bundle directory
+    /// (e.g. `assets/ProductPage-3f2a.js`)
+    pub chunks: Vec<String>,
+}
+
+/// HTML of each warm-up URL, keyed by URL
+type WarmupPages = Vec<(String, String)>;
+
 /// Internal render request
 struct RenderRequest {
     url: String,
     data: String,
     render_function: String,
-    response_tx: oneshot::Sender<Result<String, String>>,
+    strict: bool,
+    enqueued_at: Instant,
+    /// Skip the request if no worker started it by then
+    deadline: Option<Instant>,
+    /// Caller's span, parent of the worker's spans
+    span: tracing::Span,
+    /// `ssr.queue_wait` span, closed when a worker picks up the request
+    queue_span: tracing::Span,
+    response_tx: oneshot::Sender<Result<RenderedPage, PoolError>>,
+}
+
+impl RenderRequest {
+    /// The caller went away or the deadline passed, so rendering is wasted
+    fn cancelled(&self) -> Option<Cancelled> {
+        if self.response_tx.is_closed() {
+            Some(Cancelled::Disconnected)
+        } else if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            Some(Cancelled::Expired)
+        } else {
+            None
+        }
+    }
+
+    /// Answer the request with `error` without rendering it
+    fn reject(self, error: PoolError) {
+        // Spans close with the caller's subscriber, like on a worker
+        tracing::dispatcher::with_default(&caller_dispatch(&self.span), || {
+            drop(self.queue_span);
+            drop(self.span);
+        });
+        let _ = self.response_tx.send(Err(error));
+    }
+}
+
+/// Why a worker skipped a queued request
+#[derive(Debug, Clone, Copy)]
+enum Cancelled {
+    /// The caller dropped its future, e.g. after the client disconnected
+    Disconnected,
+    /// The request's deadline passed while it was queued
+    Expired,
 }
 
 /// Errors returned by the V8 pool
@@ -60,6 +158,12 @@ pub enum PoolError {
     WorkerCrashed,
     /// Rendering failed inside V8
     Render(String),
+    /// A worker failed to initialize its V8 runtime
+    Init(String),
+    /// Render function is not the configured one or an allowed entry
+    UnknownEntry(String),
+    /// Request was dropped from the full queue for a more important one
+    Shed,
 }
 
 impl std::fmt::Display for PoolError {
@@ -69,12 +173,30 @@ impl std::fmt::Display for PoolError {
             PoolError::Disconnected => write!(f, "V8 pool is not accepting requests"),
             PoolError::WorkerCrashed => write!(f, "V8 worker crashed while rendering"),
             PoolError::Render(msg) => write!(f, "{}", msg),
+            PoolError::Init(msg) => write!(f, "V8 worker failed to initialize: {}", msg),
+            PoolError::UnknownEntry(name) => write!(f, "Unknown render entry: {}", name),
+            PoolError::Shed => write!(f, "Render shed from the full V8 pool queue"),
         }
     }
 }
 
 impl std::error::Error for PoolError {}
 
+impl PoolError {
+    /// Short name of the variant, used as the `kind` label of error metrics
+    pub fn kind(&self) -> &'static str {
+        match self {
+            PoolError::Timeout => "timeout",
+            PoolError::Disconnected => "disconnected",
+            PoolError::WorkerCrashed => "worker_crashed",
+            PoolError::Render(_) => "render",
+            PoolError::Init(_) => "init",
+            PoolError::UnknownEntry(_) => "unknown_entry",
+            PoolError::Shed => "shed",
+        }
+    }
+}
+
 /// V8 Thread Pool for parallel SSR rendering
 ///
 /// Each worker thread has its own V8 isolate, solving the `!Send + !Sync`
@@ -92,24 +214,71 @@ impl std::error::Error for PoolError {}
 /// ```
 pub struct V8Pool {
     config: V8PoolConfig,
-    request_tx: mpsc::SyncSender<RenderRequest>,
-    #[allow(dead_code)]
-    request_rx: Arc<Mutex<mpsc::Receiver<RenderRequest>>>,
+    queue: Arc<RenderQueue<RenderRequest>>,
     worker_count: Arc<Mutex<usize>>,
     #[allow(dead_code)]
     core_affinity: Option<Arc<Vec<CoreId>>>,
     #[allow(dead_code)]
     next_core: Arc<AtomicUsize>,
+    warmup_pages: Mutex<WarmupPages>,
+    metrics: Arc<PoolMetrics>,
 }
 
 impl V8Pool {
     /// Create a new V8 thread pool
+    ///
+    /// Workers initialize in the background; one that fails to load the
+    /// bundle logs the error and exits. Use [`V8Pool::try_new`] to fail instead.
     pub fn new(config: V8PoolConfig) -> Self {
+        Self::spawn(config).0
+    }
+
+    /// Create a new V8 thread pool and wait for every worker to initialize
+    ///
+    /// Fails if any worker cannot load the bundle, if the render function
+    /// or one of the render entries is missing from it, or if a warm-up
+    /// render throws.
+    pub fn try_new(config: V8PoolConfig) -> Result<Self, PoolError> {
+        let (pool, ready_rx) = Self::spawn(config);
+
+        for _ in 0..pool.config.num_threads {
+            match ready_rx.recv() {
+                Ok(Ok(pages)) => {
+                    let mut warmup_pages = pool.warmup_pages.lock().unwrap();
+                    if warmup_pages.is_empty() {
+                        *warmup_pages = pages;
+                    }
+                }
+                Ok(Err(msg)) => return Err(PoolError::Init(msg)),
+                Err(_) => return Err(PoolError::WorkerCrashed),
+            }
+        }
+
+        Ok(pool)
+    }
+
+    /// Take the pages rendered for `warmup_urls` by the first ready worker
+    ///
+    /// Only populated by [`V8Pool::try_new`]; use it to pre-fill a cache.
+    pub fn take_warmup_pages(&self) -> Vec<(String, String)> {
+        std::mem::take(&mut *self.warmup_pages.lock().unwrap())
+    }
+
+    /// Spawn the workers, returning a channel that reports their initialization
+    fn spawn(config: V8PoolConfig) -> (Self, mpsc::Receiver<Result<WarmupPages, String>>) {
         tracing::info!("🔧 Creating V8 pool with {} threads", config.num_threads);
 
-        let (request_tx, request_rx) = mpsc::sync_channel(config.queue_capacity);
-        let request_rx = Arc::new(Mutex::new(request_rx));
+        let queue = Arc::new(RenderQueue::new(config.queue_capacity, config.scheduling));
         let worker_count = Arc::new(Mutex::new(0));
+        let (ready_tx, ready_rx) = mpsc::channel();
+        let setup = Arc::new(WorkerSetup {
+            config: config.clone(),
+            snapshot: if config.render_isolation == RenderIsolation::FreshRuntime {
+                create_snapshot(&config)
+            } else {
+                None
+            },
+        });
 
         let core_affinity = if config.pin_threads {
             core_affinity::get_core_ids().map(Arc::new)
@@ -119,27 +288,35 @@ impl V8Pool {
 
         let pool = Self {
             config: config.clone(),
-            request_tx,
-            request_rx: Arc::clone(&request_rx),
+            queue: Arc::clone(&queue),
             worker_count: Arc::clone(&worker_count),
             core_affinity: core_affinity.clone(),
             next_core: Arc::new(AtomicUsize::new(0)),
+            warmup_pages: Mutex::new(Vec::new()),
+            metrics: Arc::new(PoolMetrics::new(config.num_threads)),
         };
 
         // Spawn worker threads
         for i in 0..config.num_threads {
+            let core_id = core_affinity.as_ref().and_then(|cores| {
+                let idx = pool.next_core.fetch_add(1, Ordering::Relaxed) % cores.len();
+                cores.get(idx).copied()
+            });
+
             spawn_worker(
                 i,
-                Arc::clone(&request_rx),
+                Arc::clone(&queue),
                 Arc::clone(&worker_count),
-                core_affinity.clone(),
-                Arc::clone(&pool.next_core),
+                core_id,
+                Arc::clone(&setup),
+                ready_tx.clone(),
+                Arc::clone(&pool.metrics),
             );
         }
 
         tracing::info!("✅ Started {} V8 workers", config.num_threads);
 
-        pool
+        (pool, ready_rx)
     }
 
     /// Render a URL to HTML
@@ -149,42 +326,177 @@ impl V8Pool {
 
     /// Render a URL to HTML with custom data
     pub async fn render_with_data(&self, url: String, data: String) -> Result<String, PoolError> {
+        self.render_page(url, data).await.map(|page| page.html)
+    }
+
+    /// Render a URL with custom data, returning the chunks the page imported
+    pub async fn render_page(&self, url: String, data: String) -> Result<RenderedPage, PoolError> {
+        self.render_page_with_options(url, data, RenderOptions::default())
+            .await
+    }
+
+    /// Render a URL with custom data at the priority and deadline of `options`
+    pub async fn render_page_with_options(
+        &self,
+        url: String,
+        data: String,
+        options: RenderOptions,
+    ) -> Result<RenderedPage, PoolError> {
+        self.dispatch(
+            self.config.render_function.clone(),
+            url,
+            data,
+            false,
+            options,
+        )
+        .await
+    }
+
+    /// Render a URL, returning errors thrown by the bundle as [`PoolError::Render`]
+    ///
+    /// Other render methods turn bundle errors into an error page.
+    pub async fn try_render_page(
+        &self,
+        url: String,
+        data: String,
+    ) -> Result<RenderedPage, PoolError> {
+        self.try_render_page_with_options(url, data, RenderOptions::default())
+            .await
+    }
+
+    /// Like [`V8Pool::try_render_page`], at the priority and deadline of `options`
+    pub async fn try_render_page_with_options(
+        &self,
+        url: String,
+        data: String,
+        options: RenderOptions,
+    ) -> Result<RenderedPage, PoolError> {
+        self.dispatch(
+            self.config.render_function.clone(),
+            url,
+            data,
+            true,
+            options,
+        )
+        .await
+    }
+
+    /// Render a URL with one of the configured `render_entries`
+    pub async fn render_entry(
+        &self,
+        render_function: &str,
+        url: String,
+        data: String,
+    ) -> Result<RenderedPage, PoolError> {
+        self.render_entry_with_options(render_function, url, data, RenderOptions::default())
+            .await
+    }
+
+    /// Like [`V8Pool::render_entry`], at the priority and deadline of `options`
+    pub async fn render_entry_with_options(
+        &self,
+        render_function: &str,
+        url: String,
+        data: String,
+        options: RenderOptions,
+    ) -> Result<RenderedPage, PoolError> {
+        if render_function != self.config.render_function
+            && !self
+                .config
+                .render_entries
+                .iter()
+                .any(|e| e == render_function)
+        {
+            let error = PoolError::UnknownEntry(render_function.to_string());
+            self.metrics.record_error(&error);
+            return Err(error);
+        }
+
+        self.dispatch(render_function.to_string(), url, data, false, options)
+            .await
+    }
+
+    /// Enqueue a render request and wait for its result
+    async fn dispatch(
+        &self,
+        render_function: String,
+        url: String,
+        data: String,
+        strict: bool,
+        options: RenderOptions,
+    ) -> Result<RenderedPage, PoolError> {
+        let result = self
+            .enqueue(render_function, url, data, strict, options)
+            .await;
+        if let Err(e) = &result {
+            self.metrics.record_error(e);
+        }
+        result
+    }
+
+    async fn enqueue(
+        &self,
+        render_function: String,
+        url: String,
+        data: String,
+        strict: bool,
+        options: RenderOptions,
+    ) -> Result<RenderedPage, PoolError> {
         let (response_tx, response_rx) = oneshot::channel();
+        let span = tracing::Span::current();
 
         let request = RenderRequest {
             url,
             data,
-            render_function: self.config.render_function.clone(),
+            render_function,
+            strict,
+            enqueued_at: Instant::now(),
+            deadline: options.deadline,
+            queue_span: tracing::info_span!(parent: &span, "ssr.queue_wait"),
+            span,
             response_tx,
         };
 
-        let deadline = self.config.request_timeout.map(|t| Instant::now() + t);
+        let deadline = options
+            .deadline
+            .or_else(|| self.config.request_timeout.map(|t| Instant::now() + t));
         let mut req = request;
 
+        // Counted before sending, so a worker never dequeues an uncounted
+        // request; the slot uncounts it if it is never sent, even when this
+        // future is dropped while waiting for room
+        let slot = self.metrics.queued();
         loop {
-            match self.request_tx.try_send(req) {
-                Ok(()) => break,
-                Err(mpsc::TrySendError::Full(r)) => {
-                    if let Some(dl) = deadline {
-                        if Instant::now() >= dl {
-                            return Err(PoolError::Timeout);
-                        }
+            let room = self.queue.room();
+            tokio::pin!(room);
+            room.as_mut().enable();
+
+            match self.queue.try_push(options.priority, req) {
+                Ok(shed) => {
+                    slot.pushed();
+                    if let Some(shed) = shed {
+                        self.metrics.unqueued();
+                        shed.reject(PoolError::Shed);
                     }
-                    req = r;
-                    tokio::task::yield_now().await;
-                    continue;
+                    break;
                 }
-                Err(mpsc::TrySendError::Disconnected(_)) => {
-                    return Err(PoolError::Disconnected);
+                Err(PushError::Full(r)) => {
+                    req = r;
+                    match deadline {
+                        Some(dl) => {
+                            let dl = tokio::time::Instant::from_std(dl);
+                            if tokio::time::timeout_at(dl, room).await.is_err() {
+                                return Err(PoolError::Timeout);
+                            }
+                        }
+                        None => room.await,
+                    }
                 }
+                Err(PushError::Closed(_)) => return Err(PoolError::Disconnected),
             }
         }
 
-        match response_rx.await {
-            Ok(Ok(html)) => Ok(html),
-            Ok(Err(msg)) => Err(PoolError::Render(msg)),
-            Err(_) => Err(PoolError::WorkerCrashed),
-        }
+        response_rx.await.unwrap_or(Err(PoolError::WorkerCrashed))
     }
 
     /// Get the number of active workers
@@ -196,22 +508,55 @@ impl V8Pool {
     pub fn config(&self) -> &V8PoolConfig {
         &self.config
     }
+
+    /// Write the pool metrics in the Prometheus text format
+    ///
+    /// Covers queue depth and wait time, busy and idle workers, per-worker
+    /// renders and busy time, V8 render time, errors by kind, isolate
+    /// restarts and heap usage.
+    pub fn encode_metrics(&self, encoder: &mut TextEncoder) {
+        self.metrics.encode(encoder);
+    }
+
+    /// Get the queue depth, per-worker statistics and recent render times
+    ///
+    /// # Example
+    /// ```rust,ignore
+    /// let stats = pool.stats();
+    /// println!("{} queued, {} rendering, p99 {:?}", stats.queue_depth, stats.in_flight, stats.render_time_p99);
+    /// for worker in &stats.workers {
+    ///     println!("worker {}: {} renders, busy {:?}", worker.id, worker.processed, worker.busy_time);
+    /// }
+    /// ```
+    pub fn stats(&self) -> PoolStats {
+        self.metrics.stats()
+    }
 }
 
 impl Drop for V8Pool {
     fn drop(&mut self) {
         tracing::info!("🛑 Shutting down V8 pool");
-        // Channels will be dropped, workers will receive disconnect and exit
+        // Workers render the queued requests, then exit
+        self.queue.close();
     }
 }
 
+/// Pool-wide state a worker creates its runtime from
+struct WorkerSetup {
+    config: V8PoolConfig,
+    /// Snapshot of the evaluated bundle that runtimes are restored from
+    snapshot: Option<&'static [u8]>,
+}
+
 /// Spawn a worker thread
 fn spawn_worker(
     id: usize,
-    request_rx: Arc<Mutex<mpsc::Receiver<RenderRequest>>>,
+    queue: Arc<RenderQueue<RenderRequest>>,
     worker_count: Arc<Mutex<usize>>,
-    core_affinity: Option<Arc<Vec<CoreId>>>,
-    next_core: Arc<AtomicUsize>,
+    core_id: Option<CoreId>,
+    setup: Arc<WorkerSetup>,
+    ready_tx: mpsc::Sender<Result<WarmupPages, String>>,
+    metrics: Arc<PoolMetrics>,
 ) {
     // Increment worker count
     {
@@ -221,66 +566,122 @@ fn spawn_worker(
 
     thread::spawn(move || {
         tracing::debug!("🟢 V8 worker {} started", id);
+        let config = &setup.config;
 
         // Pin to CPU core if requested
-        if let Some(cores) = core_affinity {
-            let idx = next_core.fetch_add(1, Ordering::Relaxed) % cores.len();
-            if let Some(core_id) = cores.get(idx) {
-                if core_affinity::set_for_current(*core_id) {
-                    tracing::debug!("📌 Worker {} pinned to core {:?}", id, core_id.id);
-                }
+        if let Some(core_id) = core_id {
+            if core_affinity::set_for_current(core_id) {
+                tracing::debug!("📌 Worker {} pinned to core {:?}", id, core_id.id);
             }
         }
 
-        // Initialize V8 runtime for this thread
-        if let Err(e) = runtime::init_runtime() {
-            tracing::error!("❌ Failed to initialize V8 for worker {}: {}", id, e);
-            let mut count = worker_count.lock().unwrap();
-            *count -= 1;
-            return;
+        // Initialize V8 runtime for this thread, check the render functions
+        // exist and warm up the hot paths
+        let init = runtime::init_runtime(config, setup.snapshot)
+            .and_then(|()| {
+                let mut functions = vec![config.render_function.clone()];
+                functions.extend(config.render_entries.iter().cloned());
+                runtime::check_render_functions(&functions)
+            })
+            .and_then(|()| warm_up(config));
+        match init {
+            Ok(pages) => {
+                let _ = ready_tx.send(Ok(pages));
+            }
+            Err(e) => {
+                tracing::error!("❌ Failed to initialize V8 for worker {}: {}", id, e);
+                let mut count = worker_count.lock().unwrap();
+                *count -= 1;
+                let _ = ready_tx.send(Err(e));
+                return;
+            }
         }
 
-        let mut requests_processed = 0usize;
+        metrics.worker_started(id, runtime::heap_used_bytes());
 
         // Main worker loop
         loop {
-            let request = {
-                let rx = request_rx.lock().unwrap();
-                match rx.recv() {
-                    Ok(req) => Some(req),
-                    Err(_) => {
-                        tracing::debug!("🔴 Worker {} channel disconnected", id);
-                        break;
-                    }
+            let request = match queue.pop() {
+                Some(req) => Some(req),
+                None => {
+                    tracing::debug!("🔴 Worker {} queue closed", id);
+                    break;
                 }
             };
 
             if let Some(req) = request {
+                if let Some(reason) = req.cancelled() {
+                    tracing::debug!("⏭️ Worker {} skipped {} ({:?})", id, req.url, reason);
+                    match reason {
+                        Cancelled::Disconnected => metrics.cancelled(),
+                        Cancelled::Expired => metrics.expired(),
+                    }
+                    req.reject(PoolError::Timeout);
+                    continue;
+                }
+
+                metrics.render_started(id, req.enqueued_at.elapsed());
+
                 // Prefetch data for better cache performance
                 prefetch_data(&req.data);
 
-                // Render via V8
-                let result = runtime::with_runtime(|js_runtime| {
-                    renderer::render_html(
-                        &req.url,
-                        Some(&req.data),
-                        &req.render_function,
-                        js_runtime,
-                    )
+                let started = Instant::now();
+                let dispatch = caller_dispatch(&req.span);
+                let result = tracing::dispatcher::with_default(&dispatch, || {
+                    drop(req.queue_span);
+                    let result =
+                        tracing::info_span!(parent: &req.span, "ssr.v8_execute", worker = id)
+                            .in_scope(|| {
+                                render(
+                                    &req.url,
+                                    &req.data,
+                                    &req.render_function,
+                                    config,
+                                    req.strict,
+                                )
+                            });
+                    // Release the caller's span, so it closes with the caller
+                    drop(req.span);
+                    result
                 });
+                let render_time = started.elapsed();
+                if let Err(e) = &result {
+                    metrics.worker_error(id, e);
+                }
+                metrics.render_finished(id, render_time);
+                metrics.set_heap_used(id, runtime::heap_used_bytes());
 
                 // Send response
-                let _ = req.response_tx.send(result);
-
-                requests_processed += 1;
+                let _ = req.response_tx.send(result.map_err(PoolError::Render));
+
+                // Reset render state off the response path, recreating the
+                // isolate if that fails
+                let reset_started = Instant::now();
+                let reset = runtime::reset_after_render(config);
+                metrics.reset_finished(id, reset_started.elapsed());
+                if let Err(e) = reset {
+                    tracing::warn!(
+                        "⚠️ Worker {} failed to reset V8 state, restarting: {}",
+                        id,
+                        e
+                    );
+                    metrics.worker_error(id, &e);
+                    if let Err(e) = runtime::restart_runtime(config) {
+                        tracing::error!("❌ Worker {} failed to restart V8: {}", id, e);
+                        metrics.worker_error(id, &e);
+                        break;
+                    }
+                    metrics.worker_restarted(id);
+                }
             }
         }
 
         tracing::debug!(
             "🔴 Worker {} stopped (processed {} requests)",
             id,
-            requests_processed
+            metrics.processed(id)
         );
+        metrics.worker_stopped(id);
 
         // Decrement worker count
         let mut count = worker_count.lock().unwrap();
@@ -288,6 +689,82 @@ fn spawn_worker(
     });
 }
 
+/// Snapshot the evaluated bundle, so fresh runtimes skip evaluating it
+///
+/// Built on its own thread, as V8 requires a thread's isolates to be
+/// dropped in the reverse order of their creation. If the bundle cannot be
+/// snapshotted, every fresh runtime evaluates it instead.
+fn create_snapshot(config: &V8PoolConfig) -> Option<&'static [u8]> {
+    let config = config.clone();
+    let result = thread::spawn(move || runtime::create_snapshot(&config))
+        .join()
+        .unwrap_or_else(|_| Err("snapshot thread panicked".to_string()));
+
+    match result {
+        Ok(snapshot) => {
+            tracing::debug!("📸 Created bundle snapshot ({} KB)", snapshot.len() / 1024);
+            Some(snapshot)
+        }
+        Err(e) => {
+            tracing::warn!(
+                "⚠️ Failed to snapshot the bundle, fresh runtimes will evaluate it: {}",
+                e
+            );
+            None
+        }
+    }
+}
+
+/// Subscriber of the caller's `span`
+///
+/// Workers create and close spans with it, so they reach scoped subscribers,
+/// not only the global default. Closing a span with another subscriber
+/// would leak its parent.
+fn caller_dispatch(span: &tracing::Span) -> tracing::Dispatch {
+    span.with_subscriber(|(_, dispatch)| dispatch.clone())
+        .unwrap_or_else(|| tracing::dispatcher::get_default(|dispatch| dispatch.clone()))
+}
+
+/// Render via V8, tracking the chunks imported by the page
+///
+/// In `strict` mode errors thrown by the bundle are returned instead of
+/// being rendered as an error page.
+fn render(
+    url: &str,
+    data: &str,
+    render_function: &str,
+    config: &V8PoolConfig,
+    strict: bool,
+) -> Result<RenderedPage, String> {
+    loader::take_used_chunks();
+    let result = runtime::with_runtime(|js_runtime| {
+        renderer::render_html(url, Some(data), render_function, js_runtime, strict)
+    });
+    trace::close_open_spans();
+
+    result.map(|mut html| {
+        let chunks = loader::take_used_chunks();
+        if let Some(base) = &config.chunk_base_url {
+            renderer::inject_preload_links(&mut html, &chunks, base);
+        }
+        RenderedPage { html, chunks }
+    })
+}
+
+/// Render every warm-up URL in strict mode
+fn warm_up(config: &V8PoolConfig) -> Result<WarmupPages, String> {
+    let mut pages = Vec::with_capacity(config.warmup_urls.len());
+
+    for url in &config.warmup_urls {
+        let page = render(url, "{}", &config.render_function, config, true)
+            .map_err(|e| format!("Warm-up render of {} failed: {}", url, e))?;
+        runtime::reset_after_render(config)?;
+        pages.push((url.clone(), page.html));
+    }
+
+    Ok(pages)
+}
+
 /// Prefetch data into CPU cache
 #[inline]
 fn prefetch_data(data: &str) {
@@ -310,14 +787,14 @@ impl V8Pool {
     /// Create a stub pool for testing (no actual V8)
     #[allow(dead_code)]
     pub fn new_stub_with(config: V8PoolConfig) -> Self {
-        let (request_tx, request_rx) = mpsc::sync_channel(config.queue_capacity);
         Self {
+            queue: Arc::new(RenderQueue::new(config.queue_capacity, config.scheduling)),
             config,
-            request_tx,
-            request_rx: Arc::new(Mutex::new(request_rx)),
             worker_count: Arc::new(Mutex::new(0)),
             core_affinity: None,
             next_core: Arc::new(AtomicUsize::new(0)),
+            warmup_pages: Mutex::new(Vec::new()),
+            metrics: Arc::new(PoolMetrics::new(0)),
         }
     }
 
@@ -330,6 +807,7 @@ impl V8Pool {
             pin_threads: false,
             request_timeout: Some(Duration::from_millis(10)),
             render_function: "renderPage".to_string(),
+            ..Default::default()
         })
     }
 }
@@ -0,0 +1,333 @@
+//! Lock-free render queue with one lane per priority
+
+use crossbeam_deque::{Injector, Steal};
+use crossbeam_utils::Backoff;
+use std::sync::atomic::{fence, AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex};
+use tokio::sync::futures::Notified;
+use tokio::sync::Notify;
+
+use crate::config::{Priority, Scheduling};
+
+/// Error of [`RenderQueue::try_push`], handing the item back
+pub enum PushError<T> {
+    /// Queue is full of requests at least as important
+    Full(T),
+    /// Queue was closed
+    Closed(T),
+}
+
+/// Queue of render requests shared by the pool and its workers
+///
+/// Each priority is a lock-free MPMC queue, so neither pushing nor popping
+/// serializes on a lock. Holds at most `capacity` requests across all
+/// priorities. Idle workers spin briefly in [`RenderQueue::pop`], then
+/// sleep until a request arrives or the queue is closed. Pushers finding
+/// the queue full wait on [`RenderQueue::room`].
+pub struct RenderQueue<T> {
+    lanes: [Injector<T>; 3],
+    /// Queued requests across all lanes
+    len: AtomicUsize,
+    capacity: usize,
+    /// Scheduling turns of each lane, in priority order
+    weights: [usize; 3],
+    /// Turns taken so far; lane `i` owns `weights[i]` of every `sum(weights)`
+    turn: AtomicUsize,
+    closed: AtomicBool,
+    /// Workers sleeping in `pop`
+    sleepers: AtomicUsize,
+    lock: Mutex<()>,
+    available: Condvar,
+    /// Signalled when a request leaves the queue, or it is closed
+    room: Notify,
+}
+
+impl<T> RenderQueue<T> {
+    /// Create a queue holding at most `capacity` requests
+    pub fn new(capacity: usize, scheduling: Scheduling) -> Self {
+        let weights = match scheduling {
+            Scheduling::Strict => [1, 0, 0],
+            Scheduling::Weighted {
+                interactive,
+                background,
+                bot,
+            } => [interactive, background, bot].map(|weight| weight as usize),
+        };
+
+        Self {
+            lanes: Default::default(),
+            len: AtomicUsize::new(0),
+            capacity,
+            weights,
+            turn: AtomicUsize::new(0),
+            closed: AtomicBool::new(false),
+            sleepers: AtomicUsize::new(0),
+            lock: Mutex::new(()),
+            available: Condvar::new(),
+            room: Notify::new(),
+        }
+    }
+
+    /// Queue `item` without blocking
+    ///
+    /// When the queue is full, the oldest request of the least important
+    /// priority below `priority` is shed to make room and returned.
+    pub fn try_push(&self, priority: Priority, item: T) -> Result<Option<T>, PushError<T>> {
+        if self.closed.load(Ordering::Acquire) {
+            return Err(PushError::Closed(item));
+        }
+
+        let reserved = self
+            .len
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |len| {
+                (len < self.capacity).then_some(len + 1)
+            })
+            .is_ok();
+        let mut shed = None;
+        if !reserved {
+            // The shed request's slot goes to the new one
+            shed = (priority.index() + 1..self.lanes.len())
+                .rev()
+                .find_map(|lane| steal(&self.lanes[lane]));
+            if shed.is_none() {
+                return Err(PushError::Full(item));
+            }
+        }
+
+        self.lanes[priority.index()].push(item);
+        self.wake_one();
+        Ok(shed)
+    }
+
+    /// Wait for the next request to render
+    ///
+    /// Returns `None` once the queue is closed and drained.
+    pub fn pop(&self) -> Option<T> {
+        let backoff = Backoff::new();
+        loop {
+            if let Some(item) = self.try_pop() {
+                return Some(item);
+            }
+            if self.closed.load(Ordering::Acquire) {
+                return self.try_pop();
+            }
+            if !backoff.is_completed() {
+                backoff.snooze();
+                continue;
+            }
+
+            let guard = self.lock.lock().unwrap();
+            self.sleepers.fetch_add(1, Ordering::SeqCst);
+            // Pairs with the fence in `wake_one`: either the pusher sees this
+            // sleeper, or this check sees the pushed request
+            fence(Ordering::SeqCst);
+            if self.lanes.iter().all(Injector::is_empty) && !self.closed.load(Ordering::SeqCst) {
+                drop(self.available.wait(guard).unwrap());
+            } else {
+                drop(guard);
+            }
+            self.sleepers.fetch_sub(1, Ordering::SeqCst);
+            backoff.reset();
+        }
+    }
+
+    /// Wait for room after [`PushError::Full`]
+    ///
+    /// Completes once a request leaves the queue or it is closed. Enable the
+    /// future before retrying the push, so a request leaving in between is
+    /// not missed.
+    pub fn room(&self) -> Notified<'_> {
+        self.room.notified()
+    }
+
+    /// Stop accepting requests and wake the sleeping workers
+    pub fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+        self.room.notify_waiters();
+        let _guard = self.lock.lock().unwrap();
+        self.available.notify_all();
+    }
+
+    /// Take the next request without blocking
+    ///
+    /// The lane whose turn it is goes first, then the others by priority.
+    fn try_pop(&self) -> Option<T> {
+        let turn = self.turn.load(Ordering::Relaxed);
+        let item =
+            steal(&self.lanes[self.lane_of(turn)]).or_else(|| self.lanes.iter().find_map(steal))?;
+
+        self.turn.fetch_add(1, Ordering::Relaxed);
+        self.len.fetch_sub(1, Ordering::AcqRel);
+        self.room.notify_one();
+        Some(item)
+    }
+
+    /// Lane owning scheduling turn `turn`
+    fn lane_of(&self, turn: usize) -> usize {
+        let mut turn = turn % self.weights.iter().sum::<usize>().max(1);
+        self.weights
+            .iter()
+            .position(|&weight| {
+                let owned = turn < weight;
+                turn = turn.saturating_sub(weight);
+                owned
+            })
+            .unwrap_or(0)
+    }
+
+    /// Wake a sleeping worker, if any, after a push
+    fn wake_one(&self) {
+        fence(Ordering::SeqCst);
+        if self.sleepers.load(Ordering::SeqCst) > 0 {
+            let _guard = self.lock.lock().unwrap();
+            self.available.notify_one();
+        }
+    }
+}
+
+/// Take the oldest item of `lane`, `None` if it is empty
+fn steal<T>(lane: &Injector<T>) -> Option<T> {
+    loop {
+        match lane.steal() {
+            Steal::Success(item) => return Some(item),
+            Steal::Empty => return None,
+            Steal::Retry => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    fn drain(queue: &RenderQueue<&'static str>, count: usize) -> Vec<&'static str> {
+        (0..count).map(|_| queue.pop().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_strict_scheduling() {
+        let queue = RenderQueue::new(10, Scheduling::Strict);
+        for (priority, item) in [
+            (Priority::Bot, "bot"),
+            (Priority::Background, "background"),
+            (Priority::Interactive, "a"),
+            (Priority::Interactive, "b"),
+        ] {
+            assert!(matches!(queue.try_push(priority, item), Ok(None)));
+        }
+
+        assert_eq!(drain(&queue, 4), ["a", "b", "background", "bot"]);
+    }
+
+    #[test]
+    fn test_weighted_scheduling() {
+        let queue = RenderQueue::new(
+            100,
+            Scheduling::Weighted {
+                interactive: 3,
+                background: 1,
+                bot: 1,
+            },
+        );
+        for _ in 0..10 {
+            let _ = queue.try_push(Priority::Interactive, "interactive");
+            let _ = queue.try_push(Priority::Background, "background");
+            let _ = queue.try_push(Priority::Bot, "bot");
+        }
+
+        let served = drain(&queue, 10);
+        assert_eq!(
+            served[..5],
+            [
+                "interactive",
+                "interactive",
+                "interactive",
+                "background",
+                "bot"
+            ]
+        );
+        assert_eq!(served[5..], served[..5]);
+    }
+
+    #[test]
+    fn test_full_queue_sheds_lowest_priority() {
+        let queue = RenderQueue::new(2, Scheduling::Strict);
+        let _ = queue.try_push(Priority::Background, "background");
+        let _ = queue.try_push(Priority::Bot, "bot");
+
+        // The least important request makes room
+        assert!(matches!(
+            queue.try_push(Priority::Interactive, "a"),
+            Ok(Some("bot"))
+        ));
+        assert!(matches!(
+            queue.try_push(Priority::Background, "late"),
+            Err(PushError::Full("late"))
+        ));
+        assert!(matches!(
+            queue.try_push(Priority::Interactive, "b"),
+            Ok(Some("background"))
+        ));
+        assert_eq!(drain(&queue, 2), ["a", "b"]);
+    }
+
+    #[test]
+    fn test_close_drains_queue() {
+        let queue = RenderQueue::new(2, Scheduling::default());
+        let _ = queue.try_push(Priority::Interactive, "a");
+        queue.close();
+
+        assert!(matches!(
+            queue.try_push(Priority::Interactive, "b"),
+            Err(PushError::Closed("b"))
+        ));
+        assert_eq!(queue.pop(), Some("a"));
+        assert_eq!(queue.pop(), None);
+    }
+
+    #[tokio::test]
+    async fn test_full_queue_waits_for_room() {
+        let queue = Arc::new(RenderQueue::new(1, Scheduling::default()));
+        let _ = queue.try_push(Priority::Interactive, "a");
+
+        let room = queue.room();
+        assert!(matches!(
+            queue.try_push(Priority::Interactive, "b"),
+            Err(PushError::Full("b"))
+        ));
+        let worker = {
+            let queue = Arc::clone(&queue);
+            thread::spawn(move || queue.pop())
+        };
+        room.await;
+
+        assert!(matches!(
+            queue.try_push(Priority::Interactive, "b"),
+            Ok(None)
+        ));
+        assert_eq!(worker.join().unwrap(), Some("a"));
+    }
+
+    #[test]
+    fn test_sleeping_workers_wake_up() {
+        let queue = Arc::new(RenderQueue::new(1000, Scheduling::default()));
+        let workers: Vec<_> = (0..4)
+            .map(|_| {
+                let queue = Arc::clone(&queue);
+                thread::spawn(move || std::iter::from_fn(|| queue.pop()).count())
+            })
+            .collect();
+
+        for _ in 0..1000 {
+            while queue.try_push(Priority::Interactive, ()).is_err() {
+                thread::yield_now();
+            }
+        }
+        queue.close();
+
+        let popped: usize = workers.into_iter().map(|w| w.join().unwrap()).sum();
+        assert_eq!(popped, 1000);
+    }
+}
@@ -5,17 +5,21 @@ use deno_core::{v8, JsRuntime};
 /// Render HTML via V8 runtime
 ///
 /// Calls `globalThis.{render_function}(url, data)` and returns the result.
+/// Errors thrown by the bundle are rendered as an error page, unless `strict`
+/// is set, in which case they are returned as `Err`.
 ///
 /// # Arguments
 /// * `url` - The URL path to render
 /// * `data` - JSON string with data to pass to the render function
 /// * `render_function` - Name of the global render function
 /// * `js_runtime` - The V8 runtime to use
+/// * `strict` - Propagate render errors instead of returning an error page
 pub fn render_html(
     url: &str,
     data: Option<&str>,
     render_function: &str,
     js_runtime: &mut JsRuntime,
+    strict: bool,
 ) -> Result<String, String> {
     let data = data.unwrap_or("{}");
 
@@ -36,6 +40,9 @@ pub fn render_html(
                 }}
                 return await globalThis.{fn}("{url}", {data});
             }} catch (error) {{
+                if ({strict}) {{
+                    throw error;
+                }}
                 console.error("Render error:", error);
                 return `<html><body><h1>SSR Error</h1><pre>${{error.stack || error.message}}</pre></body></html>`;
             }}
@@ -43,7 +50,8 @@ pub fn render_html(
         "#,
         fn = render_function,
         url = escaped_url,
-        data = safe_data
+        data = safe_data,
+        strict = strict
     );
 
     // Execute the JS code
@@ -57,9 +65,70 @@ pub fn render_html(
         .map_err(|e| format!("Promise resolution error: {}", e))?;
 
     // Deserialize the result
+    let _span = tracing::info_span!("ssr.serialize").entered();
     let scope = &mut js_runtime.handle_scope();
     let local = v8::Local::new(scope, resolved);
 
     serde_v8::from_v8::<String>(scope, local)
         .map_err(|e| format!("Result deserialization error: {}", e))
 }
+
+/// Inject `<link rel="modulepreload">` tags for the given chunks
+///
+/// Tags are inserted before `</head>`, or prepended if the page has no head.
+/// `base` is the public URL prefix the chunks are served from (e.g. `/assets/`).
+pub fn inject_preload_links(html: &mut String, chunks: &[String], base: &str) {
+    if chunks.is_empty() {
+        return;
+    }
+
+    let base = base.trim_end_matches('/');
+    let links: String = chunks
+        .iter()
+        .map(|chunk| {
+            let href = format!("{}/{}", base, chunk.trim_start_matches('/'))
+                .replace('&', "&amp;")
+                .replace('"', "&quot;")
+                .replace('<', "&lt;");
+            format!(r#"<link rel="modulepreload" href="{}">"#, href)
+        })
+        .collect();
+
+    let position = html.find("</head>").unwrap_or(0);
+    html.insert_str(position, &links);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inject_preload_links_into_head() {
+        let mut html = "<html><head><title>x</title></head><body></body></html>".to_string();
+        inject_preload_links(&mut html, &["chunks/Lazy.js".to_string()], "/assets/");
+
+        assert_eq!(
+            html,
+            r#"<html><head><title>x</title><link rel="modulepreload" href="/assets/chunks/Lazy.js"></head><body></body></html>"#
+        );
+    }
+
+    #[test]
+    fn test_inject_preload_links_without_head() {
+        let mut html = "<div>app</div>".to_string();
+        inject_preload_links(&mut html, &["a.js".to_string()], "");
+
+        assert_eq!(
+            html,
+            r#"<link rel="modulepreload" href="/a.js"><div>app</div>"#
+        );
+    }
+
+    #[test]
+    fn test_no_chunks_leaves_html_untouched() {
+        let mut html = "<html><head></head></html>".to_string();
+        inject_preload_links(&mut html, &[], "/assets");
+
+        assert_eq!(html, "<html><head></head></html>");
+    }
+}
@@ -1,36 +1,60 @@
 //! Thread-local V8 runtime management
 
-use deno_core::{JsRuntime, RuntimeOptions};
-use std::cell::RefCell;
+use deno_core::{v8, JsRuntime, JsRuntimeForSnapshot, ModuleSpecifier, RuntimeOptions};
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 
 use super::bundle;
+use super::loader::SandboxedModuleLoader;
+use super::pool::V8PoolConfig;
+use super::{chunks, trace, web};
+use crate::config::RenderIsolation;
+
+/// Global under which the namespace of an ES module bundle is exposed
+///
+/// Render functions of module bundles are called as
+/// `globalThis.__rustySsrExports.{export}(url, data)`.
+pub const MODULE_EXPORTS_GLOBAL: &str = "__rustySsrExports";
+
+/// Installs `globalThis.__rustySsrResetGlobals` for [`RenderIsolation::ResetGlobals`]
+const ISOLATION_SCRIPT: &str = include_str!("js/isolation.js");
+
+/// Installs `globalThis.__rustySsrTrace` for bundle code
+const TRACE_SCRIPT: &str = include_str!("js/trace.js");
 
 thread_local! {
     /// Thread-local V8 runtime (each worker thread has its own)
     static JS_RUNTIME: RefCell<Option<JsRuntime>> = const { RefCell::new(None) };
+
+    /// Snapshot the current thread's runtimes are created from, if any
+    static STARTUP_SNAPSHOT: Cell<Option<&'static [u8]>> = const { Cell::new(None) };
 }
 
 /// Initialize the V8 runtime in the current thread
 ///
 /// This should be called once per worker thread.
-/// The runtime loads the SSR bundle and is ready to render.
-pub fn init_runtime() -> Result<(), String> {
+/// The runtime loads the SSR bundle and is ready to render, or is restored
+/// from `snapshot` if given, which already holds the evaluated bundle.
+/// Module imports are restricted to the bundle directory and the configured sandbox.
+pub fn init_runtime(config: &V8PoolConfig, snapshot: Option<&'static [u8]>) -> Result<(), String> {
+    STARTUP_SNAPSHOT.with(|startup| startup.set(snapshot));
+
     JS_RUNTIME.with(|runtime| {
         let mut runtime = runtime.borrow_mut();
 
         if runtime.is_none() {
-            let mut js_runtime = JsRuntime::new(RuntimeOptions {
-                module_loader: Some(Rc::new(deno_core::FsModuleLoader)),
-                ..Default::default()
-            });
+            let mut js_runtime = JsRuntime::new(runtime_options(config, snapshot));
 
-            // Load the cached SSR bundle (zero-copy - uses &'static str)
-            let bundle_code = bundle::get_bundle();
+            if snapshot.is_none() {
+                load_bundle(&mut js_runtime, config)?;
+            }
 
-            js_runtime
-                .execute_script("<ssr-bundle>", bundle_code)
-                .map_err(|e| format!("Failed to load SSR bundle: {}", e))?;
+            // Snapshot globals last, so everything the bundle set up is kept
+            if config.render_isolation == RenderIsolation::ResetGlobals {
+                js_runtime
+                    .execute_script("<ssr-isolation>", ISOLATION_SCRIPT)
+                    .map_err(|e| format!("Failed to install render isolation: {}", e))?;
+            }
 
             *runtime = Some(js_runtime);
 
@@ -44,6 +68,201 @@ pub fn init_runtime() -> Result<(), String> {
     })
 }
 
+/// Evaluate the bundle once and snapshot the resulting heap
+///
+/// Runtimes created from the snapshot start with the bundle, its module
+/// graph and any pre-loaded chunks already evaluated. The snapshot is
+/// leaked, as V8 needs it for as long as runtimes are created from it.
+pub fn create_snapshot(config: &V8PoolConfig) -> Result<&'static [u8], String> {
+    // The first runtime picks the V8 flags; snapshot mode would make
+    // `Math.random` and hashing deterministic for every later isolate
+    JsRuntime::init_platform(None, false);
+
+    let mut js_runtime = JsRuntimeForSnapshot::try_new(runtime_options(config, None))
+        .map_err(|e| format!("Failed to create snapshot runtime: {}", e))?;
+    load_bundle(&mut js_runtime, config)?;
+
+    Ok(Box::leak(js_runtime.snapshot()))
+}
+
+/// Options for the runtimes of a pool, restored from `startup_snapshot` if given
+///
+/// Extensions must be the same with and without a snapshot, as the
+/// snapshot refers to their ops.
+fn runtime_options(
+    config: &V8PoolConfig,
+    startup_snapshot: Option<&'static [u8]>,
+) -> RuntimeOptions {
+    RuntimeOptions {
+        module_loader: Some(Rc::new(SandboxedModuleLoader::new(
+            bundle::bundle_root(),
+            &config.module_sandbox,
+        ))),
+        extensions: vec![
+            web::rusty_ssr_web::init_ops(),
+            trace::rusty_ssr_trace::init_ops(),
+        ],
+        startup_snapshot,
+        ..Default::default()
+    }
+}
+
+/// Evaluate the SSR bundle, and the chunks it imports if configured
+fn load_bundle(js_runtime: &mut JsRuntime, config: &V8PoolConfig) -> Result<(), String> {
+    js_runtime
+        .execute_script("<ssr-trace>", TRACE_SCRIPT)
+        .map_err(|e| format!("Failed to install tracing API: {}", e))?;
+
+    // Load the cached SSR bundle (zero-copy - uses &'static str)
+    let bundle_code = bundle::get_bundle();
+
+    js_runtime
+        .execute_script("<ssr-bundle>", bundle_code)
+        .map_err(|e| format!("Failed to load SSR bundle: {}", e))?;
+
+    if let Some(module) = bundle::get_module() {
+        load_module_bundle(js_runtime, module)?;
+
+        if config.preload_chunks {
+            preload_chunks(js_runtime);
+        }
+    }
+
+    Ok(())
+}
+
+/// Check that every render function exists in the current thread's runtime
+///
+/// Functions are property paths on `globalThis`, e.g. `renderPage` or
+/// `__rustySsrExports.renderEmail`.
+pub fn check_render_functions(functions: &[String]) -> Result<(), String> {
+    let names = serde_json::to_string(functions)
+        .map_err(|e| format!("Failed to encode render functions: {}", e))?;
+    let script = format!(
+        "{}.filter((path) => typeof path.split('.').reduce((obj, key) => obj?.[key], globalThis) !== 'function')",
+        names
+    );
+
+    with_runtime(|js_runtime| {
+        let missing = js_runtime
+            .execute_script("<ssr-check>", script)
+            .map_err(|e| format!("Failed to check render functions: {}", e))?;
+
+        let scope = &mut js_runtime.handle_scope();
+        let local = v8::Local::new(scope, missing);
+        let missing = serde_v8::from_v8::<Vec<String>>(scope, local)
+            .map_err(|e| format!("Failed to read missing render functions: {}", e))?;
+
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(format!(
+                "Render function(s) not found in bundle: {}",
+                missing
+                    .iter()
+                    .map(|path| format!("globalThis.{}", path))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))
+        }
+    })
+}
+
+/// Discard the state a render left behind, according to `config.render_isolation`
+///
+/// Called by workers after the response has been sent.
+pub fn reset_after_render(config: &V8PoolConfig) -> Result<(), String> {
+    match config.render_isolation {
+        RenderIsolation::Shared => Ok(()),
+        RenderIsolation::ResetGlobals => with_runtime(|js_runtime| {
+            let mutated = js_runtime
+                .execute_script("<ssr-reset>", "globalThis.__rustySsrResetGlobals()")
+                .map_err(|e| format!("Failed to reset globals: {}", e))?;
+
+            let scope = &mut js_runtime.handle_scope();
+            let local = v8::Local::new(scope, mutated);
+            let mutated = serde_v8::from_v8::<Vec<String>>(scope, local)
+                .map_err(|e| format!("Failed to read mutated globals: {}", e))?;
+
+            if !mutated.is_empty() {
+                tracing::debug!("🧹 Render mutated globals: {}", mutated.join(", "));
+            }
+            Ok(())
+        }),
+        RenderIsolation::FreshRuntime => restart_runtime(config),
+    }
+}
+
+/// Discard the current thread's runtime and initialize a new one
+pub fn restart_runtime(config: &V8PoolConfig) -> Result<(), String> {
+    let old = JS_RUNTIME.with(|runtime| runtime.borrow_mut().take());
+    drop(old);
+    init_runtime(config, STARTUP_SNAPSHOT.with(Cell::get))
+}
+
+/// Load and evaluate an ES module bundle, then expose its namespace
+/// as `globalThis.__rustySsrExports`
+fn load_module_bundle(
+    js_runtime: &mut JsRuntime,
+    module: &bundle::ModuleBundle,
+) -> Result<(), String> {
+    let module_id = futures::executor::block_on(async {
+        let id = js_runtime
+            .load_main_es_module_from_code(&module.specifier, module.code.clone())
+            .await?;
+        let evaluation = js_runtime.mod_evaluate(id);
+        js_runtime.run_event_loop(Default::default()).await?;
+        evaluation.await?;
+        Ok::<_, deno_core::error::AnyError>(id)
+    })
+    .map_err(|e| format!("Failed to load SSR module bundle: {}", e))?;
+
+    let namespace = js_runtime
+        .get_module_namespace(module_id)
+        .map_err(|e| format!("Failed to read SSR module exports: {}", e))?;
+
+    let scope = &mut js_runtime.handle_scope();
+    let global = scope.get_current_context().global(scope);
+    let key = v8::String::new(scope, MODULE_EXPORTS_GLOBAL)
+        .ok_or_else(|| "Failed to allocate V8 string".to_string())?;
+    let namespace = v8::Local::new(scope, namespace);
+    global.set(scope, key.into(), namespace.into());
+
+    Ok(())
+}
+
+/// Load and evaluate the chunks the bundle entry can `import()`
+///
+/// Chunks stay in the isolate's module map, so later `import()` calls
+/// during renders resolve without touching the disk or recompiling. Files
+/// in the bundle directory the entry never imports are not evaluated.
+fn preload_chunks(js_runtime: &mut JsRuntime) {
+    let (Some(root), Some(graph)) = (bundle::bundle_root(), chunks::chunk_graph()) else {
+        return;
+    };
+
+    let mut loaded = 0usize;
+    for chunk in graph.lazy_chunks() {
+        let Ok(specifier) = ModuleSpecifier::from_file_path(root.join(chunk)) else {
+            continue;
+        };
+
+        let result = futures::executor::block_on(async {
+            let id = js_runtime.load_side_es_module(&specifier).await?;
+            let evaluation = js_runtime.mod_evaluate(id);
+            js_runtime.run_event_loop(Default::default()).await?;
+            evaluation.await
+        });
+
+        match result {
+            Ok(()) => loaded += 1,
+            Err(e) => tracing::warn!("⚠️ Failed to preload chunk {}: {}", specifier, e),
+        }
+    }
+
+    tracing::debug!("📦 Preloaded {} chunks", loaded);
+}
+
 /// Execute a function with access to the thread-local V8 runtime
 pub fn with_runtime<F, R>(f: F) -> R
 where
@@ -57,3 +276,12 @@ where
         f(js_runtime)
     })
 }
+
+/// Bytes of V8 heap in use by the current thread's isolate
+pub fn heap_used_bytes() -> usize {
+    with_runtime(|js_runtime| {
+        let mut stats = v8::HeapStatistics::default();
+        js_runtime.v8_isolate().get_heap_statistics(&mut stats);
+        stats.used_heap_size()
+    })
+}
@@ -0,0 +1,454 @@
+//! Pool statistics and metrics
+
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use super::pool::PoolError;
+use crate::metrics::{
+    Counter, Family, Histogram, MetricKind, Registry, TextEncoder, DEFAULT_BUCKETS,
+};
+
+/// Number of recent renders render time percentiles are computed over
+const RECENT_RENDERS: usize = 1024;
+
+/// Snapshot of the pool's load and per-worker state, see [`V8Pool::stats`](super::V8Pool::stats)
+#[derive(Debug, Clone, Default)]
+pub struct PoolStats {
+    /// Requests waiting for a worker
+    pub queue_depth: usize,
+    /// Requests being rendered
+    pub in_flight: usize,
+    /// Requests skipped before rendering because their caller went away
+    pub cancelled: u64,
+    /// Requests skipped before rendering because their deadline passed
+    pub expired: u64,
+    /// Isolate restarts of all workers
+    pub restarts: u64,
+    /// Most recent error of any request or worker
+    pub last_error: Option<String>,
+    /// Median render time of the last 1024 renders
+    pub render_time_p50: Duration,
+    /// 95th percentile render time of the last 1024 renders
+    pub render_time_p95: Duration,
+    /// 99th percentile render time of the last 1024 renders
+    pub render_time_p99: Duration,
+    /// Per-worker statistics, indexed by worker id
+    pub workers: Vec<WorkerStats>,
+}
+
+/// Statistics of one pool worker
+#[derive(Debug, Clone, Default)]
+pub struct WorkerStats {
+    /// Worker id
+    pub id: usize,
+    /// The worker initialized and has not stopped
+    pub alive: bool,
+    /// The worker is rendering
+    pub busy: bool,
+    /// Requests processed
+    pub processed: u64,
+    /// Time spent rendering and resetting render state
+    pub busy_time: Duration,
+    /// Used V8 heap of the worker's isolate after its last render
+    pub heap_used_bytes: u64,
+    /// Times the isolate was recreated after failing to reset
+    pub restarts: u64,
+    /// Most recent render or reset error
+    pub last_error: Option<String>,
+}
+
+/// State of one worker, updated by its thread
+#[derive(Default)]
+struct WorkerMetrics {
+    alive: AtomicBool,
+    busy: AtomicBool,
+    processed: AtomicU64,
+    busy_nanos: AtomicU64,
+    heap_used: AtomicU64,
+    restarts: AtomicU64,
+    last_error: Mutex<Option<String>>,
+}
+
+/// Ring buffer of the most recent render times
+struct RecentDurations {
+    nanos: Box<[AtomicU64]>,
+    next: AtomicUsize,
+}
+
+impl RecentDurations {
+    fn new(capacity: usize) -> Self {
+        Self {
+            nanos: (0..capacity).map(|_| AtomicU64::new(0)).collect(),
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    fn record(&self, duration: Duration) {
+        let slot = self.next.fetch_add(1, Ordering::Relaxed) % self.nanos.len();
+        self.nanos[slot].store(duration.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    /// Nearest-rank percentiles, `quantiles` in `0.0..=1.0`
+    fn percentiles<const N: usize>(&self, quantiles: [f64; N]) -> [Duration; N] {
+        let len = self.next.load(Ordering::Relaxed).min(self.nanos.len());
+        let mut sorted: Vec<u64> = self.nanos[..len]
+            .iter()
+            .map(|nanos| nanos.load(Ordering::Relaxed))
+            .collect();
+        sorted.sort_unstable();
+
+        quantiles.map(|q| {
+            let rank = ((q * len as f64).ceil() as usize).clamp(1, len.max(1));
+            sorted
+                .get(rank - 1)
+                .map_or(Duration::ZERO, |&nanos| Duration::from_nanos(nanos))
+        })
+    }
+}
+
+/// A request counted in the queue depth while it is being enqueued
+pub(crate) struct QueueSlot<'a> {
+    metrics: &'a PoolMetrics,
+    pushed: bool,
+}
+
+impl QueueSlot<'_> {
+    /// The request is in the queue; a worker uncounts it when taking it
+    pub(crate) fn pushed(mut self) {
+        self.pushed = true;
+    }
+}
+
+impl Drop for QueueSlot<'_> {
+    fn drop(&mut self) {
+        if !self.pushed {
+            self.metrics.unqueued();
+        }
+    }
+}
+
+/// Metrics shared by the pool and its workers
+pub(crate) struct PoolMetrics {
+    registry: Registry,
+    /// Requests waiting for a worker
+    queue_depth: AtomicUsize,
+    /// Time from enqueueing a request until a worker picks it up
+    queue_wait: Arc<Histogram>,
+    /// Time spent rendering in V8
+    render_time: Arc<Histogram>,
+    /// Failed requests by [`PoolError::kind`]
+    errors: Arc<Family<Counter>>,
+    /// Requests skipped by a worker because their caller went away
+    cancelled: Arc<Counter>,
+    /// Requests skipped by a worker because their deadline passed
+    expired: Arc<Counter>,
+    restarts: Arc<Counter>,
+    recent_renders: RecentDurations,
+    last_error: Mutex<Option<String>>,
+    workers: Box<[WorkerMetrics]>,
+}
+
+impl PoolMetrics {
+    pub(crate) fn new(num_workers: usize) -> Self {
+        let registry = Registry::new();
+        let skipped = registry.counter_family(
+            "ssr_pool_skipped_total",
+            "Queued requests dropped before rendering, by reason",
+            &["reason"],
+        );
+        Self {
+            queue_depth: AtomicUsize::new(0),
+            queue_wait: registry.histogram(
+                "ssr_pool_queue_wait_seconds",
+                "Time render requests waited for a worker",
+                DEFAULT_BUCKETS,
+            ),
+            render_time: registry.histogram(
+                "ssr_pool_render_duration_seconds",
+                "Time spent rendering in V8",
+                DEFAULT_BUCKETS,
+            ),
+            errors: registry.counter_family(
+                "ssr_pool_errors_total",
+                "Failed render requests by error kind",
+                &["kind"],
+            ),
+            cancelled: skipped.with_labels(&["cancelled"]),
+            expired: skipped.with_labels(&["expired"]),
+            restarts: registry.counter(
+                "ssr_pool_restarts_total",
+                "Isolates recreated after failing to reset",
+            ),
+            registry,
+            recent_renders: RecentDurations::new(RECENT_RENDERS),
+            last_error: Mutex::new(None),
+            workers: (0..num_workers).map(|_| WorkerMetrics::default()).collect(),
+        }
+    }
+
+    /// A request is about to be enqueued
+    ///
+    /// The request counts as queued until the returned slot is dropped
+    /// without [`QueueSlot::pushed`], e.g. when the caller goes away while
+    /// waiting for room in the queue.
+    pub(crate) fn queued(&self) -> QueueSlot<'_> {
+        self.queue_depth.fetch_add(1, Ordering::Relaxed);
+        QueueSlot {
+            metrics: self,
+            pushed: false,
+        }
+    }
+
+    /// A queued request left the queue without being rendered
+    pub(crate) fn unqueued(&self) {
+        self.queue_depth.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_error(&self, error: &PoolError) {
+        self.errors.with_labels(&[error.kind()]).inc();
+        *self.last_error.lock().unwrap() = Some(error.to_string());
+    }
+
+    pub(crate) fn worker_started(&self, id: usize, heap_used: usize) {
+        if let Some(worker) = self.workers.get(id) {
+            worker.alive.store(true, Ordering::Relaxed);
+            worker.heap_used.store(heap_used as u64, Ordering::Relaxed);
+        }
+    }
+
+    pub(crate) fn worker_stopped(&self, id: usize) {
+        if let Some(worker) = self.workers.get(id) {
+            worker.alive.store(false, Ordering::Relaxed);
+        }
+    }
+
+    /// Worker `id` picked up a request that waited `queue_wait`
+    pub(crate) fn render_started(&self, id: usize, queue_wait: Duration) {
+        self.queue_depth.fetch_sub(1, Ordering::Relaxed);
+        self.queue_wait.observe_duration(queue_wait);
+        if let Some(worker) = self.workers.get(id) {
+            worker.busy.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// A worker dropped a request whose caller went away
+    pub(crate) fn cancelled(&self) {
+        self.queue_depth.fetch_sub(1, Ordering::Relaxed);
+        self.cancelled.inc();
+    }
+
+    /// A worker dropped a request whose deadline passed
+    pub(crate) fn expired(&self) {
+        self.queue_depth.fetch_sub(1, Ordering::Relaxed);
+        self.expired.inc();
+    }
+
+    /// Worker `id` rendered a request in `render_time`
+    ///
+    /// Recorded before the caller is answered, so it sees its own render.
+    pub(crate) fn render_finished(&self, id: usize, render_time: Duration) {
+        self.render_time.observe_duration(render_time);
+        self.recent_renders.record(render_time);
+        if let Some(worker) = self.workers.get(id) {
+            worker.processed.fetch_add(1, Ordering::Relaxed);
+            worker
+                .busy_nanos
+                .fetch_add(render_time.as_nanos() as u64, Ordering::Relaxed);
+            worker.busy.store(false, Ordering::Relaxed);
+        }
+    }
+
+    /// A worker reset its render state after answering
+    pub(crate) fn reset_finished(&self, id: usize, reset_time: Duration) {
+        if let Some(worker) = self.workers.get(id) {
+            worker
+                .busy_nanos
+                .fetch_add(reset_time.as_nanos() as u64, Ordering::Relaxed);
+        }
+    }
+
+    pub(crate) fn worker_error(&self, id: usize, error: &str) {
+        if let Some(worker) = self.workers.get(id) {
+            *worker.last_error.lock().unwrap() = Some(error.to_string());
+        }
+        *self.last_error.lock().unwrap() = Some(error.to_string());
+    }
+
+    pub(crate) fn worker_restarted(&self, id: usize) {
+        self.restarts.inc();
+        if let Some(worker) = self.workers.get(id) {
+            worker.restarts.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub(crate) fn set_heap_used(&self, id: usize, bytes: usize) {
+        if let Some(worker) = self.workers.get(id) {
+            worker.heap_used.store(bytes as u64, Ordering::Relaxed);
+        }
+    }
+
+    /// Requests processed by worker `id`
+    pub(crate) fn processed(&self, id: usize) -> u64 {
+        self.workers
+            .get(id)
+            .map_or(0, |worker| worker.processed.load(Ordering::Relaxed))
+    }
+
+    pub(crate) fn stats(&self) -> PoolStats {
+        let workers: Vec<WorkerStats> = self
+            .workers
+            .iter()
+            .enumerate()
+            .map(|(id, worker)| WorkerStats {
+                id,
+                alive: worker.alive.load(Ordering::Relaxed),
+                busy: worker.busy.load(Ordering::Relaxed),
+                processed: worker.processed.load(Ordering::Relaxed),
+                busy_time: Duration::from_nanos(worker.busy_nanos.load(Ordering::Relaxed)),
+                heap_used_bytes: worker.heap_used.load(Ordering::Relaxed),
+                restarts: worker.restarts.load(Ordering::Relaxed),
+                last_error: worker.last_error.lock().unwrap().clone(),
+            })
+            .collect();
+        let [p50, p95, p99] = self.recent_renders.percentiles([0.5, 0.95, 0.99]);
+
+        PoolStats {
+            queue_depth: self.queue_depth.load(Ordering::Relaxed),
+            in_flight: workers.iter().filter(|worker| worker.busy).count(),
+            cancelled: self.cancelled.get(),
+            expired: self.expired.get(),
+            restarts: self.restarts.get(),
+            last_error: self.last_error.lock().unwrap().clone(),
+            render_time_p50: p50,
+            render_time_p95: p95,
+            render_time_p99: p99,
+            workers,
+        }
+    }
+
+    /// Write the metrics in the Prometheus text format
+    pub(crate) fn encode(&self, encoder: &mut TextEncoder) {
+        let stats = self.stats();
+        let alive: Vec<&WorkerStats> = stats.workers.iter().filter(|w| w.alive).collect();
+
+        encoder.header(
+            "ssr_pool_queue_depth",
+            "Render requests waiting for a worker",
+            MetricKind::Gauge,
+        );
+        encoder.sample("ssr_pool_queue_depth", &[], stats.queue_depth as f64);
+
+        let busy = alive.iter().filter(|w| w.busy).count();
+        encoder.header(
+            "ssr_pool_workers",
+            "Active V8 workers by state",
+            MetricKind::Gauge,
+        );
+        encoder.sample("ssr_pool_workers", &[("state", "busy")], busy as f64);
+        encoder.sample(
+            "ssr_pool_workers",
+            &[("state", "idle")],
+            (alive.len() - busy) as f64,
+        );
+
+        encoder.header(
+            "ssr_pool_worker_renders_total",
+            "Requests processed by each worker",
+            MetricKind::Counter,
+        );
+        for worker in &alive {
+            encoder.sample(
+                "ssr_pool_worker_renders_total",
+                &[("worker", &worker.id.to_string())],
+                worker.processed as f64,
+            );
+        }
+
+        encoder.header(
+            "ssr_pool_worker_busy_seconds_total",
+            "Time each worker spent rendering",
+            MetricKind::Counter,
+        );
+        for worker in &alive {
+            encoder.sample(
+                "ssr_pool_worker_busy_seconds_total",
+                &[("worker", &worker.id.to_string())],
+                worker.busy_time.as_secs_f64(),
+            );
+        }
+
+        encoder.header(
+            "ssr_isolate_heap_used_bytes",
+            "Used V8 heap of each worker's isolate",
+            MetricKind::Gauge,
+        );
+        for worker in &alive {
+            encoder.sample(
+                "ssr_isolate_heap_used_bytes",
+                &[("worker", &worker.id.to_string())],
+                worker.heap_used_bytes as f64,
+            );
+        }
+
+        self.registry.encode(encoder);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_time_percentiles() {
+        let recent = RecentDurations::new(100);
+        assert_eq!(recent.percentiles([0.5]), [Duration::ZERO]);
+
+        // Older samples are overwritten once the buffer is full
+        for ms in (1..=100).chain([1000; 10]) {
+            recent.record(Duration::from_millis(ms));
+        }
+
+        let [p50, p95, p99] = recent.percentiles([0.5, 0.95, 0.99]);
+        assert_eq!(p50, Duration::from_millis(60));
+        assert_eq!(p95, Duration::from_millis(1000));
+        assert_eq!(p99, Duration::from_millis(1000));
+    }
+
+    #[test]
+    fn test_worker_stats() {
+        let metrics = PoolMetrics::new(2);
+        metrics.worker_started(0, 1024);
+        for _ in 0..4 {
+            metrics.queued().pushed();
+        }
+        // Requests that never made it into the queue are uncounted
+        drop(metrics.queued());
+        metrics.render_started(0, Duration::from_millis(1));
+        metrics.cancelled();
+        metrics.expired();
+
+        let stats = metrics.stats();
+        assert_eq!(stats.queue_depth, 1);
+        assert_eq!((stats.cancelled, stats.expired), (1, 1));
+        assert_eq!(stats.in_flight, 1);
+        assert!(stats.workers[0].alive && !stats.workers[1].alive);
+
+        metrics.render_finished(0, Duration::from_millis(5));
+        metrics.reset_finished(0, Duration::from_millis(1));
+        metrics.worker_error(0, "reset failed");
+        metrics.worker_restarted(0);
+
+        let stats = metrics.stats();
+        assert_eq!(stats.in_flight, 0);
+        assert_eq!(stats.restarts, 1);
+        assert_eq!(stats.render_time_p99, Duration::from_millis(5));
+        assert_eq!(stats.last_error.as_deref(), Some("reset failed"));
+
+        let worker = &stats.workers[0];
+        assert_eq!(worker.processed, 1);
+        assert_eq!(worker.busy_time, Duration::from_millis(6));
+        assert_eq!(worker.heap_used_bytes, 1024);
+        assert_eq!(worker.last_error.as_deref(), Some("reset failed"));
+    }
+}
@@ -0,0 +1,112 @@
+//! Rust-backed ops for tracing from bundle code
+//!
+//! The JS side lives in `js/trace.js` and exposes `globalThis.__rustySsrTrace`.
+//! Events and spans recorded there become children of the innermost span the
+//! bundle has open, or of the `ssr.v8_execute` span of the render, under the
+//! `rusty_ssr::js` target. Span names chosen by the bundle are set as
+//! `otel.name`, which OpenTelemetry layers use as the exported span name.
+
+use deno_core::{extension, op2};
+use std::cell::RefCell;
+
+extension!(
+    rusty_ssr_trace,
+    ops = [op_ssr_trace_event, op_ssr_trace_enter, op_ssr_trace_exit],
+);
+
+/// Spans the bundle opened during the current render
+///
+/// A runtime lives on one worker thread, so the stack is per runtime. Spans
+/// are not entered while the bundle awaits, so `tracing`'s current span
+/// stays `ssr.v8_execute`; parents are taken from this stack instead.
+#[derive(Default)]
+struct JsSpans {
+    next: u32,
+    /// Open spans by handle, innermost last
+    open: Vec<(u32, tracing::Span)>,
+}
+
+thread_local! {
+    static JS_SPANS: RefCell<JsSpans> = RefCell::new(JsSpans::default());
+}
+
+/// Record an event with JSON-encoded attributes
+#[op2(fast)]
+fn op_ssr_trace_event(#[string] name: String, #[string] attributes: String) {
+    let parent = innermost_span();
+    tracing::info!(target: "rusty_ssr::js", parent: &parent, attributes = %attributes, "{}", name);
+}
+
+/// Open a span, returning its handle
+#[op2(fast)]
+fn op_ssr_trace_enter(#[string] name: String, #[string] attributes: String) -> u32 {
+    open_span(&name, &attributes)
+}
+
+/// Close the span with the given handle
+#[op2(fast)]
+fn op_ssr_trace_exit(handle: u32) {
+    close_span(handle);
+}
+
+/// The innermost span the bundle has open, or the current span
+fn innermost_span() -> tracing::Span {
+    JS_SPANS
+        .with(|spans| spans.borrow().open.last().map(|(_, span)| span.clone()))
+        .unwrap_or_else(tracing::Span::current)
+}
+
+fn open_span(name: &str, attributes: &str) -> u32 {
+    let parent = innermost_span();
+    let span = tracing::info_span!(
+        target: "rusty_ssr::js",
+        parent: &parent,
+        "ssr.js",
+        otel.name = %name,
+        attributes = %attributes
+    );
+
+    JS_SPANS.with(|spans| {
+        let spans = &mut *spans.borrow_mut();
+        spans.next = spans.next.wrapping_add(1);
+        spans.open.push((spans.next, span));
+        spans.next
+    })
+}
+
+fn close_span(handle: u32) {
+    let span = JS_SPANS.with(|spans| {
+        let open = &mut spans.borrow_mut().open;
+        let index = open.iter().rposition(|(h, _)| *h == handle)?;
+        Some(open.remove(index))
+    });
+    drop(span);
+}
+
+/// Close the spans the bundle left open, called after each render
+pub fn close_open_spans() {
+    let spans = JS_SPANS.with(|spans| std::mem::take(&mut spans.borrow_mut().open));
+    drop(spans);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spans_close_by_handle() {
+        let first = open_span("fetchProducts", "{}");
+        let second = open_span("renderList", "{}");
+        assert_ne!(first, second);
+
+        // Out of order, as when concurrent promises settle
+        close_span(first);
+        JS_SPANS.with(|spans| {
+            let open: Vec<u32> = spans.borrow().open.iter().map(|(h, _)| *h).collect();
+            assert_eq!(open, [second]);
+        });
+
+        close_open_spans();
+        JS_SPANS.with(|spans| assert!(spans.borrow().open.is_empty()));
+    }
+}
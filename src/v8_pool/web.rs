@@ -0,0 +1,190 @@
+//! Rust-backed ops for the web platform polyfills
+//!
+//! `URL` parsing follows the WHATWG URL standard via the `url` crate, and
+//! `crypto.getRandomValues` draws from the OS random source. The JS side
+//! lives in `js/web_apis.js` and is installed by [`Polyfills::web_apis`].
+//!
+//! [`Polyfills::web_apis`]: super::Polyfills::web_apis
+
+use deno_core::error::{generic_error, AnyError};
+use deno_core::url::{quirks, Url};
+use deno_core::{extension, op2};
+use serde::Serialize;
+
+/// Largest buffer `crypto.getRandomValues` accepts, per the Web Crypto spec
+const MAX_RANDOM_BYTES: usize = 65536;
+
+extension!(
+    rusty_ssr_web,
+    ops = [op_ssr_random_fill, op_ssr_url_parse, op_ssr_url_set],
+);
+
+/// Components of a parsed URL, as exposed on `URL` instances
+#[derive(Debug, Serialize)]
+pub struct UrlParts {
+    href: String,
+    origin: String,
+    protocol: String,
+    username: String,
+    password: String,
+    host: String,
+    hostname: String,
+    port: String,
+    pathname: String,
+    search: String,
+    hash: String,
+}
+
+impl From<&Url> for UrlParts {
+    fn from(url: &Url) -> Self {
+        Self {
+            href: quirks::href(url).to_string(),
+            origin: quirks::origin(url),
+            protocol: quirks::protocol(url).to_string(),
+            username: quirks::username(url).to_string(),
+            password: quirks::password(url).to_string(),
+            host: quirks::host(url).to_string(),
+            hostname: quirks::hostname(url).to_string(),
+            port: quirks::port(url).to_string(),
+            pathname: quirks::pathname(url).to_string(),
+            search: quirks::search(url).to_string(),
+            hash: quirks::hash(url).to_string(),
+        }
+    }
+}
+
+/// Fill a buffer with cryptographically secure random bytes
+#[op2(fast)]
+fn op_ssr_random_fill(#[buffer] buf: &mut [u8]) -> Result<(), AnyError> {
+    fill_random(buf)
+}
+
+/// Parse `href`, optionally relative to `base`
+#[op2]
+#[serde]
+fn op_ssr_url_parse(
+    #[string] href: String,
+    #[string] base: Option<String>,
+) -> Result<UrlParts, AnyError> {
+    parse_url(&href, base.as_deref())
+}
+
+/// Apply a `URL` setter (`pathname`, `search`, ...) and return the new components
+#[op2]
+#[serde]
+fn op_ssr_url_set(
+    #[string] href: String,
+    #[string] component: String,
+    #[string] value: String,
+) -> Result<UrlParts, AnyError> {
+    set_url_component(&href, &component, &value)
+}
+
+fn fill_random(buf: &mut [u8]) -> Result<(), AnyError> {
+    if buf.len() > MAX_RANDOM_BYTES {
+        return Err(generic_error(format!(
+            "getRandomValues: {} bytes exceeds the maximum of {}",
+            buf.len(),
+            MAX_RANDOM_BYTES
+        )));
+    }
+    getrandom::getrandom(buf).map_err(|e| generic_error(format!("getRandomValues: {}", e)))
+}
+
+fn parse_url(href: &str, base: Option<&str>) -> Result<UrlParts, AnyError> {
+    let url = match base {
+        Some(base) => Url::parse(base).and_then(|base| base.join(href)),
+        None => Url::parse(href),
+    }
+    .map_err(|e| generic_error(format!("Invalid URL '{}': {}", href, e)))?;
+
+    Ok(UrlParts::from(&url))
+}
+
+fn set_url_component(href: &str, component: &str, value: &str) -> Result<UrlParts, AnyError> {
+    let mut url =
+        Url::parse(href).map_err(|e| generic_error(format!("Invalid URL '{}': {}", href, e)))?;
+
+    // Setters silently ignore invalid values, as in browsers
+    match component {
+        "href" => {
+            url = Url::parse(value)
+                .map_err(|e| generic_error(format!("Invalid URL '{}': {}", value, e)))?;
+        }
+        "protocol" => {
+            let _ = quirks::set_protocol(&mut url, value);
+        }
+        "username" => {
+            let _ = quirks::set_username(&mut url, value);
+        }
+        "password" => {
+            let _ = quirks::set_password(&mut url, value);
+        }
+        "host" => {
+            let _ = quirks::set_host(&mut url, value);
+        }
+        "hostname" => {
+            let _ = quirks::set_hostname(&mut url, value);
+        }
+        "port" => {
+            let _ = quirks::set_port(&mut url, value);
+        }
+        "pathname" => quirks::set_pathname(&mut url, value),
+        "search" => quirks::set_search(&mut url, value),
+        "hash" => quirks::set_hash(&mut url, value),
+        other => return Err(generic_error(format!("Unknown URL component: {}", other))),
+    }
+
+    Ok(UrlParts::from(&url))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_absolute_url() {
+        let parts = parse_url("https://user:pw@example.com:8080/a/b?x=1#top", None).unwrap();
+        assert_eq!(parts.origin, "https://example.com:8080");
+        assert_eq!(parts.protocol, "https:");
+        assert_eq!(parts.username, "user");
+        assert_eq!(parts.hostname, "example.com");
+        assert_eq!(parts.port, "8080");
+        assert_eq!(parts.pathname, "/a/b");
+        assert_eq!(parts.search, "?x=1");
+        assert_eq!(parts.hash, "#top");
+    }
+
+    #[test]
+    fn test_parse_relative_url() {
+        let parts = parse_url("../c?y=2", Some("https://example.com/a/b/")).unwrap();
+        assert_eq!(parts.href, "https://example.com/a/c?y=2");
+    }
+
+    #[test]
+    fn test_parse_invalid_url() {
+        assert!(parse_url("/no-base", None).is_err());
+    }
+
+    #[test]
+    fn test_set_components() {
+        let parts = set_url_component("https://example.com/a", "pathname", "/b c").unwrap();
+        assert_eq!(parts.href, "https://example.com/b%20c");
+
+        let parts = set_url_component(&parts.href, "search", "q=1").unwrap();
+        assert_eq!(parts.search, "?q=1");
+
+        let parts = set_url_component(&parts.href, "port", "not-a-port").unwrap();
+        assert_eq!(parts.port, "");
+    }
+
+    #[test]
+    fn test_fill_random() {
+        let mut buf = [0u8; 32];
+        fill_random(&mut buf).unwrap();
+        assert!(buf.iter().any(|&b| b != 0));
+
+        let mut too_big = vec![0u8; MAX_RANDOM_BYTES + 1];
+        assert!(fill_random(&mut too_big).is_err());
+    }
+}
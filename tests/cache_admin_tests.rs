@@ -0,0 +1,113 @@
+//! Cache Admin API Tests for Rusty-SSR
+//!
+//! Run with: `cargo test --test cache_admin_tests`
+
+#[cfg(all(
+    test,
+    feature = "v8-pool",
+    feature = "cache",
+    feature = "axum-integration"
+))]
+mod cache_admin_tests {
+    use axum::body::{to_bytes, Body};
+    use axum::http::{header, Method, Request, StatusCode};
+    use axum::Router;
+    use rusty_ssr::middleware::admin_router;
+    use rusty_ssr::SsrEngine;
+    use serde_json::Value;
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    const TEST_BUNDLE: &str = r#"
+        globalThis.renderPage = async function(url, data) {
+            return '<html><body><h1>' + url + '</h1></body></html>';
+        };
+    "#;
+    const TOKEN: &str = "s3cret";
+
+    async fn call(
+        app: &Router,
+        method: Method,
+        uri: &str,
+        body: Option<Value>,
+    ) -> (StatusCode, Value) {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(header::AUTHORIZATION, format!("Bearer {}", TOKEN))
+            .header(header::CONTENT_TYPE, "application/json");
+        let body = body.map_or_else(Body::empty, |body| Body::from(body.to_string()));
+
+        let response = app
+            .clone()
+            .oneshot(request.body(body).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (
+            status,
+            serde_json::from_slice(&bytes).unwrap_or(Value::Null),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_admin_endpoints() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundle_path = dir.path().join("admin-bundle.js");
+        std::fs::write(&bundle_path, TEST_BUNDLE).unwrap();
+
+        let engine = Arc::new(
+            SsrEngine::builder()
+                .bundle_path(&bundle_path)
+                .pool_size(1)
+                .cache_size(100)
+                .build_engine()
+                .expect("Failed to create test engine"),
+        );
+        for url in ["/blog/1", "/blog/2", "/about"] {
+            engine.render(url).await.unwrap();
+        }
+        let app = admin_router(Arc::clone(&engine), TOKEN);
+
+        // Requests without the token are rejected
+        let response = app
+            .clone()
+            .oneshot(Request::get("/entries").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let (status, page) = call(&app, Method::GET, "/entries?prefix=/blog&limit=1", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(page["total"], 2);
+        assert_eq!(page["entries"][0]["url"], "/blog/1");
+
+        let (status, entry) = call(&app, Method::GET, "/entry?url=/about", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(entry["html"].as_str().unwrap().contains("<h1>/about</h1>"));
+        let (status, _) = call(&app, Method::GET, "/entry?url=/missing", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (_, snapshot) = call(&app, Method::GET, "/snapshot", None).await;
+        assert_eq!(snapshot["entries"].as_array().unwrap().len(), 3);
+
+        let (status, removed) = call(
+            &app,
+            Method::POST,
+            "/invalidate",
+            Some(serde_json::json!({ "prefix": "/blog" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(removed["removed"], 2);
+
+        let (status, _) = call(&app, Method::POST, "/clear", None).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        assert_eq!(engine.cache().size(), 0);
+
+        let (_, imported) = call(&app, Method::POST, "/snapshot", Some(snapshot)).await;
+        assert_eq!(imported["imported"], 3);
+        assert!(engine.cache().try_get("/blog/2").is_some());
+    }
+}
@@ -0,0 +1,49 @@
+//! Shared Cache Backend Tests for Rusty-SSR
+//!
+//! Run with: `cargo test --test cache_backend_tests`
+
+#[cfg(all(test, feature = "v8-pool", feature = "cache"))]
+mod cache_backend_tests {
+    use rusty_ssr::cache::MemoryBackend;
+    use rusty_ssr::SsrEngine;
+    use std::sync::Arc;
+
+    const TEST_BUNDLE: &str = r#"
+        globalThis.renderPage = async function(url, data) {
+            return '<html><body>' + url + ' ' + Math.random() + '</body></html>';
+        };
+    "#;
+
+    #[tokio::test]
+    async fn test_replicas_share_rendered_pages() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundle_path = dir.path().join("backend-bundle.js");
+        std::fs::write(&bundle_path, TEST_BUNDLE).unwrap();
+
+        let shared = Arc::new(MemoryBackend::new());
+        let replica = || {
+            SsrEngine::builder()
+                .bundle_path(&bundle_path)
+                .pool_size(1)
+                .cache_size(100)
+                .cache_backend(shared.clone())
+                .build_engine()
+                .expect("Failed to create test engine")
+        };
+        let replica_a = replica();
+        let replica_b = replica();
+
+        let html = replica_a.render("/products").await.unwrap();
+        // Write-through runs in the background
+        for _ in 0..100 {
+            if !shared.is_empty() {
+                break;
+            }
+            tokio::task::yield_now().await;
+        }
+
+        // Rendered pages are random, so an equal page came from the backend
+        assert_eq!(replica_b.render("/products").await.unwrap(), html);
+        assert_eq!(replica_b.cache_metrics().remote_hits, 1);
+    }
+}
@@ -0,0 +1,57 @@
+//! Per-Route Cache Policy Tests for Rusty-SSR
+//!
+//! Run with: `cargo test --test cache_route_tests`
+
+#[cfg(all(test, feature = "v8-pool", feature = "cache"))]
+mod cache_route_tests {
+    use rusty_ssr::cache::RoutePolicy;
+    use rusty_ssr::SsrEngine;
+    use std::time::Duration;
+
+    const TEST_BUNDLE: &str = r#"
+        globalThis.renderPage = async function(url, data) {
+            return '<html><body><h1>' + url + '</h1></body></html>';
+        };
+    "#;
+
+    #[tokio::test]
+    async fn test_render_follows_route_policies() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundle_path = dir.path().join("route-bundle.js");
+        std::fs::write(&bundle_path, TEST_BUNDLE).unwrap();
+
+        let engine = SsrEngine::builder()
+            .bundle_path(&bundle_path)
+            .pool_size(1)
+            .cache_size(100)
+            .cache_route("/checkout/**", RoutePolicy::no_store())
+            .cache_route(
+                "/products/:id",
+                RoutePolicy::new()
+                    .ttl(Duration::from_secs(60))
+                    .vary_query(["variant"]),
+            )
+            .build_engine()
+            .expect("Failed to create test engine");
+
+        for _ in 0..2 {
+            let html = engine.render("/checkout/pay").await.unwrap();
+            assert!(html.contains("<h1>/checkout/pay</h1>"));
+        }
+        assert!(engine.cache().try_get("/checkout/pay").is_none());
+
+        engine.render("/products/1?utm_source=mail").await.unwrap();
+        // Only `variant` is part of the key, so this is a hit
+        let html = engine.render("/products/1?utm_source=ads").await.unwrap();
+        assert!(html.contains("<h1>/products/1?utm_source=mail</h1>"));
+        engine.render("/products/1?variant=red").await.unwrap();
+
+        let routes = engine.cache_metrics().routes;
+        assert_eq!(routes[0].pattern, "/checkout/**");
+        assert!(routes[0].no_store);
+        assert_eq!(routes[0].bypasses, 2);
+        assert_eq!(routes[1].ttl_secs, Some(60));
+        assert_eq!(routes[1].hits, 1);
+        assert_eq!(routes[1].misses, 2);
+    }
+}
@@ -44,6 +44,7 @@ mod pool_config_tests {
             pin_threads: true,
             request_timeout: Some(std::time::Duration::from_secs(1)),
             render_function: "customRender".to_string(),
+            ..Default::default()
         };
 
         assert_eq!(config.num_threads, 4);
@@ -61,6 +62,7 @@ mod pool_config_tests {
             pin_threads: false,
             request_timeout: None,
             render_function: "render".to_string(),
+            ..Default::default()
         };
 
         let cloned = config.clone();
@@ -80,6 +82,7 @@ mod pool_config_tests {
 #[cfg(all(test, feature = "v8-pool"))]
 mod pool_timeout_tests {
     use rusty_ssr::v8_pool::{PoolError, V8Pool, V8PoolConfig};
+    use rusty_ssr::{Priority, RenderOptions};
     use std::time::Duration;
 
     #[tokio::test]
@@ -90,6 +93,7 @@ mod pool_timeout_tests {
             pin_threads: false,
             request_timeout: Some(Duration::from_millis(5)),
             render_function: "renderPage".to_string(),
+            ..Default::default()
         });
 
         let result = pool
@@ -101,6 +105,128 @@ mod pool_timeout_tests {
             other => panic!("Expected timeout error, got {:?}", other),
         }
     }
+
+    #[tokio::test]
+    async fn test_stats_after_failed_enqueue() {
+        let pool = V8Pool::new_stub_with(V8PoolConfig {
+            num_threads: 0,
+            queue_capacity: 0,
+            pin_threads: false,
+            request_timeout: Some(Duration::from_millis(5)),
+            ..Default::default()
+        });
+
+        let result = pool
+            .render_with_data("/timeout".to_string(), "{}".to_string())
+            .await;
+        assert!(matches!(result, Err(PoolError::Timeout)));
+
+        let stats = pool.stats();
+        assert_eq!(stats.queue_depth, 0);
+        assert_eq!(
+            stats.last_error.as_deref(),
+            Some("Timed out waiting for a free V8 worker")
+        );
+
+        // A caller that gives up while waiting for room is uncounted too
+        let pool = V8Pool::new_stub_with(V8PoolConfig {
+            num_threads: 0,
+            queue_capacity: 0,
+            pin_threads: false,
+            request_timeout: None,
+            ..Default::default()
+        });
+        let render = pool.render_with_data("/slow".to_string(), "{}".to_string());
+        assert!(tokio::time::timeout(Duration::from_millis(20), render)
+            .await
+            .is_err());
+        assert_eq!(pool.stats().queue_depth, 0);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_render_entry_rejected() {
+        let pool = V8Pool::new_stub_with(V8PoolConfig {
+            num_threads: 0,
+            queue_capacity: 0,
+            render_entries: vec!["renderEmail".to_string()],
+            request_timeout: Some(Duration::from_millis(5)),
+            ..Default::default()
+        });
+
+        let result = pool
+            .render_entry("renderWidget", "/".to_string(), "{}".to_string())
+            .await;
+
+        match result {
+            Err(PoolError::UnknownEntry(name)) => assert_eq!(name, "renderWidget"),
+            other => panic!("Expected unknown entry error, got {:?}", other),
+        }
+
+        // Allowed entries reach the queue (and time out on the stub)
+        let result = pool
+            .render_entry("renderEmail", "/".to_string(), "{}".to_string())
+            .await;
+        assert!(matches!(result, Err(PoolError::Timeout)));
+    }
+
+    #[tokio::test]
+    async fn test_render_entry_honors_deadline() {
+        let pool = V8Pool::new_stub_with(V8PoolConfig {
+            num_threads: 0,
+            queue_capacity: 0,
+            render_entries: vec!["renderEmail".to_string()],
+            request_timeout: None,
+            ..Default::default()
+        });
+        let options = RenderOptions {
+            deadline: Some(std::time::Instant::now() + Duration::from_millis(5)),
+            ..Default::default()
+        };
+
+        // Without the deadline, the stub would wait for room forever
+        let result = pool
+            .render_entry_with_options("renderEmail", "/".to_string(), "{}".to_string(), options)
+            .await;
+        assert!(matches!(result, Err(PoolError::Timeout)), "{:?}", result);
+    }
+
+    #[tokio::test]
+    async fn test_full_queue_sheds_lower_priority() {
+        let pool = V8Pool::new_stub_with(V8PoolConfig {
+            num_threads: 0,
+            queue_capacity: 1,
+            request_timeout: Some(Duration::from_millis(5)),
+            ..Default::default()
+        });
+        let options = |priority| RenderOptions {
+            priority,
+            ..Default::default()
+        };
+
+        // The interactive request takes the background request's place
+        let (background, _) = tokio::join!(
+            pool.render_page_with_options(
+                "/sitemap".to_string(),
+                "{}".to_string(),
+                options(Priority::Background)
+            ),
+            tokio::time::timeout(
+                Duration::from_millis(20),
+                pool.render_page_with_options(
+                    "/home".to_string(),
+                    "{}".to_string(),
+                    options(Priority::Interactive)
+                )
+            ),
+        );
+        assert!(matches!(background, Err(PoolError::Shed)));
+
+        // Nothing less important is left to shed for a bot
+        let bot = pool
+            .render_page_with_options("/".to_string(), "{}".to_string(), options(Priority::Bot))
+            .await;
+        assert!(matches!(bot, Err(PoolError::Timeout)));
+    }
 }
 
 // ============================================================================
@@ -405,8 +531,9 @@ mod thread_safety_tests {
 
 #[cfg(all(test, feature = "v8-pool", feature = "cache"))]
 mod v8_render_tests {
-    use rusty_ssr::SsrEngine;
+    use rusty_ssr::{RenderOptions, SsrEngine, SsrError};
     use std::sync::OnceLock;
+    use std::time::Instant;
 
     const TEST_BUNDLE: &str = r#"
         globalThis.renderPage = async function(url, data) {
@@ -459,6 +586,21 @@ mod v8_render_tests {
         assert!(html.contains("42"));
     }
 
+    #[tokio::test]
+    async fn test_render_past_deadline_skipped() {
+        let engine = get_engine();
+        let options = RenderOptions {
+            deadline: Some(Instant::now()),
+            ..Default::default()
+        };
+
+        let result = engine
+            .render_uncached_with_options("/expired", "{}", options)
+            .await;
+        assert!(matches!(result, Err(SsrError::Timeout)), "{:?}", result);
+        assert!(engine.pool_stats().expired >= 1);
+    }
+
     #[tokio::test]
     async fn test_render_with_empty_data() {
         let engine = get_engine();
@@ -0,0 +1,54 @@
+//! Cross-instance Invalidation Tests for Rusty-SSR
+//!
+//! Run with: `cargo test --test invalidation_tests`
+
+#[cfg(all(test, feature = "v8-pool", feature = "cache"))]
+mod invalidation_tests {
+    use rusty_ssr::cache::InProcessBus;
+    use rusty_ssr::SsrEngine;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    const TEST_BUNDLE: &str = r#"
+        globalThis.renderPage = async function(url, data) {
+            return '<html><body>' + url + '</body></html>';
+        };
+    "#;
+
+    #[tokio::test]
+    async fn test_invalidation_reaches_every_replica() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundle_path = dir.path().join("invalidation-bundle.js");
+        std::fs::write(&bundle_path, TEST_BUNDLE).unwrap();
+
+        let bus = Arc::new(InProcessBus::new());
+        let replica = || {
+            SsrEngine::builder()
+                .bundle_path(&bundle_path)
+                .pool_size(1)
+                .cache_size(100)
+                .invalidation_bus(bus.clone())
+                .build_engine()
+                .expect("Failed to create test engine")
+        };
+        let replica_a = replica();
+        let replica_b = replica();
+
+        for engine in [&replica_a, &replica_b] {
+            engine.render("/blog/1").await.unwrap();
+            engine.render("/about").await.unwrap();
+        }
+
+        // e.g. a CMS webhook hitting replica A
+        replica_a.invalidate_prefix("/blog");
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while replica_b.cache().contains("/blog/1") {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("invalidation did not reach replica B");
+        assert!(replica_b.cache().contains("/about"));
+    }
+}
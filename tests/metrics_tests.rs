@@ -0,0 +1,168 @@
+//! Prometheus Metrics Tests for Rusty-SSR
+//!
+//! Run with: `cargo test --test metrics_tests`
+
+#[cfg(all(
+    test,
+    feature = "v8-pool",
+    feature = "cache",
+    feature = "axum-integration"
+))]
+mod metrics_tests {
+    use axum::body::{to_bytes, Body};
+    use axum::http::{header, Request, StatusCode};
+    use axum::{routing::get, Router};
+    use rusty_ssr::cache::RoutePolicy;
+    use rusty_ssr::middleware::metrics_handler;
+    use rusty_ssr::SsrEngine;
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    const TEST_BUNDLE: &str = r#"
+        globalThis.renderPage = async function(url, data) {
+            if (url.includes('fail')) {
+                throw new Error('render failed');
+            }
+            return '<html><body><h1>' + url + '</h1></body></html>';
+        };
+    "#;
+
+    /// Value of the sample with exactly this name and label set
+    fn sample(text: &str, series: &str) -> f64 {
+        text.lines()
+            .find_map(|line| line.strip_prefix(series)?.strip_prefix(' '))
+            .unwrap_or_else(|| panic!("missing {} in:\n{}", series, text))
+            .parse()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_metrics_endpoint() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundle_path = dir.path().join("metrics-bundle.js");
+        std::fs::write(&bundle_path, TEST_BUNDLE).unwrap();
+
+        let engine = Arc::new(
+            SsrEngine::builder()
+                .bundle_path(&bundle_path)
+                .pool_size(1)
+                .cache_size(100)
+                .cache_route("/products/:id", RoutePolicy::new())
+                .build_engine()
+                .expect("Failed to create test engine"),
+        );
+        for url in ["/products/1", "/products/1", "/products/2", "/about"] {
+            engine.render(url).await.unwrap();
+        }
+        assert!(engine
+            .v8_pool()
+            .try_render_page("/fail".to_string(), "{}".to_string())
+            .await
+            .is_err());
+        engine
+            .metrics_registry()
+            .counter("app_signups_total", "Completed signups")
+            .inc();
+
+        let app = Router::new()
+            .route("/metrics", get(metrics_handler))
+            .with_state(Arc::clone(&engine));
+        let response = app
+            .oneshot(Request::get("/metrics").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "text/plain; version=0.0.4; charset=utf-8"
+        );
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let text = String::from_utf8(bytes.to_vec()).unwrap();
+
+        // Render latency is split by cache route
+        assert_eq!(
+            sample(
+                &text,
+                r#"ssr_render_duration_seconds_count{route="/products/:id"}"#
+            ),
+            2.0
+        );
+        assert_eq!(
+            sample(&text, r#"ssr_render_duration_seconds_count{route="other"}"#),
+            1.0
+        );
+        assert!(text.contains("# TYPE ssr_render_duration_seconds histogram\n"));
+
+        // Pool metrics
+        assert_eq!(sample(&text, "ssr_pool_queue_wait_seconds_count"), 4.0);
+        assert_eq!(sample(&text, "ssr_pool_render_duration_seconds_count"), 4.0);
+        assert_eq!(sample(&text, "ssr_pool_queue_depth"), 0.0);
+        assert_eq!(sample(&text, r#"ssr_pool_workers{state="idle"}"#), 1.0);
+        assert_eq!(
+            sample(&text, r#"ssr_pool_errors_total{kind="render"}"#),
+            1.0
+        );
+        assert!(text.contains(r#"ssr_isolate_heap_used_bytes{worker="0"}"#));
+
+        // Cache metrics
+        assert_eq!(sample(&text, r#"ssr_cache_hits_total{tier="hot"}"#), 1.0);
+        assert_eq!(
+            sample(&text, r#"ssr_cache_misses_total{tier="local"}"#),
+            3.0
+        );
+        assert_eq!(sample(&text, r#"ssr_cache_entries{tier="cold"}"#), 3.0);
+        assert!(sample(&text, r#"ssr_cache_bytes{tier="cold"}"#) > 0.0);
+        assert_eq!(
+            sample(
+                &text,
+                r#"ssr_cache_route_lookups_total{route="/products/:id",outcome="miss"}"#
+            ),
+            2.0
+        );
+
+        // Application metrics
+        assert_eq!(sample(&text, "app_signups_total"), 1.0);
+    }
+
+    #[tokio::test]
+    async fn test_pool_stats() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundle_path = dir.path().join("stats-bundle.js");
+        std::fs::write(&bundle_path, TEST_BUNDLE).unwrap();
+
+        let engine = SsrEngine::builder()
+            .bundle_path(&bundle_path)
+            .pool_size(2)
+            .build_engine()
+            .expect("Failed to create test engine");
+        for i in 0..6 {
+            engine
+                .render_uncached(&format!("/page/{}", i), "{}")
+                .await
+                .unwrap();
+        }
+        let _ = engine
+            .v8_pool()
+            .try_render_page("/fail".to_string(), "{}".to_string())
+            .await;
+
+        let stats = engine.pool_stats();
+        assert_eq!(stats.queue_depth, 0);
+        assert_eq!(stats.in_flight, 0);
+        assert_eq!(stats.restarts, 0);
+        assert!(stats.render_time_p50 <= stats.render_time_p95);
+        assert!(stats.render_time_p95 <= stats.render_time_p99);
+        assert!(stats.last_error.unwrap().contains("render failed"));
+
+        assert_eq!(stats.workers.len(), 2);
+        assert_eq!(stats.workers.iter().map(|w| w.processed).sum::<u64>(), 7);
+        for worker in &stats.workers {
+            assert!(worker.alive && !worker.busy);
+            assert!(worker.heap_used_bytes > 0);
+        }
+        assert!(stats.workers.iter().any(|w| w
+            .last_error
+            .as_deref()
+            .is_some_and(|e| e.contains("render failed"))));
+    }
+}
@@ -0,0 +1,133 @@
+//! ES Module Bundle Tests for Rusty-SSR
+//!
+//! Run with: `cargo test --test module_bundle_tests`
+//!
+//! The SSR bundle is process-global, so module bundles are tested in their
+//! own test binary, separate from the script bundle in `integration_tests`.
+
+#[cfg(all(test, feature = "v8-pool", feature = "cache"))]
+mod module_render_tests {
+    use rusty_ssr::{BundleFormat, SsrEngine};
+    use std::sync::OnceLock;
+
+    const ENTRY: &str = r#"
+        import { layout } from './lib/layout.js';
+
+        export async function renderPage(url, data) {
+            if (url === '/lazy') {
+                const { Lazy } = await import('./chunks/Lazy.js');
+                return layout(Lazy());
+            }
+            if (url === '/unrelated') {
+                return String(globalThis.__unrelatedLoaded === true);
+            }
+            if (url === '/escape') {
+                const outside = await import('../outside.js');
+                return 'escaped: ' + outside.secret;
+            }
+            return layout('<h1>' + url + '</h1>');
+        }
+    "#;
+
+    const LAYOUT: &str = r#"
+        export function layout(body) {
+            return '<html><body>' + body + '</body></html>';
+        }
+    "#;
+
+    const LAZY_CHUNK: &str = r#"
+        import { wrap } from './shared.js';
+
+        export function Lazy() {
+            return wrap('lazy');
+        }
+    "#;
+
+    const SHARED_CHUNK: &str = r#"
+        export function wrap(text) {
+            return '<section>' + text + '</section>';
+        }
+    "#;
+
+    /// Never imported by the entry, so never evaluated
+    const UNRELATED: &str = r#"
+        globalThis.__unrelatedLoaded = true;
+    "#;
+
+    fn get_engine() -> &'static SsrEngine {
+        static ENGINE: OnceLock<SsrEngine> = OnceLock::new();
+        ENGINE.get_or_init(|| {
+            // Workers load imports on their own threads, so keep the directory alive
+            let dir = Box::leak(Box::new(tempfile::tempdir().unwrap())).path();
+            let bundle_dir = dir.join("bundle");
+            std::fs::create_dir_all(bundle_dir.join("lib")).unwrap();
+            std::fs::write(bundle_dir.join("entry.mjs"), ENTRY).unwrap();
+            std::fs::write(bundle_dir.join("lib/layout.js"), LAYOUT).unwrap();
+            std::fs::create_dir_all(bundle_dir.join("chunks")).unwrap();
+            std::fs::write(bundle_dir.join("chunks/Lazy.js"), LAZY_CHUNK).unwrap();
+            std::fs::write(bundle_dir.join("chunks/shared.js"), SHARED_CHUNK).unwrap();
+            std::fs::write(bundle_dir.join("unrelated.js"), UNRELATED).unwrap();
+            std::fs::write(dir.join("outside.js"), "export const secret = 1;").unwrap();
+
+            SsrEngine::builder()
+                .bundle_path(bundle_dir.join("entry.mjs"))
+                .bundle_format(BundleFormat::Module)
+                .preload_chunks(true)
+                .chunk_base_url("/assets/")
+                .pool_size(2)
+                .cache_size(100)
+                .build_engine()
+                .expect("Failed to create module engine")
+        })
+    }
+
+    #[tokio::test]
+    async fn test_module_render_with_import() {
+        let engine = get_engine();
+        let html = engine.render("/esm").await.unwrap();
+
+        assert_eq!(&*html, "<html><body><h1>/esm</h1></body></html>");
+    }
+
+    #[tokio::test]
+    async fn test_lazy_chunk_reported() {
+        let engine = get_engine();
+
+        // Twice: the second import is served from the isolate's module cache
+        for _ in 0..2 {
+            let page = engine.render_page("/lazy", "{}").await.unwrap();
+            assert_eq!(
+                page.chunks,
+                vec!["chunks/Lazy.js".to_string(), "chunks/shared.js".to_string()]
+            );
+            assert!(page.html.contains("<section>lazy</section>"));
+            assert!(page.html.starts_with(concat!(
+                r#"<link rel="modulepreload" href="/assets/chunks/Lazy.js">"#,
+                r#"<link rel="modulepreload" href="/assets/chunks/shared.js">"#
+            )));
+        }
+
+        let page = engine.render_page("/esm", "{}").await.unwrap();
+        assert!(page.chunks.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_unrelated_files_not_preloaded() {
+        let engine = get_engine();
+        let html = engine.render_uncached("/unrelated", "{}").await.unwrap();
+
+        assert_eq!(html, "false", "files the entry never imports must not run");
+    }
+
+    #[tokio::test]
+    async fn test_import_outside_bundle_dir_rejected() {
+        let engine = get_engine();
+        let html = engine.render_uncached("/escape", "{}").await.unwrap();
+
+        assert!(
+            !html.contains("escaped"),
+            "import outside the bundle dir must fail"
+        );
+        assert!(html.contains("outside the module sandbox"));
+    }
+}
@@ -0,0 +1,63 @@
+//! Cache Pre-warming Tests for Rusty-SSR
+//!
+//! Run with: `cargo test --test prewarm_tests`
+
+#[cfg(all(test, feature = "v8-pool", feature = "cache"))]
+mod prewarm_tests {
+    use rusty_ssr::SsrEngine;
+    use std::sync::Arc;
+
+    const TEST_BUNDLE: &str = r#"
+        globalThis.renderPage = async function(url, data) {
+            if (url === '/broken') {
+                throw new Error('render failed');
+            }
+            return '<html><body>' + url + '</body></html>';
+        };
+    "#;
+
+    const SITEMAP: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+          <url><loc>https://example.com/</loc></url>
+          <url><loc>https://example.com/products</loc></url>
+          <url><loc>https://example.com/cached</loc></url>
+          <url><loc>https://example.com/broken</loc></url>
+        </urlset>"#;
+
+    #[tokio::test]
+    async fn test_prewarm_from_sitemap() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundle_path = dir.path().join("prewarm-bundle.js");
+        let sitemap_path = dir.path().join("sitemap.xml");
+        std::fs::write(&bundle_path, TEST_BUNDLE).unwrap();
+        std::fs::write(&sitemap_path, SITEMAP).unwrap();
+
+        let engine = Arc::new(
+            SsrEngine::builder()
+                .bundle_path(&bundle_path)
+                .pool_size(2)
+                .prewarm_concurrency(1)
+                .cache_size(100)
+                .build_engine()
+                .expect("Failed to create test engine"),
+        );
+        engine.render("/cached").await.unwrap();
+
+        let urls = rusty_ssr::prewarm::load_sitemap(&sitemap_path).unwrap();
+        let handle = engine.prewarm(urls);
+        assert_eq!(handle.progress().total(), 4);
+
+        let report = handle.wait().await;
+        assert_eq!(report.total, 4);
+        assert_eq!(report.rendered, 2);
+        assert_eq!(report.skipped, 1);
+        assert_eq!(report.failures.len(), 1);
+        assert_eq!(report.failures[0].0, "/broken");
+        assert!(report.failures[0].1.contains("render failed"));
+
+        let cache = engine.cache();
+        assert!(cache.contains("/"));
+        assert!(cache.contains("/products"));
+        assert!(!cache.contains("/broken"), "error pages must not be cached");
+    }
+}
@@ -0,0 +1,134 @@
+//! Render Entry Tests for Rusty-SSR
+//!
+//! Run with: `cargo test --test render_entry_tests`
+//!
+//! The bundle exposes several render functions, called through
+//! `SsrEngine::render_entry` and cached per entry and render data.
+
+#[cfg(all(test, feature = "v8-pool", feature = "cache"))]
+mod render_entry_tests {
+    use rusty_ssr::{SsrEngine, SsrError};
+    use std::path::PathBuf;
+    use std::sync::OnceLock;
+
+    const TEST_BUNDLE: &str = r#"
+        globalThis.renderPage = async (url, data) => '<html>' + url + '</html>';
+        globalThis.renderEmail = async (url, data) => 'email:' + url + ':' + data.name;
+        globalThis.renderWidget = async (url, data) => '<div>' + url + '</div>';
+    "#;
+
+    fn bundle_path() -> &'static PathBuf {
+        static PATH: OnceLock<PathBuf> = OnceLock::new();
+        PATH.get_or_init(|| {
+            let dir = Box::leak(Box::new(tempfile::tempdir().unwrap())).path();
+            let path = dir.join("entries-bundle.js");
+            std::fs::write(&path, TEST_BUNDLE).unwrap();
+            path
+        })
+    }
+
+    fn get_engine() -> &'static SsrEngine {
+        static ENGINE: OnceLock<SsrEngine> = OnceLock::new();
+        ENGINE.get_or_init(|| {
+            SsrEngine::builder()
+                .bundle_path(bundle_path())
+                .render_entry("renderEmail")
+                .render_entry("renderWidget")
+                .pool_size(2)
+                .cache_size(100)
+                .build_engine()
+                .expect("Failed to create test engine")
+        })
+    }
+
+    #[tokio::test]
+    async fn test_render_entries() {
+        let engine = get_engine();
+
+        let email = engine
+            .render_entry("renderEmail", "/welcome", r#"{"name":"Ann"}"#)
+            .await
+            .unwrap();
+        assert_eq!(&*email, "email:/welcome:Ann");
+
+        let widget = engine
+            .render_entry("renderWidget", "/cart", "{}")
+            .await
+            .unwrap();
+        assert_eq!(&*widget, "<div>/cart</div>");
+
+        let page = engine
+            .render_entry("renderPage", "/cart", "{}")
+            .await
+            .unwrap();
+        assert_eq!(&*page, "<html>/cart</html>");
+    }
+
+    #[tokio::test]
+    async fn test_entries_cached_separately() {
+        let engine = get_engine();
+
+        let page = engine.render("/shared").await.unwrap();
+        let widget = engine
+            .render_entry("renderWidget", "/shared", "{}")
+            .await
+            .unwrap();
+
+        assert_eq!(&*page, "<html>/shared</html>");
+        assert_eq!(&*widget, "<div>/shared</div>");
+
+        // Served from cache, still per entry
+        assert_eq!(&*engine.render("/shared").await.unwrap(), &*page);
+        assert_eq!(
+            &*engine
+                .render_entry("renderWidget", "/shared", "{}")
+                .await
+                .unwrap(),
+            &*widget
+        );
+    }
+
+    #[tokio::test]
+    async fn test_entries_cached_per_data() {
+        let engine = get_engine();
+
+        let ann = engine
+            .render_entry("renderEmail", "/receipt", r#"{"name":"Ann"}"#)
+            .await
+            .unwrap();
+        let bob = engine
+            .render_entry("renderEmail", "/receipt", r#"{"name":"Bob"}"#)
+            .await
+            .unwrap();
+
+        assert_eq!(&*ann, "email:/receipt:Ann");
+        assert_eq!(&*bob, "email:/receipt:Bob");
+    }
+
+    #[tokio::test]
+    async fn test_unlisted_entry_rejected() {
+        let result = get_engine().render_entry("renderSitemap", "/", "{}").await;
+
+        assert!(
+            matches!(result, Err(SsrError::UnknownRenderEntry(name)) if name == "renderSitemap")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_missing_entry_fails_at_startup() {
+        let result = SsrEngine::builder()
+            .bundle_path(bundle_path())
+            .render_entry("renderSitemap")
+            .pool_size(1)
+            .cache_size(10)
+            .build_engine();
+
+        match result {
+            Err(SsrError::BundleLoad(msg)) => {
+                assert!(msg.contains("globalThis.renderSitemap"), "{}", msg)
+            }
+            Err(other) => panic!("Expected bundle load error, got {}", other),
+            Ok(_) => panic!("Expected bundle load error"),
+        }
+    }
+}
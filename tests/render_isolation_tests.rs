@@ -0,0 +1,94 @@
+//! Render Isolation Tests for Rusty-SSR
+//!
+//! Run with: `cargo test --test render_isolation_tests`
+//!
+//! Render A stores user data in globals, storage, the `document` mock and a
+//! module-level variable; render B on the same worker reports what it can see.
+//! `/evaluation` reports a value computed when the bundle was evaluated.
+
+#[cfg(all(test, feature = "v8-pool", feature = "cache"))]
+mod render_isolation_tests {
+    use rusty_ssr::{RenderIsolation, SsrEngine};
+
+    const TEST_BUNDLE: &str = r#"
+        let renders = 0;
+        const evaluationId = Math.random().toString(36).slice(2);
+
+        globalThis.renderPage = async function(url, data) {
+            renders += 1;
+            if (url === '/evaluation') {
+                return evaluationId;
+            }
+            if (url === '/write') {
+                globalThis.currentUser = data.user;
+                localStorage.setItem('user', data.user);
+                document.title = data.user;
+                return 'written';
+            }
+            return [
+                globalThis.currentUser ?? '-',
+                localStorage.getItem('user') ?? '-',
+                document.title ?? '-',
+                renders,
+            ].join('|');
+        };
+    "#;
+
+    fn engine(isolation: RenderIsolation) -> SsrEngine {
+        let dir = tempfile::tempdir().unwrap();
+        let bundle_path = dir.path().join("isolation-bundle.js");
+        std::fs::write(&bundle_path, TEST_BUNDLE).unwrap();
+
+        // One worker, so both renders hit the same isolate
+        SsrEngine::builder()
+            .bundle_path(&bundle_path)
+            .pool_size(1)
+            .cache_size(100)
+            .render_isolation(isolation)
+            .build_engine()
+            .expect("Failed to create test engine")
+    }
+
+    async fn write_then_read(engine: &SsrEngine) -> String {
+        let written = engine
+            .render_uncached("/write", r#"{"user":"alice"}"#)
+            .await
+            .unwrap();
+        assert_eq!(written, "written");
+
+        engine.render_uncached("/read", "{}").await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_shared_runtime_leaks_state() {
+        let engine = engine(RenderIsolation::Shared);
+
+        assert_eq!(write_then_read(&engine).await, "alice|alice|alice|2");
+    }
+
+    #[tokio::test]
+    async fn test_reset_globals_hides_previous_render() {
+        let engine = engine(RenderIsolation::ResetGlobals);
+
+        // Module-level state is out of scope for this mode
+        assert_eq!(write_then_read(&engine).await, "-|-|-|2");
+    }
+
+    #[tokio::test]
+    async fn test_fresh_runtime_hides_previous_render() {
+        let engine = engine(RenderIsolation::FreshRuntime);
+
+        assert_eq!(write_then_read(&engine).await, "-|-|-|1");
+    }
+
+    #[tokio::test]
+    async fn test_fresh_runtime_evaluates_bundle_once() {
+        let engine = engine(RenderIsolation::FreshRuntime);
+
+        // Every isolate is restored from the same snapshot, so the bundle's
+        // top-level code is not run again per render
+        let first = engine.render_uncached("/evaluation", "{}").await.unwrap();
+        let second = engine.render_uncached("/evaluation", "{}").await.unwrap();
+        assert_eq!(first, second);
+    }
+}
@@ -0,0 +1,219 @@
+//! Tracing Span Tests for Rusty-SSR
+//!
+//! Run with: `cargo test --test tracing_tests`
+
+#[cfg(all(test, feature = "v8-pool", feature = "cache"))]
+mod tracing_tests {
+    use rusty_ssr::cache::RoutePolicy;
+    use rusty_ssr::SsrEngine;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Subscriber};
+    use tracing_subscriber::layer::{Context, SubscriberExt};
+    use tracing_subscriber::registry::LookupSpan;
+    use tracing_subscriber::Layer;
+
+    const TEST_BUNDLE: &str = r#"
+        globalThis.renderPage = async function(url, data) {
+            const trace = globalThis.__rustySsrTrace;
+            trace.event('render.start', { url });
+            const body = await trace.span('loadData', { source: 'test' }, async () => {
+                await null;
+                return trace.span('formatBody', () => {
+                    trace.event('body.format', {});
+                    return '<h1>' + url + '</h1>';
+                });
+            });
+            return '<html><body>' + body + '</body></html>';
+        };
+    "#;
+
+    #[derive(Debug, Clone)]
+    struct CapturedSpan {
+        name: String,
+        parent: Option<String>,
+        fields: HashMap<String, String>,
+    }
+
+    #[derive(Default)]
+    struct Fields(HashMap<String, String>);
+
+    impl Visit for Fields {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.insert(field.name().to_string(), value.to_string());
+        }
+
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            self.0
+                .insert(field.name().to_string(), format!("{:?}", value));
+        }
+    }
+
+    /// Collects closed spans and events with the name of their parent span
+    ///
+    /// Parents named by the bundle are reported by their `otel.name`.
+    #[derive(Clone, Default)]
+    struct Capture {
+        spans: Arc<Mutex<Vec<CapturedSpan>>>,
+        events: Arc<Mutex<Vec<CapturedSpan>>>,
+    }
+
+    impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for Capture {
+        fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+            let mut fields = Fields::default();
+            attrs.record(&mut fields);
+            ctx.span(id).unwrap().extensions_mut().insert(fields);
+        }
+
+        fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+            let span = ctx.span(id).unwrap();
+            let mut extensions = span.extensions_mut();
+            values.record(extensions.get_mut::<Fields>().unwrap());
+        }
+
+        fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+            let mut fields = Fields::default();
+            event.record(&mut fields);
+            self.events.lock().unwrap().push(CapturedSpan {
+                name: event.metadata().target().to_string(),
+                parent: ctx.event_span(event).map(|span| display_name(&span)),
+                fields: fields.0,
+            });
+        }
+
+        fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+            let span = ctx.span(&id).unwrap();
+            let fields = span.extensions_mut().remove::<Fields>().unwrap();
+            self.spans.lock().unwrap().push(CapturedSpan {
+                name: span.name().to_string(),
+                parent: span.parent().map(|parent| display_name(&parent)),
+                fields: fields.0,
+            });
+        }
+    }
+
+    fn display_name<S: for<'a> LookupSpan<'a>>(
+        span: &tracing_subscriber::registry::SpanRef<'_, S>,
+    ) -> String {
+        span.extensions()
+            .get::<Fields>()
+            .and_then(|fields| fields.0.get("otel.name").cloned())
+            .unwrap_or_else(|| span.name().to_string())
+    }
+
+    impl Capture {
+        fn spans(&self, name: &str) -> Vec<CapturedSpan> {
+            let spans = self.spans.lock().unwrap();
+            spans.iter().filter(|s| s.name == name).cloned().collect()
+        }
+    }
+
+    fn create_engine(dir: &tempfile::TempDir) -> SsrEngine {
+        let bundle_path = dir.path().join("tracing-bundle.js");
+        std::fs::write(&bundle_path, TEST_BUNDLE).unwrap();
+
+        SsrEngine::builder()
+            .bundle_path(&bundle_path)
+            .pool_size(1)
+            .cache_size(100)
+            .cache_route("/live/**", RoutePolicy::no_store())
+            .build_engine()
+            .expect("Failed to create test engine")
+    }
+
+    #[tokio::test]
+    async fn test_render_span_tree() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine = create_engine(&dir);
+
+        let capture = Capture::default();
+        let subscriber = tracing_subscriber::registry().with(capture.clone());
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        engine.render("/products/1").await.unwrap();
+        engine.render("/products/1").await.unwrap();
+        engine.render("/live/feed").await.unwrap();
+
+        let renders = capture.spans("ssr.render");
+        let outcomes: Vec<&str> = renders.iter().map(|s| s.fields["cache"].as_str()).collect();
+        assert_eq!(outcomes, ["miss", "hit", "bypass"]);
+        assert_eq!(renders[0].fields["url"], "/products/1");
+
+        // Two renders reached V8, spanning the worker thread
+        for name in ["ssr.queue_wait", "ssr.v8_execute"] {
+            let spans = capture.spans(name);
+            assert_eq!(spans.len(), 2, "{}", name);
+            assert!(spans
+                .iter()
+                .all(|s| s.parent.as_deref() == Some("ssr.render")));
+        }
+        let serialize = capture.spans("ssr.serialize");
+        assert_eq!(serialize.len(), 2);
+        assert_eq!(serialize[0].parent.as_deref(), Some("ssr.v8_execute"));
+
+        // Only the cacheable page was inserted
+        let inserts = capture.spans("ssr.cache_insert");
+        assert_eq!(inserts.len(), 1);
+        assert_eq!(inserts[0].parent.as_deref(), Some("ssr.render"));
+    }
+
+    #[tokio::test]
+    async fn test_bundle_annotates_spans() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine = create_engine(&dir);
+
+        let capture = Capture::default();
+        let subscriber = tracing_subscriber::registry().with(capture.clone());
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let html = engine.render_uncached("/about", "{}").await.unwrap();
+        assert!(html.contains("<h1>/about</h1>"), "{}", html);
+
+        let js_spans = capture.spans("ssr.js");
+        let load = js_spans
+            .iter()
+            .find(|s| s.fields["otel.name"] == "loadData")
+            .expect("loadData span");
+        assert_eq!(load.fields["attributes"], r#"{"source":"test"}"#);
+        assert_eq!(load.parent.as_deref(), Some("ssr.v8_execute"));
+
+        let events = capture.events.lock().unwrap();
+        let event = events
+            .iter()
+            .find(|e| e.name == "rusty_ssr::js")
+            .expect("bundle event");
+        assert_eq!(event.fields["message"], "render.start");
+        assert_eq!(event.fields["attributes"], r#"{"url":"/about"}"#);
+        assert_eq!(event.parent.as_deref(), Some("ssr.v8_execute"));
+    }
+
+    #[tokio::test]
+    async fn test_nested_bundle_spans() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine = create_engine(&dir);
+
+        let capture = Capture::default();
+        let subscriber = tracing_subscriber::registry().with(capture.clone());
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        engine.render_uncached("/nested", "{}").await.unwrap();
+
+        // Opened after an await, when tracing's current span is the V8 span
+        let js_spans = capture.spans("ssr.js");
+        assert_eq!(js_spans.len(), 2);
+        let format = js_spans
+            .iter()
+            .find(|s| s.fields["otel.name"] == "formatBody")
+            .expect("formatBody span");
+        assert_eq!(format.parent.as_deref(), Some("loadData"));
+
+        let events = capture.events.lock().unwrap();
+        let event = events
+            .iter()
+            .find(|e| e.fields.get("message").map(String::as_str) == Some("body.format"))
+            .expect("nested event");
+        assert_eq!(event.parent.as_deref(), Some("formatBody"));
+    }
+}
@@ -0,0 +1,97 @@
+//! URL Normalization Tests for Rusty-SSR
+//!
+//! Run with: `cargo test --test url_normalizer_tests`
+
+#[cfg(all(test, feature = "v8-pool", feature = "cache"))]
+mod url_normalizer_tests {
+    use rusty_ssr::normalize::{TrailingSlash, UrlNormalizer};
+    use rusty_ssr::SsrEngine;
+
+    const TEST_BUNDLE: &str = r#"
+        globalThis.renderPage = async function(url, data) {
+            return '<html><body><h1>' + url + '</h1></body></html>';
+        };
+    "#;
+
+    fn create_engine(dir: &tempfile::TempDir) -> SsrEngine {
+        let bundle_path = dir.path().join("normalize-bundle.js");
+        std::fs::write(&bundle_path, TEST_BUNDLE).unwrap();
+
+        SsrEngine::builder()
+            .bundle_path(&bundle_path)
+            .pool_size(1)
+            .cache_size(100)
+            .url_normalizer(
+                UrlNormalizer::new()
+                    .trailing_slash(TrailingSlash::Strip)
+                    .lowercase_path()
+                    .deny_tracking_params(),
+            )
+            .build_engine()
+            .expect("Failed to create test engine")
+    }
+
+    #[tokio::test]
+    async fn test_url_variants_share_one_render() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine = create_engine(&dir);
+
+        for url in [
+            "/products/1",
+            "/products/1/",
+            "/products/1?utm_source=x",
+            "/PRODUCTS/1",
+        ] {
+            let html = engine.render(url).await.unwrap();
+            // The renderer sees the canonical URL
+            assert!(html.contains("<h1>/products/1</h1>"), "{}", url);
+        }
+
+        let metrics = engine.cache_metrics();
+        assert_eq!(metrics.misses, 1);
+        assert_eq!(metrics.insertions, 1);
+
+        engine.invalidate("/Products/1/");
+        assert!(engine.cache().try_get("/products/1").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_normalize_url_reports_redirects() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine = create_engine(&dir);
+
+        let normalized = engine.normalize_url("/About/?utm_source=x");
+        assert_eq!(normalized.url, "/about");
+        assert_eq!(normalized.redirect.as_deref(), Some("/about?utm_source=x"));
+
+        assert_eq!(engine.normalize_url("/about?utm_source=x").redirect, None);
+    }
+
+    #[cfg(all(feature = "axum-integration", feature = "cache-compression"))]
+    #[tokio::test]
+    async fn test_ssr_handler_redirects_to_canonical_path() {
+        use axum::body::Body;
+        use axum::extract::{Request, State};
+        use axum::http::{header, StatusCode};
+        use rusty_ssr::middleware::ssr_handler;
+        use std::sync::Arc;
+
+        let dir = tempfile::tempdir().unwrap();
+        let engine = Arc::new(create_engine(&dir));
+
+        let request = Request::builder()
+            .uri("/About/?ref=mail")
+            .body(Body::empty())
+            .unwrap();
+        let response = ssr_handler(State(Arc::clone(&engine)), request).await;
+        assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+        assert_eq!(response.headers()[header::LOCATION], "/about?ref=mail");
+
+        let request = Request::builder()
+            .uri("/about")
+            .body(Body::empty())
+            .unwrap();
+        let response = ssr_handler(State(engine), request).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,72 @@
+//! Startup Warm-up Tests for Rusty-SSR
+//!
+//! Run with: `cargo test --test warmup_tests`
+
+#[cfg(all(test, feature = "v8-pool", feature = "cache"))]
+mod warmup_tests {
+    use rusty_ssr::{SsrConfigBuilder, SsrEngine, SsrError};
+    use std::path::PathBuf;
+    use std::sync::OnceLock;
+
+    const TEST_BUNDLE: &str = r#"
+        globalThis.renderPage = async function(url, data) {
+            if (url === '/broken') {
+                throw new Error('products API unavailable');
+            }
+            return '<html><body>' + url + '</body></html>';
+        };
+    "#;
+
+    fn builder() -> SsrConfigBuilder {
+        static PATH: OnceLock<PathBuf> = OnceLock::new();
+        let path = PATH.get_or_init(|| {
+            let dir = Box::leak(Box::new(tempfile::tempdir().unwrap())).path();
+            let path = dir.join("warmup-bundle.js");
+            std::fs::write(&path, TEST_BUNDLE).unwrap();
+            path
+        });
+
+        SsrEngine::builder()
+            .bundle_path(path)
+            .pool_size(2)
+            .cache_size(100)
+    }
+
+    #[tokio::test]
+    async fn test_warmup_prefills_cache() {
+        let engine = builder()
+            .warmup_urls(["/", "/about"])
+            .build_engine()
+            .expect("Warm-up should succeed");
+
+        assert_eq!(
+            engine.cache().try_get("/about").as_deref(),
+            Some("<html><body>/about</body></html>")
+        );
+        assert!(engine.cache().try_get("/").is_some());
+        assert!(engine.cache().try_get("/contact").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_failing_warmup_fails_construction() {
+        let result = builder().warmup_urls(["/", "/broken"]).build_engine();
+
+        match result {
+            Err(SsrError::BundleLoad(msg)) => {
+                assert!(msg.contains("/broken"), "{}", msg);
+                assert!(msg.contains("products API unavailable"), "{}", msg);
+            }
+            Err(other) => panic!("Expected bundle load error, got {}", other),
+            Ok(_) => panic!("Expected bundle load error"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_error_page_without_warmup() {
+        // Outside warm-up, bundle errors still render the error page
+        let engine = builder().build_engine().unwrap();
+        let html = engine.render_uncached("/broken", "{}").await.unwrap();
+
+        assert!(html.contains("SSR Error"));
+    }
+}
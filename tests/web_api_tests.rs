@@ -0,0 +1,194 @@
+//! Web Platform API Tests for Rusty-SSR
+//!
+//! Run with: `cargo test --test web_api_tests`
+//!
+//! Each route of the test bundle exercises one web API inside the isolate
+//! and renders the result as plain text.
+
+#[cfg(all(test, feature = "v8-pool", feature = "cache"))]
+mod web_api_tests {
+    use rusty_ssr::SsrEngine;
+    use std::sync::OnceLock;
+
+    const TEST_BUNDLE: &str = r#"
+        const routes = {
+            '/url': () => {
+                const u = new URL('../c?b=2&a=1#top', 'https://user@example.com:8080/a/b/');
+                u.searchParams.append('q', 'x y');
+                return [u.href, u.origin, u.pathname, u.searchParams.get('a'), u.hash].join('|');
+            },
+            '/url-invalid': () => {
+                try { new URL('/no-base'); return 'parsed'; }
+                catch (e) { return e instanceof TypeError ? 'TypeError' : String(e); }
+            },
+            '/search-params': () => {
+                const p = new URLSearchParams('b=2&a=1&a=3&plus=a+b');
+                p.sort();
+                p.set('c', '&');
+                return [p.toString(), p.getAll('a').join(','), p.get('plus'), p.size].join('|');
+            },
+            '/text': () => {
+                const bytes = new TextEncoder().encode('héllo');
+                return bytes.length + '|' + new TextDecoder().decode(bytes);
+            },
+            '/encode-into': () => {
+                const dest = new Uint8Array(5);
+                const { read, written } = new TextEncoder().encodeInto('a😀b', dest);
+                return [read, written, dest[4]].join('|');
+            },
+            '/url-private': () => {
+                const url = new URL('https://example.com/?a=1');
+                return [
+                    typeof url._setSearchFromParams,
+                    typeof URLSearchParams._attach,
+                    typeof URLSearchParams._reparse,
+                ].join('|');
+            },
+            '/base64': () => {
+                let error = '';
+                try { btoa('✓'); } catch (e) { error = e.name; }
+                return [btoa('hello'), atob('aGVsbG8='), error].join('|');
+            },
+            '/random': () => {
+                const values = crypto.getRandomValues(new Uint32Array(8));
+                return String(values.some((v) => v !== 0));
+            },
+            '/uuid': () => crypto.randomUUID(),
+            '/clone': () => {
+                const original = { date: new Date(0), nested: { list: [1, 2] }, map: new Map([['k', 'v']]) };
+                const copy = structuredClone(original);
+                copy.nested.list.push(3);
+                return [
+                    original.nested.list.length,
+                    copy.nested.list.length,
+                    copy.date instanceof Date,
+                    copy.map.get('k'),
+                ].join('|');
+            },
+            '/abort': () => {
+                const controller = new AbortController();
+                let seen = '';
+                controller.signal.addEventListener('abort', () => { seen = 'abort'; });
+                controller.abort('stop');
+                return [controller.signal.aborted, controller.signal.reason, seen].join('|');
+            },
+            '/abort-default': () => {
+                const controller = new AbortController();
+                controller.abort();
+                const reason = controller.signal.reason;
+                return [reason instanceof DOMException, reason.name].join('|');
+            },
+            '/microtask': async () => {
+                const order = [];
+                queueMicrotask(() => order.push('micro'));
+                order.push('sync');
+                await Promise.resolve();
+                return order.join(',');
+            },
+        };
+
+        globalThis.renderPage = async function(url, data) {
+            const route = routes[url];
+            return route ? await route() : 'not found';
+        };
+    "#;
+
+    fn get_engine() -> &'static SsrEngine {
+        static ENGINE: OnceLock<SsrEngine> = OnceLock::new();
+        ENGINE.get_or_init(|| {
+            let dir = tempfile::tempdir().unwrap();
+            let bundle_path = dir.path().join("web-api-bundle.js");
+            std::fs::write(&bundle_path, TEST_BUNDLE).unwrap();
+
+            SsrEngine::builder()
+                .bundle_path(&bundle_path)
+                .pool_size(1)
+                .cache_size(100)
+                .build_engine()
+                .expect("Failed to create test engine")
+        })
+    }
+
+    async fn render(url: &str) -> String {
+        get_engine().render_uncached(url, "{}").await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_url() {
+        assert_eq!(
+            render("/url").await,
+            "https://user@example.com:8080/a/c?b=2&a=1&q=x+y#top|https://example.com:8080|/a/c|1|#top"
+        );
+        assert_eq!(render("/url-invalid").await, "TypeError");
+    }
+
+    #[tokio::test]
+    async fn test_url_search_params() {
+        assert_eq!(
+            render("/search-params").await,
+            "a=1&a=3&b=2&plus=a+b&c=%26|1,3|a b|5"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_text_encoder_decoder() {
+        assert_eq!(render("/text").await, "6|héllo");
+    }
+
+    #[tokio::test]
+    async fn test_text_encoder_encode_into_truncates() {
+        // 'a' and the surrogate pair fit in 5 bytes, 'b' does not
+        assert_eq!(render("/encode-into").await, "3|5|128");
+    }
+
+    #[tokio::test]
+    async fn test_url_internals_not_exposed() {
+        assert_eq!(
+            render("/url-private").await,
+            "undefined|undefined|undefined"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_atob_btoa() {
+        assert_eq!(
+            render("/base64").await,
+            "aGVsbG8=|hello|InvalidCharacterError"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_crypto_get_random_values() {
+        assert_eq!(render("/random").await, "true");
+    }
+
+    #[tokio::test]
+    async fn test_crypto_random_uuid() {
+        let uuid = render("/uuid").await;
+
+        assert_eq!(uuid.len(), 36);
+        assert_eq!(&uuid[14..15], "4", "UUID must be version 4");
+        assert!(matches!(&uuid[19..20], "8" | "9" | "a" | "b"));
+        assert_ne!(uuid, render("/uuid").await);
+    }
+
+    #[tokio::test]
+    async fn test_structured_clone() {
+        assert_eq!(render("/clone").await, "2|3|true|v");
+    }
+
+    #[tokio::test]
+    async fn test_abort_controller() {
+        assert_eq!(render("/abort").await, "true|stop|abort");
+    }
+
+    #[tokio::test]
+    async fn test_abort_controller_default_reason() {
+        assert_eq!(render("/abort-default").await, "true|AbortError");
+    }
+
+    #[tokio::test]
+    async fn test_queue_microtask() {
+        assert_eq!(render("/microtask").await, "sync,micro");
+    }
+}